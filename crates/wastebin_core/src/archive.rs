@@ -0,0 +1,238 @@
+//! Portable archive format used by `wastebin-ctl export` and `wastebin-ctl import`. An archive is
+//! a JSON Lines file starting with a [`Header`] line followed by one [`Record`] line per entry.
+//! Unencrypted content is stored as plain text, so that archives do not depend on how a particular
+//! instance compresses its data. Encrypted content is stored as-is.
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use chacha20poly1305::XNonce;
+use serde::{Deserialize, Serialize};
+
+use crate::codec::{self, Encoding};
+use crate::db::{RawContent, RawEntry};
+use crate::id::Id;
+
+/// Format identifier written to the [`Header`].
+pub const FORMAT: &str = "wastebin";
+
+/// Current archive version.
+pub const VERSION: u32 = 1;
+
+/// Archive related errors.
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("not a wastebin archive")]
+    Format,
+    #[error("unsupported archive version {0}, expected at most {VERSION}")]
+    Version(u32),
+    #[error("invalid base64: {0}")]
+    Base64(#[from] base64::DecodeError),
+    #[error("invalid nonce")]
+    Nonce,
+    #[error("failed to compress: {0}")]
+    Compression(#[from] std::io::Error),
+    #[error("content is not valid UTF-8")]
+    Utf8(#[from] std::string::FromUtf8Error),
+    #[error("content requires dictionary {0}")]
+    Dictionary(u32),
+}
+
+/// First line of an archive.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Header {
+    /// Always [`FORMAT`]
+    pub format: String,
+    /// Version the archive was written with
+    pub version: u32,
+}
+
+/// A single entry.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Record {
+    /// Identifier
+    pub id: Id,
+    /// User identifier that inserted the entry
+    #[serde(default)]
+    pub uid: Option<i64>,
+    /// Title
+    #[serde(default)]
+    pub title: Option<String>,
+    /// File extension
+    #[serde(default)]
+    pub extension: Option<String>,
+    /// Paste this entry was forked from
+    #[serde(default)]
+    pub parent: Option<Id>,
+    /// Creation datetime as `YYYY-MM-DD HH:MM:SS` in UTC
+    #[serde(default)]
+    pub created: Option<String>,
+    /// Expiration datetime as `YYYY-MM-DD HH:MM:SS` in UTC
+    #[serde(default)]
+    pub expires: Option<String>,
+    /// Entry is deleted after being viewed this many times, `1` for burn after reading
+    #[serde(default)]
+    pub max_views: Option<u32>,
+    /// Number of times the entry was viewed
+    #[serde(default)]
+    pub views: u32,
+    /// Current content
+    pub content: Content,
+    /// Content of previous revisions, oldest first
+    #[serde(default)]
+    pub revisions: Vec<Content>,
+}
+
+/// Content of an entry or one of its revisions. `multi_file` is set if the content holds the files
+/// of a multi-file paste, see [`crate::files`].
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Content {
+    /// Unencrypted content
+    Text {
+        text: String,
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        multi_file: bool,
+    },
+    /// Base64-encoded, compressed and encrypted data and its nonce
+    Encrypted {
+        data: String,
+        nonce: String,
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        multi_file: bool,
+    },
+}
+
+impl Default for Header {
+    fn default() -> Self {
+        Self {
+            format: FORMAT.to_string(),
+            version: VERSION,
+        }
+    }
+}
+
+impl Header {
+    /// Check that records following this header can be read.
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.format != FORMAT {
+            return Err(Error::Format);
+        }
+
+        if self.version > VERSION {
+            return Err(Error::Version(self.version));
+        }
+
+        Ok(())
+    }
+}
+
+impl TryFrom<RawContent> for Content {
+    type Error = Error;
+
+    fn try_from(
+        RawContent {
+            data,
+            nonce,
+            encoding,
+            multi_file,
+        }: RawContent,
+    ) -> Result<Self, Self::Error> {
+        // Archives must be readable without the dictionaries of a particular database.
+        if let Some(dictionary) = encoding.dictionary {
+            return Err(Error::Dictionary(dictionary));
+        }
+
+        Ok(match nonce {
+            None => Self::Text {
+                text: String::from_utf8(codec::decode(&data, encoding, None)?)?,
+                multi_file,
+            },
+            Some(nonce) => Self::Encrypted {
+                data: STANDARD.encode(data),
+                nonce: STANDARD.encode(nonce),
+                multi_file,
+            },
+        })
+    }
+}
+
+impl TryFrom<Content> for RawContent {
+    type Error = Error;
+
+    fn try_from(content: Content) -> Result<Self, Self::Error> {
+        Ok(match content {
+            Content::Text { text, multi_file } => {
+                let (data, encoding) = codec::encode(text.as_bytes(), codec::DEFAULT_LEVEL, None)?;
+
+                Self {
+                    data,
+                    nonce: None,
+                    encoding,
+                    multi_file,
+                }
+            }
+            Content::Encrypted {
+                data,
+                nonce,
+                multi_file,
+            } => Self {
+                data: STANDARD.decode(data)?,
+                nonce: Some(
+                    XNonce::try_from(STANDARD.decode(nonce)?.as_slice())
+                        .map_err(|_| Error::Nonce)?,
+                ),
+                // Encrypted content is always a zstd frame of unknown level.
+                encoding: Encoding::default(),
+                multi_file,
+            },
+        })
+    }
+}
+
+impl TryFrom<RawEntry> for Record {
+    type Error = Error;
+
+    fn try_from(entry: RawEntry) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: entry.id,
+            uid: entry.uid,
+            title: entry.title,
+            extension: entry.extension,
+            parent: entry.parent,
+            created: entry.created,
+            expires: entry.expires,
+            max_views: entry.max_views,
+            views: entry.views,
+            content: entry.content.try_into()?,
+            revisions: entry
+                .revisions
+                .into_iter()
+                .map(Content::try_from)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+impl TryFrom<Record> for RawEntry {
+    type Error = Error;
+
+    fn try_from(record: Record) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: record.id,
+            uid: record.uid,
+            title: record.title,
+            extension: record.extension,
+            parent: record.parent,
+            created: record.created,
+            expires: record.expires,
+            max_views: record.max_views,
+            views: record.views,
+            content: record.content.try_into()?,
+            revisions: record
+                .revisions
+                .into_iter()
+                .map(RawContent::try_from)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
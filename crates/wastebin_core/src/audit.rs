@@ -0,0 +1,86 @@
+//! Audit log of paste lifecycle events. Storage backends append a [`Record`] whenever an entry is
+//! created, restored or removed, so that it can be told afterwards why a paste is gone. Records are
+//! never changed or removed, not even together with the entry they refer to.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::id::Id;
+
+/// Error returned when parsing an unknown event name.
+#[derive(thiserror::Error, Debug)]
+#[error("unknown audit event `{0}`")]
+pub struct UnknownEvent(String);
+
+/// Lifecycle event of an entry.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Event {
+    /// Entry was inserted
+    Create,
+    /// Entry was removed because it reached its view limit
+    Burn,
+    /// Entry was deleted by its owner or an operator
+    Delete,
+    /// Entry was moved out of the trash
+    Restore,
+    /// Expired entry was removed when accessed
+    Expire,
+    /// Expired entry or entry whose trash period ended was purged
+    Purge,
+    /// Entry was evicted to stay within the storage quota
+    Evict,
+}
+
+/// A single audit log record.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Record {
+    /// Identifier of the affected entry
+    pub id: Id,
+    /// Owner of the affected entry
+    pub uid: Option<i64>,
+    /// What happened
+    pub event: Event,
+    /// Program or subcommand that caused the event, see [`crate::db::Config::actor`]
+    pub actor: String,
+    /// UTC time of the event formatted as `YYYY-MM-DD HH:MM:SS`
+    pub created: String,
+}
+
+impl Event {
+    /// Name as stored in the database.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Create => "create",
+            Self::Burn => "burn",
+            Self::Delete => "delete",
+            Self::Restore => "restore",
+            Self::Expire => "expire",
+            Self::Purge => "purge",
+            Self::Evict => "evict",
+        }
+    }
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Event {
+    type Err = UnknownEvent;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "create" => Ok(Self::Create),
+            "burn" => Ok(Self::Burn),
+            "delete" => Ok(Self::Delete),
+            "restore" => Ok(Self::Restore),
+            "expire" => Ok(Self::Expire),
+            "purge" => Ok(Self::Purge),
+            "evict" => Ok(Self::Evict),
+            _ => Err(UnknownEvent(s.to_string())),
+        }
+    }
+}
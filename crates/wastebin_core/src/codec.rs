@@ -0,0 +1,169 @@
+//! Codecs that content is stored with. The codec, compression level and dictionary are recorded
+//! for every stored piece of content, so that content written with past settings stays readable
+//! after the settings change.
+
+use std::io::Cursor;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+use crate::dictionary::Dictionary;
+
+/// Compression level used unless configured otherwise.
+pub const DEFAULT_LEVEL: i32 = zstd::DEFAULT_COMPRESSION_LEVEL;
+
+/// Error returned when parsing an unknown codec name.
+#[derive(thiserror::Error, Debug)]
+#[error("unknown codec `{0}`")]
+pub struct UnknownCodec(String);
+
+/// Format of stored content.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Codec {
+    /// zstd frame, optionally compressed with a trained [`Dictionary`]
+    #[default]
+    Zstd,
+    /// Plain content for pastes that zstd would make larger
+    Uncompressed,
+}
+
+/// How a particular piece of content is stored.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Encoding {
+    /// Codec
+    pub codec: Codec,
+    /// Compression level, `None` if unknown or not applicable
+    pub level: Option<i32>,
+    /// Dictionary the content was compressed with
+    pub dictionary: Option<u32>,
+}
+
+impl Codec {
+    /// Name as stored in the database.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Zstd => "zstd",
+            Self::Uncompressed => "none",
+        }
+    }
+}
+
+impl FromStr for Codec {
+    type Err = UnknownCodec;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "zstd" => Ok(Self::Zstd),
+            "none" => Ok(Self::Uncompressed),
+            _ => Err(UnknownCodec(s.to_string())),
+        }
+    }
+}
+
+impl Encoding {
+    /// Encoding of content that is stored as is.
+    pub const UNCOMPRESSED: Self = Self {
+        codec: Codec::Uncompressed,
+        level: None,
+        dictionary: None,
+    };
+
+    /// Encoding of content compressed with zstd at `level` and `dictionary`.
+    #[must_use]
+    pub fn zstd(level: i32, dictionary: Option<&Dictionary>) -> Self {
+        Self {
+            codec: Codec::Zstd,
+            level: Some(level),
+            dictionary: dictionary.map(|dictionary| dictionary.id),
+        }
+    }
+}
+
+/// Valid compression levels.
+#[must_use]
+pub fn levels() -> RangeInclusive<i32> {
+    zstd::compression_level_range()
+}
+
+/// Return `data` encoded with `encoding` unless it is not smaller than `text` itself in which case
+/// `text` is returned uncompressed.
+#[must_use]
+pub fn smaller(text: &[u8], data: Vec<u8>, encoding: Encoding) -> (Vec<u8>, Encoding) {
+    if data.len() < text.len() {
+        (data, encoding)
+    } else {
+        (text.to_vec(), Encoding::UNCOMPRESSED)
+    }
+}
+
+/// Compress `text` with zstd at `level` and `dictionary` or leave it uncompressed if that is
+/// smaller.
+pub fn encode(
+    text: &[u8],
+    level: i32,
+    dictionary: Option<&Dictionary>,
+) -> std::io::Result<(Vec<u8>, Encoding)> {
+    let data = match dictionary {
+        Some(dictionary) => {
+            zstd::bulk::Compressor::with_dictionary(level, &dictionary.data)?.compress(text)?
+        }
+        None => zstd::stream::encode_all(text, level)?,
+    };
+
+    Ok(smaller(text, data, Encoding::zstd(level, dictionary)))
+}
+
+/// Decode `data` stored with `encoding`. `dictionary` must be the one referenced by `encoding`.
+pub fn decode(
+    data: &[u8],
+    encoding: Encoding,
+    dictionary: Option<&Dictionary>,
+) -> std::io::Result<Vec<u8>> {
+    if encoding.dictionary != dictionary.map(|dictionary| dictionary.id) {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("content requires dictionary {:?}", encoding.dictionary),
+        ));
+    }
+
+    match (encoding.codec, dictionary) {
+        (Codec::Uncompressed, _) => Ok(data.to_vec()),
+        (Codec::Zstd, None) => zstd::stream::decode_all(Cursor::new(data)),
+        (Codec::Zstd, Some(dictionary)) => {
+            let mut decoder =
+                zstd::stream::Decoder::with_dictionary(Cursor::new(data), &dictionary.data)?;
+            let mut text = Vec::new();
+            std::io::copy(&mut decoder, &mut text)?;
+            Ok(text)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tiny_content_is_not_compressed() -> Result<(), Box<dyn std::error::Error>> {
+        let (data, encoding) = encode(b"hi", DEFAULT_LEVEL, None)?;
+        assert_eq!(encoding, Encoding::UNCOMPRESSED);
+        assert_eq!(decode(&data, encoding, None)?, b"hi");
+
+        let text = "hello world ".repeat(100);
+        let (data, encoding) = encode(text.as_bytes(), 19, None)?;
+        assert_eq!(encoding, Encoding::zstd(19, None));
+        assert!(data.len() < text.len());
+        assert_eq!(decode(&data, encoding, None)?, text.as_bytes());
+
+        Ok(())
+    }
+
+    #[test]
+    fn codec_names() {
+        for codec in [Codec::Zstd, Codec::Uncompressed] {
+            assert_eq!(codec.as_str().parse::<Codec>().ok(), Some(codec));
+        }
+
+        assert!("lz4".parse::<Codec>().is_err());
+    }
+}
@@ -1,28 +1,60 @@
-use std::io::Cursor;
-use std::path::PathBuf;
-use std::time::Duration;
+pub mod memory;
+pub mod sqlite;
 
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::num::NonZeroU64;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, PoisonError, RwLock};
+use std::time::{Duration, SystemTime};
+
+use async_compression::tokio::bufread::ZstdDecoder;
+use async_compression::tokio::write::ZstdEncoder;
 use chacha20poly1305::XNonce;
-use rusqlite::{Connection, Transaction, params, params_from_iter};
-use rusqlite_migration::{HookError, M, Migrations};
+use rand::RngExt;
+use sha2::{Digest, Sha256};
+use tokio::io::{AsyncBufRead, AsyncRead, AsyncReadExt, AsyncWriteExt};
 use tokio::sync::oneshot;
+use tokio_util::io::StreamReader;
 
+use crate::audit;
+use crate::codec::{self, Codec, Encoding};
 use crate::crypto::{self, Password};
-use crate::expiration::Expiration;
-use crate::id::Id;
-use read::{DatabaseEntry, ListEntry, Metadata};
+use crate::dictionary::{self, Dictionaries, Dictionary};
+use crate::id::{Generator, Id};
+use read::{BlobEntry, DatabaseEntry, ListEntry, Metadata, SearchEntry};
+
+/// Size of the chunks that streamed content is read from request bodies and blobs in.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Compressed content of a streamed insert is handed to the storage backend in chunks of at least
+/// this size.
+const UPLOAD_CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Number of bytes of streamed content that are put into the search index.
+pub const INDEXED_SIZE: usize = 1024 * 1024;
 
 /// Database related errors.
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
     #[error("not allowed to delete")]
     Delete,
+    #[error("not allowed to edit")]
+    Edit,
+    #[error("not allowed to restore")]
+    Restore,
     #[error("sqlite error: {0}")]
     Sqlite(rusqlite::Error),
     #[error("migrations error: {0}")]
     Migration(#[from] rusqlite_migration::Error),
     #[error("failed to compress: {0}")]
     Compression(String),
+    #[error("failed to read content: {0}")]
+    Read(std::io::Error),
+    #[error("content is not valid UTF-8: {0}")]
+    Utf8(#[from] std::str::Utf8Error),
     #[error("password not given")]
     NoPassword,
     #[error("entry not found")]
@@ -37,21 +69,45 @@ pub enum Error {
     ResultSendError,
     #[error("failed to send result: {0}")]
     ResultRecvError(#[from] oneshot::error::RecvError),
+    #[error("not supported by the storage backend")]
+    Unsupported,
+    #[error("unknown storage backend `{0}`")]
+    UnknownBackend(String),
+    #[error("entry already exists")]
+    Conflict,
+    #[error("storage quota exceeded")]
+    QuotaExceeded,
+    #[error("database schema version {current} differs from version {latest} of this build")]
+    Schema { current: usize, latest: usize },
+    #[error("unknown schema version {0}")]
+    UnknownVersion(i64),
+    #[error("migration {0} cannot be reverted")]
+    Irreversible(&'static str),
 }
 
 /// The programmatic database interface. However, database calls are not translated directly to
-/// sqlite but moved forward to a [`Handler`] which reads database commands from a queue and
-/// processes them. This is done to avoid locking the underlying non-Send database per call which
-/// cuts down performance in half on certain systems.
+/// the [`Storage`] backend but moved forward to a [`Handler`] which reads database commands from a
+/// queue and processes them. This is done to avoid locking the underlying non-Send database per
+/// call which cuts down performance in half on certain systems.
 #[derive(Clone)]
 pub struct Database {
     /// Sender for database commands.
     sender: kanal::AsyncSender<Command>,
+    /// Sender for read-only commands, served by a pool of reader handlers if the backend supports
+    /// it and the writing handler otherwise.
+    reader: kanal::AsyncSender<Command>,
+    /// Dictionaries loaded so far.
+    dictionaries: Arc<RwLock<Dictionaries>>,
+    /// Level to compress new content with.
+    level: i32,
+    /// Views of entries without a view limit and the time of their latest view that are not yet
+    /// written, see [`Database::flush_views`].
+    views: Arc<Mutex<HashMap<Id, (u32, SystemTime)>>>,
 }
 
-/// Actual database handler that owns the connection to the underlying sqlite database.
+/// Actual database handler that owns the underlying storage backend.
 struct Handler {
-    conn: Connection,
+    storage: Box<dyn Storage>,
     /// Receiver for database commands.
     receiver: kanal::Receiver<Command>,
 }
@@ -62,10 +118,34 @@ enum Command {
         entry: write::DatabaseEntry,
         result: oneshot::Sender<Result<(Id, write::Entry), Error>>,
     },
+    Append {
+        upload: i64,
+        seq: u32,
+        data: Vec<u8>,
+        result: oneshot::Sender<Result<(), Error>>,
+    },
+    InsertUpload {
+        upload: i64,
+        entry: write::Upload,
+        result: oneshot::Sender<Result<(Id, write::Entry), Error>>,
+    },
+    Discard {
+        upload: i64,
+        result: oneshot::Sender<Result<(), Error>>,
+    },
     Get {
         id: Id,
         result: oneshot::Sender<Result<DatabaseEntry, Error>>,
     },
+    GetBlob {
+        id: Id,
+        result: oneshot::Sender<Result<Option<BlobEntry>, Error>>,
+    },
+    ReadBlob {
+        blob: read::Blob,
+        offset: u64,
+        result: oneshot::Sender<Result<Vec<u8>, Error>>,
+    },
     GetMetadata {
         id: Id,
         result: oneshot::Sender<Result<Metadata, Error>>,
@@ -83,15 +163,93 @@ enum Command {
         uids: Vec<i64>,
         result: oneshot::Sender<Result<(), Error>>,
     },
+    GetTrashed {
+        id: Id,
+        result: oneshot::Sender<Result<Metadata, Error>>,
+    },
+    Restore {
+        id: Id,
+        uids: Option<Vec<i64>>,
+        result: oneshot::Sender<Result<(), Error>>,
+    },
+    Edit {
+        id: Id,
+        uids: Vec<i64>,
+        entry: write::DatabaseEntry,
+        result: oneshot::Sender<Result<u32, Error>>,
+    },
+    EditMetadata {
+        id: Id,
+        uids: Vec<i64>,
+        title: Option<String>,
+        extension: Option<String>,
+        result: oneshot::Sender<Result<(), Error>>,
+    },
+    SetExpiration {
+        id: Id,
+        uids: Vec<i64>,
+        expires: Option<SystemTime>,
+        result: oneshot::Sender<Result<(), Error>>,
+    },
+    GetRevision {
+        id: Id,
+        rev: u32,
+        result: oneshot::Sender<Result<DatabaseEntry, Error>>,
+    },
+    View {
+        id: Id,
+        result: oneshot::Sender<Result<bool, Error>>,
+    },
     NextUid {
         result: oneshot::Sender<Result<i64, Error>>,
     },
     List {
         result: oneshot::Sender<Result<Vec<ListEntry>, Error>>,
     },
+    AddViews {
+        views: Vec<(Id, u32, SystemTime)>,
+        result: oneshot::Sender<Result<(), Error>>,
+    },
     Purge {
         result: oneshot::Sender<Result<Vec<Id>, Error>>,
     },
+    Audit {
+        id: Option<Id>,
+        since: Option<String>,
+        result: oneshot::Sender<Result<Vec<audit::Record>, Error>>,
+    },
+    Search {
+        query: String,
+        result: oneshot::Sender<Result<Vec<SearchEntry>, Error>>,
+    },
+    Vacuum {
+        result: oneshot::Sender<Result<(), Error>>,
+    },
+    Backup {
+        path: PathBuf,
+        result: oneshot::Sender<Result<(), Error>>,
+    },
+    Export {
+        result: oneshot::Sender<Result<Vec<RawEntry>, Error>>,
+    },
+    Import {
+        entry: RawEntry,
+        conflict: Conflict,
+        result: oneshot::Sender<Result<Option<Id>, Error>>,
+    },
+    GetDictionary {
+        id: u32,
+        result: oneshot::Sender<Result<Dictionary, Error>>,
+    },
+    AddDictionary {
+        data: Vec<u8>,
+        result: oneshot::Sender<Result<u32, Error>>,
+    },
+    Recompress {
+        level: i32,
+        dictionary: Option<Arc<Dictionary>>,
+        result: oneshot::Sender<Result<Recompressed, Error>>,
+    },
 }
 
 /// Database opening modes
@@ -103,15 +261,325 @@ pub enum Open {
     Path(PathBuf),
 }
 
+/// How an existing SQLite database is opened with regard to its schema.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Access {
+    /// Migrate the schema to the latest version
+    #[default]
+    Migrate,
+    /// Leave the schema as is and fail with [`Error::Schema`] unless it is at the latest version
+    ReadWrite,
+    /// Open read-only and leave the schema as is. Fails with [`Error::Schema`] if the schema is
+    /// older than the latest version, newer ones are only warned about.
+    ReadOnly,
+}
+
+/// Settings applied when opening a [`Database`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Config {
+    /// Number of additional read-only handlers, see [`Database::with_storage`]
+    pub readers: usize,
+    /// Upper bound for the size of all stored data in bytes. Entries that expire are evicted to
+    /// make room for new ones, soonest-expiring first.
+    pub max_storage: Option<NonZeroU64>,
+    /// zstd level to compress new content with, [`codec::DEFAULT_LEVEL`] if not set
+    pub compression_level: Option<i32>,
+    /// Generator of new identifiers
+    pub generator: Generator,
+    /// Deleted entries are kept in a trash and can be restored for this long instead of being
+    /// removed right away. Expired entries are never moved to the trash.
+    pub trash_period: Option<Duration>,
+    /// Recorded as the actor of [`audit`] events caused through this database, e.g. `wastebin` or
+    /// the `wastebin-ctl` subcommand
+    pub actor: &'static str,
+    /// How to open an existing SQLite database
+    pub access: Access,
+}
+
+/// Storage backends to choose from at startup.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Backend {
+    /// SQLite database opened according to [`Open`]
+    #[default]
+    Sqlite,
+    /// Plain in-memory map that is wiped after reload and ignores [`Open`]
+    Memory,
+}
+
+impl FromStr for Backend {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "sqlite" => Ok(Self::Sqlite),
+            "memory" => Ok(Self::Memory),
+            _ => Err(Error::UnknownBackend(s.to_string())),
+        }
+    }
+}
+
+/// Content of an entry as stored, i.e. compressed and potentially encrypted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawContent {
+    /// Compressed and potentially encrypted data
+    pub data: Vec<u8>,
+    /// Nonce if the data is encrypted
+    pub nonce: Option<XNonce>,
+    /// How the data is stored
+    pub encoding: Encoding,
+    /// Data holds the files of a multi-file paste, see [`crate::files`]
+    pub multi_file: bool,
+}
+
+/// Complete entry including its revisions as exported and imported in bulk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawEntry {
+    /// Identifier
+    pub id: Id,
+    /// User identifier that inserted the entry
+    pub uid: Option<i64>,
+    /// Title
+    pub title: Option<String>,
+    /// File extension
+    pub extension: Option<String>,
+    /// Paste this entry was forked from
+    pub parent: Option<Id>,
+    /// Creation datetime if known
+    pub created: Option<String>,
+    /// Expiration datetime if set
+    pub expires: Option<String>,
+    /// Entry is deleted after being fetched this many times
+    pub max_views: Option<u32>,
+    /// Number of times the entry was fetched
+    pub views: u32,
+    /// Current content
+    pub content: RawContent,
+    /// Content of previous revisions, oldest first
+    pub revisions: Vec<RawContent>,
+}
+
+/// Outcome of [`Database::recompress`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Recompressed {
+    /// Number of rewritten pieces of content, counting each revision and shared blob once
+    pub rewritten: usize,
+    /// Number of encrypted pieces of content that cannot be recompressed without their password
+    pub skipped: usize,
+}
+
+/// How to import an entry whose id is already taken.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Conflict {
+    /// Refuse with [`Error::Conflict`]
+    #[default]
+    Fail,
+    /// Keep the existing entry and skip the imported one
+    Skip,
+    /// Delete the existing entry and store the imported one
+    Replace,
+    /// Store the imported entry under a new random id
+    NewId,
+}
+
+/// How to store the owner uids of imported entries, see [`Database::owners`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Owners {
+    /// Keep uids as they are, when restoring into an empty database
+    Keep,
+    /// Replace each uid with a new one, so that local users do not own imported entries, when
+    /// merging into a database that already has entries. Maps imported to new uids.
+    Remap(HashMap<i64, i64>),
+}
+
+/// Storage backend the [`Handler`] forwards [`Database`] calls to. Backends are only ever accessed
+/// from the handler thread and thus need to be [`Send`] but not [`Sync`].
+pub trait Storage: Send {
+    /// Insert `entry` under a new random id or its slug and return the id. Fails with
+    /// [`Error::Conflict`] if the slug is taken by an entry that has not expired. Backends with a
+    /// storage quota evict expiring entries to make room and fail with [`Error::QuotaExceeded`] if
+    /// that is not enough.
+    fn insert(&mut self, entry: write::DatabaseEntry) -> Result<(Id, write::Entry), Error>;
+
+    /// Append `data` as chunk `seq`, counting from zero, to the content of `upload`, which is
+    /// inserted with [`Storage::insert_upload`] once complete. Appended data counts against the
+    /// storage quota.
+    fn append(&mut self, upload: i64, seq: u32, data: Vec<u8>) -> Result<(), Error>;
+
+    /// Insert `entry` like [`Storage::insert`] with the data appended to `upload` as its content
+    /// and remove the upload.
+    fn insert_upload(
+        &mut self,
+        upload: i64,
+        entry: write::Upload,
+    ) -> Result<(Id, write::Entry), Error>;
+
+    /// Remove the data appended to `upload` without inserting it.
+    fn discard(&mut self, upload: i64) -> Result<(), Error>;
+
+    /// Get the raw entry `id`.
+    fn get(&self, id: Id) -> Result<DatabaseEntry, Error>;
+
+    /// Get entry `id` without its content if that is stored unencrypted and can be read in chunks
+    /// with [`Storage::read_blob`]. Returns `None` otherwise.
+    fn get_blob(&self, _id: Id) -> Result<Option<BlobEntry>, Error> {
+        Ok(None)
+    }
+
+    /// Read up to `len` bytes of `blob` starting at `offset`. Fails with [`Error::NotFound`] if the
+    /// blob was removed or rewritten since it was returned by [`Storage::get_blob`].
+    fn read_blob(&self, _blob: &read::Blob, _offset: u64, _len: usize) -> Result<Vec<u8>, Error> {
+        Err(Error::Unsupported)
+    }
+
+    /// Get the metadata of entry `id`.
+    fn get_metadata(&self, id: Id) -> Result<Metadata, Error>;
+
+    /// Delete expired entry `id` right away.
+    fn delete(&mut self, id: Id) -> Result<(), Error>;
+
+    /// Delete entries `ids` and return how many existed. Entries are moved to the trash if a trash
+    /// period is configured.
+    fn delete_many(&mut self, ids: Vec<Id>) -> Result<usize, Error>;
+
+    /// Delete entry `id` if any of `uids` owns it. The entry is moved to the trash if a trash
+    /// period is configured.
+    fn delete_for(&mut self, id: Id, uids: &[i64]) -> Result<(), Error>;
+
+    /// Get the metadata of entry `id` if it is in the trash and can still be restored.
+    fn get_trashed(&self, id: Id) -> Result<Metadata, Error>;
+
+    /// Move entry `id` out of the trash if it can still be restored and, unless `uids` is `None`,
+    /// any of `uids` owns it. Fails with [`Error::Restore`] if `uids` are given and
+    /// [`Error::NotFound`] otherwise.
+    fn restore(&mut self, id: Id, uids: Option<&[i64]>) -> Result<(), Error>;
+
+    /// Archive the current content of `id` as a revision and replace it with `entry` if any of
+    /// `uids` owns it. Returns the new revision number.
+    fn edit(&mut self, id: Id, uids: &[i64], entry: write::DatabaseEntry) -> Result<u32, Error>;
+
+    /// Change the title and extension of entry `id` if any of `uids` owns it. `None` keeps the
+    /// current value and an empty string removes it. Fails with [`Error::Edit`] if the entry has
+    /// already expired.
+    fn edit_metadata(
+        &mut self,
+        id: Id,
+        uids: &[i64],
+        title: Option<String>,
+        extension: Option<String>,
+    ) -> Result<(), Error>;
+
+    /// Let entry `id` expire at `expires` or never if any of `uids` owns it. Fails with
+    /// [`Error::Edit`] if the entry has already expired.
+    fn set_expiration(
+        &mut self,
+        id: Id,
+        uids: &[i64],
+        expires: Option<SystemTime>,
+    ) -> Result<(), Error>;
+
+    /// Get entry `id` with the content of revision `rev`.
+    fn get_revision(&self, id: Id, rev: u32) -> Result<DatabaseEntry, Error>;
+
+    /// Count a view of `id`, record the access and delete it if it reached its view limit. Returns
+    /// `true` if the entry was deleted.
+    fn view(&mut self, id: Id) -> Result<bool, Error>;
+
+    /// Add `count` views to each entry `id` and record `accessed` as its latest access. Entries
+    /// that no longer exist are skipped.
+    fn add_views(&mut self, views: &[(Id, u32, SystemTime)]) -> Result<(), Error>;
+
+    /// Retrieve next monotonically increasing uid.
+    fn next_uid(&mut self) -> Result<i64, Error>;
+
+    /// List all entries.
+    fn list(&self) -> Result<Vec<ListEntry>, Error>;
+
+    /// Delete all expired entries and entries in the trash that can no longer be restored and
+    /// return their ids.
+    fn purge(&mut self) -> Result<Vec<Id>, Error>;
+
+    /// Return audit log records of entry `id` or all entries, oldest first. Only records created at
+    /// or after `since`, a UTC date or datetime like `YYYY-MM-DD HH:MM:SS`, are returned if given.
+    fn audit(&self, id: Option<Id>, since: Option<&str>) -> Result<Vec<audit::Record>, Error>;
+
+    /// Search unencrypted entries for `query`. Backends without a full-text index do not support
+    /// this.
+    fn search(&self, _query: &str) -> Result<Vec<SearchEntry>, Error> {
+        Err(Error::Unsupported)
+    }
+
+    /// Reclaim disk space freed by deleted entries. Backends that do not need this do nothing.
+    fn vacuum(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Write a consistent snapshot of all data to a new database file at `path`.
+    fn backup(&self, _path: &Path) -> Result<(), Error> {
+        Err(Error::Unsupported)
+    }
+
+    /// Return all entries including their revisions as stored. Entries in the trash are left out.
+    fn export(&self) -> Result<Vec<RawEntry>, Error> {
+        Err(Error::Unsupported)
+    }
+
+    /// Store `entry` under its id and resolve an existing entry with the same id according to
+    /// `conflict`. Uids generated afterwards must be larger than the uid of `entry`. Returns the id
+    /// the entry was stored under or `None` if it was skipped.
+    fn import(&mut self, _entry: RawEntry, _conflict: Conflict) -> Result<Option<Id>, Error> {
+        Err(Error::Unsupported)
+    }
+
+    /// Return all stored dictionaries.
+    fn dictionaries(&self) -> Result<Vec<Dictionary>, Error> {
+        Ok(Vec::new())
+    }
+
+    /// Get dictionary `id`.
+    fn get_dictionary(&self, _id: u32) -> Result<Dictionary, Error> {
+        Err(Error::NotFound)
+    }
+
+    /// Store a new dictionary and return its id. Ids increase with every dictionary.
+    fn add_dictionary(&mut self, _data: Vec<u8>) -> Result<u32, Error> {
+        Err(Error::Unsupported)
+    }
+
+    /// Rewrite all unencrypted content with zstd at `level` and, if the content is small enough,
+    /// `dictionary` or uncompressed if that is smaller.
+    fn recompress(
+        &mut self,
+        _level: i32,
+        _dictionary: Option<&Dictionary>,
+    ) -> Result<Recompressed, Error> {
+        Err(Error::Unsupported)
+    }
+
+    /// Open another, read-only handle to the same data that can serve [`Storage::get`],
+    /// [`Storage::get_blob`], [`Storage::read_blob`], [`Storage::get_metadata`],
+    /// [`Storage::get_trashed`], [`Storage::list`] and [`Storage::audit`] concurrently to writes.
+    /// Returns `None` if the backend does not support concurrent readers.
+    fn reader(&self) -> Result<Option<Box<dyn Storage>>, Error> {
+        Ok(None)
+    }
+}
+
 /// Module with types for insertion.
 pub mod write {
+    use crate::codec::{self, Encoding};
     use crate::crypto::{Encrypted, Password, Plaintext};
     use crate::db::Error;
+    use crate::dictionary::{self, Dictionary};
+    use crate::files::{self, File};
+    use crate::id::{Id, Slug};
+    use async_compression::Level;
     use async_compression::tokio::bufread::ZstdEncoder;
     use chacha20poly1305::XNonce;
     use serde::{Deserialize, Serialize};
+    use std::borrow::Cow;
     use std::io::Cursor;
     use std::num::NonZeroU32;
+    use std::time::SystemTime;
     use tokio::io::AsyncReadExt;
 
     /// An uncompressed entry to be inserted into the database.
@@ -123,14 +591,24 @@ pub mod write {
         pub extension: Option<String>,
         /// Expiration in seconds from now
         pub expires: Option<NonZeroU32>,
-        /// Delete if read
-        pub burn_after_reading: Option<bool>,
+        /// Point in time of expiration, takes precedence over `expires`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub expires_at: Option<SystemTime>,
+        /// Delete after being read this many times
+        pub max_views: Option<NonZeroU32>,
         /// User identifier that inserted the entry
         pub uid: Option<i64>,
         /// Optional password to encrypt the entry
         pub password: Option<String>,
         /// Title
         pub title: Option<String>,
+        /// Paste this entry was forked from
+        pub parent: Option<Id>,
+        /// Custom identifier instead of a random one
+        pub slug: Option<Slug>,
+        /// Text holds the files of a multi-file paste, see [`Entry::set_files`]
+        #[serde(default)]
+        pub multi_file: bool,
     }
 
     /// A compressed entry to be inserted.
@@ -139,6 +617,23 @@ pub mod write {
         entry: Entry,
         /// Compressed data
         data: Vec<u8>,
+        /// How the data is stored
+        encoding: Encoding,
+    }
+
+    /// An unencrypted entry whose compressed content was appended to an upload in chunks, see
+    /// [`crate::db::Storage::insert_upload`].
+    pub struct Upload {
+        /// Original data without content
+        pub entry: Entry,
+        /// SHA-256 hash of the content
+        pub hash: Vec<u8>,
+        /// Size of the content in bytes
+        pub size: u64,
+        /// How the content is stored
+        pub encoding: Encoding,
+        /// Beginning of the content to put into the search index
+        pub searchable: String,
     }
 
     /// An entry that might be encrypted.
@@ -149,12 +644,50 @@ pub mod write {
         pub data: Vec<u8>,
         /// Nonce for this entry
         pub nonce: Option<XNonce>,
+        /// How the data is stored
+        pub encoding: Encoding,
     }
 
     impl Entry {
-        /// Compress the entry for insertion.
-        pub async fn compress(self) -> Result<CompressedEntry, Error> {
-            let mut encoder = ZstdEncoder::new(Cursor::new(&self.text));
+        /// Make this entry a multi-file paste of `files`.
+        pub fn set_files(&mut self, files: &[File]) -> Result<(), files::Error> {
+            self.text = files::encode(files)?;
+            self.multi_file = true;
+            Ok(())
+        }
+
+        /// Text to put into the search index.
+        #[must_use]
+        pub fn searchable(&self) -> Cow<'_, str> {
+            if self.multi_file {
+                Cow::Owned(files::searchable(&self.text))
+            } else {
+                Cow::Borrowed(&self.text)
+            }
+        }
+
+        /// Compress the entry for insertion at `level`, using `dictionary` if given and the text
+        /// is small enough, see [`dictionary::MAX_CONTENT_SIZE`]. Content that does not get
+        /// smaller is kept uncompressed. Entries that are going to be encrypted are always plain
+        /// zstd frames without a dictionary, so that their ciphertext stays readable without this
+        /// particular database.
+        pub async fn compress(
+            self,
+            level: i32,
+            dictionary: Option<&Dictionary>,
+        ) -> Result<CompressedEntry, Error> {
+            let dictionary = dictionary::for_size(dictionary, self.text.len())
+                .filter(|_| self.password.is_none());
+            let cursor = Cursor::new(&self.text);
+
+            let mut encoder = match dictionary {
+                Some(dictionary) => {
+                    ZstdEncoder::with_dict(cursor, Level::Precise(level), &dictionary.data)
+                        .map_err(|e| Error::Compression(e.to_string()))?
+                }
+                None => ZstdEncoder::with_quality(cursor, Level::Precise(level)),
+            };
+
             let mut data = Vec::new();
 
             encoder
@@ -162,7 +695,18 @@ pub mod write {
                 .await
                 .map_err(|e| Error::Compression(e.to_string()))?;
 
-            Ok(CompressedEntry { entry: self, data })
+            let encoding = Encoding::zstd(level, dictionary);
+            let (data, encoding) = if self.password.is_none() {
+                codec::smaller(self.text.as_bytes(), data, encoding)
+            } else {
+                (data, encoding)
+            };
+
+            Ok(CompressedEntry {
+                entry: self,
+                data,
+                encoding,
+            })
         }
     }
 
@@ -182,6 +726,7 @@ pub mod write {
                 entry: self.entry,
                 data,
                 nonce,
+                encoding: self.encoding,
             })
         }
     }
@@ -189,14 +734,18 @@ pub mod write {
 
 /// Module with types for reading from the database.
 pub mod read {
+    use crate::codec::{Codec, Encoding};
     use crate::crypto::{Encrypted, Password};
     use crate::db::Error;
+    use crate::dictionary::Dictionary;
     use crate::expiration::Expiration;
+    use crate::files::{self, File};
     use crate::id::Id;
     use async_compression::tokio::bufread::ZstdDecoder;
     use chacha20poly1305::XNonce;
     use std::io::Cursor;
-    use tokio::io::AsyncReadExt;
+    use std::pin::Pin;
+    use tokio::io::{AsyncRead, AsyncReadExt};
 
     /// A raw entry as read from the database.
     #[derive(Debug)]
@@ -207,10 +756,41 @@ pub mod read {
         pub metadata: Metadata,
         /// Entry is expired
         pub expired: bool,
-        /// Entry must be deleted
-        pub must_be_deleted: bool,
         /// Nonce for this entry
         pub nonce: Option<XNonce>,
+        /// How the data is stored
+        pub encoding: Encoding,
+    }
+
+    /// Stored content of an unencrypted entry that is read in chunks.
+    #[derive(Debug, Clone)]
+    pub struct Blob {
+        /// Hash identifying the blob
+        pub hash: Vec<u8>,
+        /// Size of the stored, compressed content in bytes
+        pub size: u64,
+        /// How the content is stored
+        pub encoding: Encoding,
+    }
+
+    /// An unencrypted entry whose content is read from its blob in chunks.
+    #[derive(Debug)]
+    pub struct BlobEntry {
+        /// Stored content
+        pub blob: Blob,
+        /// Metadata
+        pub metadata: Metadata,
+        /// Entry is expired
+        pub expired: bool,
+    }
+
+    /// Uncompressed, decrypted content that is read while it is consumed, see
+    /// [`crate::db::Database::stream`].
+    pub struct Stream {
+        /// Content
+        pub reader: Pin<Box<dyn AsyncRead + Send>>,
+        /// Metadata
+        pub metadata: Metadata,
     }
 
     /// Potentially decrypted but still compressed entry
@@ -218,10 +798,10 @@ pub mod read {
     pub struct CompressedReadEntry {
         /// Compressed data
         data: Vec<u8>,
+        /// How the data is stored
+        encoding: Encoding,
         /// Metadata
         metadata: Metadata,
-        /// Entry must be deleted
-        must_be_deleted: bool,
     }
 
     /// Uncompressed entry
@@ -231,8 +811,6 @@ pub mod read {
         pub text: String,
         /// Metadata
         pub metadata: Metadata,
-        /// Entry must be deleted
-        pub must_be_deleted: bool,
     }
 
     /// Uncompressed, decrypted data read from the database.
@@ -244,6 +822,45 @@ pub mod read {
         pub metadata: Metadata,
     }
 
+    impl Data {
+        /// Files of a multi-file paste, `None` for regular content.
+        pub fn files(&self) -> Option<Vec<File>> {
+            self.metadata
+                .multi_file
+                .then(|| files::decode(&self.text))
+                .flatten()
+        }
+    }
+
+    impl From<Data> for Stream {
+        fn from(data: Data) -> Self {
+            Self {
+                reader: Box::pin(Cursor::new(data.text.into_bytes())),
+                metadata: data.metadata,
+            }
+        }
+    }
+
+    impl Stream {
+        /// Files of a multi-file paste, `None` for regular content. The content of a multi-file
+        /// paste is read at once while regular content is left to be streamed.
+        pub async fn files(&mut self) -> Result<Option<Vec<File>>, Error> {
+            if !self.metadata.multi_file {
+                return Ok(None);
+            }
+
+            let mut text = String::new();
+            self.reader
+                .read_to_string(&mut text)
+                .await
+                .map_err(Error::Read)?;
+            let files = files::decode(&text);
+            self.reader = Box::pin(Cursor::new(text.into_bytes()));
+
+            Ok(files)
+        }
+    }
+
     /// Paste metadata, i.e. anything but actual content.
     #[derive(Debug)]
     pub struct Metadata {
@@ -251,10 +868,36 @@ pub mod read {
         pub uid: Option<i64>,
         /// Title
         pub title: Option<String>,
+        /// File extension given on insert or set later
+        pub extension: Option<String>,
         /// Entry expiration datetime
         pub expiration: Option<Expiration>,
-        /// Entry will be deleted the next time it is fetched via [`Database::get`].
-        pub must_be_deleted: bool,
+        /// Entry is deleted after being fetched this many times via [`Database::get`].
+        pub max_views: Option<u32>,
+        /// Number of times the entry was fetched via [`Database::get`].
+        pub views: u32,
+        /// Current revision number, starting at 1 for the originally inserted content.
+        pub revision: u32,
+        /// Paste this entry was forked from
+        pub parent: Option<Id>,
+        /// Creation datetime, unknown for entries created before it was recorded
+        pub created: Option<String>,
+        /// Datetime of the previous access
+        pub accessed: Option<String>,
+        /// Size of the content in bytes
+        pub size: Option<u64>,
+        /// Size of the stored, compressed and potentially encrypted content in bytes
+        pub compressed_size: Option<u64>,
+        /// Content holds the files of a multi-file paste, see [`files`]
+        pub multi_file: bool,
+    }
+
+    impl Metadata {
+        /// Number of views left before the entry is deleted, `None` if views are unlimited.
+        pub fn remaining_views(&self) -> Option<u32> {
+            self.max_views
+                .map(|max_views| max_views.saturating_sub(self.views))
+        }
     }
 
     /// Potentially deleted or non-existent expired entry.
@@ -279,6 +922,27 @@ pub mod read {
         pub expiration: Option<String>,
         /// If entry is expired
         pub is_expired: bool,
+        /// Creation datetime if known
+        pub created: Option<String>,
+        /// Datetime of the last access
+        pub accessed: Option<String>,
+        /// Size of the content in bytes if known
+        pub size: Option<u64>,
+        /// Size of the stored content in bytes
+        pub compressed_size: Option<u64>,
+        /// Datetime the entry was moved to the trash
+        pub deleted: Option<String>,
+    }
+
+    /// An entry matching a full-text search query.
+    #[derive(Debug)]
+    pub struct SearchEntry {
+        /// Identifier
+        pub id: Id,
+        /// Optional title
+        pub title: Option<String>,
+        /// Excerpt of the content around the match
+        pub snippet: String,
     }
 
     impl DatabaseEntry {
@@ -290,16 +954,16 @@ pub mod read {
                 (Some(_), None) => Err(Error::NoPassword),
                 (None, None | Some(_)) => Ok(CompressedReadEntry {
                     data: self.data,
+                    encoding: self.encoding,
                     metadata: self.metadata,
-                    must_be_deleted: self.must_be_deleted,
                 }),
                 (Some(nonce), Some(password)) => {
                     let encrypted = Encrypted::new(self.data, nonce);
                     let decrypted = encrypted.decrypt(password).await?;
                     Ok(CompressedReadEntry {
                         data: decrypted,
+                        encoding: self.encoding,
                         metadata: self.metadata,
-                        must_be_deleted: self.must_be_deleted,
                     })
                 }
             }
@@ -307,8 +971,39 @@ pub mod read {
     }
 
     impl CompressedReadEntry {
-        pub async fn decompress(self) -> Result<UncompressedEntry, Error> {
-            let mut decoder = ZstdDecoder::new(Cursor::new(self.data));
+        /// Dictionary needed to decompress this entry.
+        pub fn dictionary(&self) -> Option<u32> {
+            self.encoding.dictionary
+        }
+
+        /// Decompress with `dictionary` which must match [`CompressedReadEntry::dictionary`].
+        pub async fn decompress(
+            self,
+            dictionary: Option<&Dictionary>,
+        ) -> Result<UncompressedEntry, Error> {
+            if dictionary.map(|dictionary| dictionary.id) != self.encoding.dictionary {
+                return Err(Error::Compression(format!(
+                    "entry requires dictionary {:?}",
+                    self.encoding.dictionary
+                )));
+            }
+
+            if self.encoding.codec == Codec::Uncompressed {
+                return Ok(UncompressedEntry {
+                    text: String::from_utf8(self.data)
+                        .map_err(|e| Error::Compression(e.to_string()))?,
+                    metadata: self.metadata,
+                });
+            }
+
+            let cursor = Cursor::new(self.data);
+
+            let mut decoder = match dictionary {
+                Some(dictionary) => ZstdDecoder::with_dict(cursor, &dictionary.data)
+                    .map_err(|e| Error::Compression(e.to_string()))?,
+                None => ZstdDecoder::new(cursor),
+            };
+
             let mut text = String::new();
 
             decoder
@@ -319,7 +1014,6 @@ pub mod read {
             Ok(UncompressedEntry {
                 text,
                 metadata: self.metadata,
-                must_be_deleted: self.must_be_deleted,
             })
         }
     }
@@ -335,57 +1029,6 @@ impl From<rusqlite::Error> for Error {
 }
 
 impl Handler {
-    /// Create new database with the given `method`.
-    fn new(method: Open, receiver: kanal::Receiver<Command>) -> Result<Self, Error> {
-        tracing::debug!("opening {method:?}");
-
-        let mut conn = match method {
-            Open::Memory => Connection::open_in_memory()?,
-            Open::Path(path) => Connection::open(path)?,
-        };
-
-        let migrations = Migrations::new(vec![
-            M::up(include_str!("migrations/0001-initial.sql")),
-            M::up(include_str!("migrations/0002-add-created-column.sql")),
-            M::up(include_str!(
-                "migrations/0003-drop-created-add-uid-column.sql"
-            )),
-            M::up_with_hook(
-                include_str!("migrations/0004-add-compressed-column.sql"),
-                |tx: &Transaction| {
-                    let mut stmt = tx.prepare("SELECT id, text FROM entries")?;
-
-                    let rows = stmt
-                        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
-                        .collect::<Result<Vec<(u32, String)>, _>>()?;
-
-                    tracing::debug!("compressing {} rows", rows.len());
-
-                    for (id, text) in rows {
-                        let cursor = Cursor::new(text);
-                        let data =
-                            zstd::stream::encode_all(cursor, zstd::DEFAULT_COMPRESSION_LEVEL)
-                                .map_err(|e| HookError::Hook(e.to_string()))?;
-
-                        tx.execute(
-                            "UPDATE entries SET data = ?1 WHERE id = ?2",
-                            params![data, id],
-                        )?;
-                    }
-
-                    Ok(())
-                },
-            ),
-            M::up(include_str!("migrations/0005-drop-text-column.sql")),
-            M::up(include_str!("migrations/0006-add-nonce-column.sql")),
-            M::up(include_str!("migrations/0007-add-title-column.sql")),
-        ]);
-
-        migrations.to_latest(&mut conn)?;
-
-        Ok(Self { conn, receiver })
-    }
-
     /// Run database command loop.
     fn run(mut self) -> Result<(), Error> {
         loop {
@@ -397,269 +1040,495 @@ impl Handler {
             match command {
                 Command::Insert { entry, result } => {
                     result
-                        .send(self.insert(entry))
+                        .send(self.storage.insert(entry))
+                        .map_err(|_| Error::ResultSendError)?;
+                }
+                Command::Append {
+                    upload,
+                    seq,
+                    data,
+                    result,
+                } => {
+                    result
+                        .send(self.storage.append(upload, seq, data))
+                        .map_err(|_| Error::ResultSendError)?;
+                }
+                Command::InsertUpload {
+                    upload,
+                    entry,
+                    result,
+                } => {
+                    result
+                        .send(self.storage.insert_upload(upload, entry))
+                        .map_err(|_| Error::ResultSendError)?;
+                }
+                Command::Discard { upload, result } => {
+                    result
+                        .send(self.storage.discard(upload))
                         .map_err(|_| Error::ResultSendError)?;
                 }
                 Command::Get { id, result } => {
                     result
-                        .send(self.get(id))
+                        .send(self.storage.get(id))
+                        .map_err(|_| Error::ResultSendError)?;
+                }
+                Command::GetBlob { id, result } => {
+                    result
+                        .send(self.storage.get_blob(id))
                         .map_err(|_| Error::ResultSendError)?;
                 }
+                Command::ReadBlob {
+                    blob,
+                    offset,
+                    result,
+                } => {
+                    // Streams are dropped whenever a client goes away before reading all content.
+                    let _ = result.send(self.storage.read_blob(&blob, offset, CHUNK_SIZE));
+                }
                 Command::GetMetadata { id, result } => {
                     result
-                        .send(self.get_metadata(id))
+                        .send(self.storage.get_metadata(id))
                         .map_err(|_| Error::ResultSendError)?;
                 }
                 Command::Delete { id, result } => {
                     result
-                        .send(self.delete(id))
+                        .send(self.storage.delete(id))
                         .map_err(|_| Error::ResultSendError)?;
                 }
                 Command::DeleteMany { ids, result } => {
                     result
-                        .send(self.delete_many(ids))
+                        .send(self.storage.delete_many(ids))
                         .map_err(|_| Error::ResultSendError)?;
                 }
                 Command::DeleteFor { id, uids, result } => {
                     result
-                        .send(self.delete_for(id, &uids))
+                        .send(self.storage.delete_for(id, &uids))
+                        .map_err(|_| Error::ResultSendError)?;
+                }
+                Command::GetTrashed { id, result } => {
+                    result
+                        .send(self.storage.get_trashed(id))
+                        .map_err(|_| Error::ResultSendError)?;
+                }
+                Command::Restore { id, uids, result } => {
+                    result
+                        .send(self.storage.restore(id, uids.as_deref()))
+                        .map_err(|_| Error::ResultSendError)?;
+                }
+                Command::Edit {
+                    id,
+                    uids,
+                    entry,
+                    result,
+                } => {
+                    result
+                        .send(self.storage.edit(id, &uids, entry))
+                        .map_err(|_| Error::ResultSendError)?;
+                }
+                Command::EditMetadata {
+                    id,
+                    uids,
+                    title,
+                    extension,
+                    result,
+                } => {
+                    result
+                        .send(self.storage.edit_metadata(id, &uids, title, extension))
+                        .map_err(|_| Error::ResultSendError)?;
+                }
+                Command::SetExpiration {
+                    id,
+                    uids,
+                    expires,
+                    result,
+                } => {
+                    result
+                        .send(self.storage.set_expiration(id, &uids, expires))
+                        .map_err(|_| Error::ResultSendError)?;
+                }
+                Command::GetRevision { id, rev, result } => {
+                    result
+                        .send(self.storage.get_revision(id, rev))
+                        .map_err(|_| Error::ResultSendError)?;
+                }
+                Command::View { id, result } => {
+                    result
+                        .send(self.storage.view(id))
                         .map_err(|_| Error::ResultSendError)?;
                 }
                 Command::NextUid { result } => {
                     result
-                        .send(self.next_uid())
+                        .send(self.storage.next_uid())
                         .map_err(|_| Error::ResultSendError)?;
                 }
                 Command::List { result } => {
                     result
-                        .send(self.list())
+                        .send(self.storage.list())
+                        .map_err(|_| Error::ResultSendError)?;
+                }
+                Command::AddViews { views, result } => {
+                    result
+                        .send(self.storage.add_views(&views))
                         .map_err(|_| Error::ResultSendError)?;
                 }
                 Command::Purge { result } => {
                     result
-                        .send(self.purge())
+                        .send(self.storage.purge())
                         .map_err(|_| Error::ResultSendError)?;
                 }
-            }
-        }
-    }
-
-    fn insert(
-        &self,
-        write::DatabaseEntry { entry, data, nonce }: write::DatabaseEntry,
-    ) -> Result<(Id, write::Entry), Error> {
-        let mut counter = 0;
-        let title = entry.title.clone();
-        let nonce = nonce.as_ref().map(|n| n.as_slice());
-
-        loop {
-            let id = Id::rand();
-
-            let result = match entry.expires {
-                None => self.conn.execute(
-                    "INSERT INTO entries (id, uid, data, burn_after_reading, nonce, title) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-                    params![id.to_i64(), entry.uid, data, entry.burn_after_reading, nonce, title],
-                ),
-                Some(expires) => self.conn.execute(
-                    "INSERT INTO entries (id, uid, data, burn_after_reading, nonce, expires, title) VALUES (?1, ?2, ?3, ?4, ?5, datetime('now', ?6), ?7)",
-                    params![
-                        id.to_i64(),
-                        entry.uid,
-                        data,
-                        entry.burn_after_reading,
-                        nonce,
-                        format!("{expires} seconds"),
-                        title,
-                    ],
-                ),
-            };
-
-            match result {
-                Err(rusqlite::Error::SqliteFailure(
-                    rusqlite::ffi::Error {
-                        code,
-                        extended_code,
-                    },
-                    Some(ref _message),
-                )) if code == rusqlite::ErrorCode::ConstraintViolation
-                    && extended_code == rusqlite::ffi::SQLITE_CONSTRAINT_PRIMARYKEY
-                    && counter < 10 =>
-                {
-                    // Retry if ID is already existent
-                    counter += 1;
-                    continue;
+                Command::Audit { id, since, result } => {
+                    result
+                        .send(self.storage.audit(id, since.as_deref()))
+                        .map_err(|_| Error::ResultSendError)?;
                 }
-                Err(err) => break Err(err)?,
-                Ok(rows) => {
-                    debug_assert!(rows == 1);
-                    return Ok((id, entry));
+                Command::Search { query, result } => {
+                    result
+                        .send(self.storage.search(&query))
+                        .map_err(|_| Error::ResultSendError)?;
                 }
-            }
-        }
-    }
-
-    fn get_metadata(&self, id: Id) -> Result<Metadata, Error> {
-        let metadata = self.conn.query_row(
-            "SELECT uid, title, CAST(ROUND((julianday(expires) - julianday('now')) * 86400) AS INTEGER), burn_after_reading FROM entries WHERE id=?1",
-            params![id.to_i64()],
-            |row| {
-                let expiration = row.get::<_, Option<i64>>(2)?
-                    .filter(|secs| *secs > 0)
-                    .and_then(|secs| u64::try_from(secs).ok())
-                    .map(|secs| Expiration { duration: Duration::from_secs(secs), default: false });
-
-                Ok(read::Metadata {
-                    uid: row.get(0)?,
-                    title: row.get::<_, Option<String>>(1)?,
-                    expiration,
-                    must_be_deleted: row.get::<_, Option<bool>>(3)?.unwrap_or(false),
-                })
-            }
-        )?;
-
-        Ok(metadata)
+                Command::Vacuum { result } => {
+                    result
+                        .send(self.storage.vacuum())
+                        .map_err(|_| Error::ResultSendError)?;
+                }
+                Command::Backup { path, result } => {
+                    result
+                        .send(self.storage.backup(&path))
+                        .map_err(|_| Error::ResultSendError)?;
+                }
+                Command::Export { result } => {
+                    result
+                        .send(self.storage.export())
+                        .map_err(|_| Error::ResultSendError)?;
+                }
+                Command::Import {
+                    entry,
+                    conflict,
+                    result,
+                } => {
+                    result
+                        .send(self.storage.import(entry, conflict))
+                        .map_err(|_| Error::ResultSendError)?;
+                }
+                Command::GetDictionary { id, result } => {
+                    result
+                        .send(self.storage.get_dictionary(id))
+                        .map_err(|_| Error::ResultSendError)?;
+                }
+                Command::AddDictionary { data, result } => {
+                    result
+                        .send(self.storage.add_dictionary(data))
+                        .map_err(|_| Error::ResultSendError)?;
+                }
+                Command::Recompress {
+                    level,
+                    dictionary,
+                    result,
+                } => {
+                    result
+                        .send(self.storage.recompress(level, dictionary.as_deref()))
+                        .map_err(|_| Error::ResultSendError)?;
+                }
+            }
+        }
     }
+}
 
-    fn get(&self, id: Id) -> Result<DatabaseEntry, Error> {
-        let metadata = self.get_metadata(id)?;
-        let entry = self.conn.query_row(
-            "SELECT data, burn_after_reading, nonce, expires < datetime('now') FROM entries WHERE id=?1",
-            params![id.to_i64()],
-            |row| {
-                let nonce = row
-                    .get::<_, Option<Vec<_>>>(2)?
-                    .map(|v| XNonce::try_from(v.as_slice()))
-                    .transpose()
-                    .map_err(|err| {
-                        rusqlite::Error::FromSqlConversionFailure(
-                            2,
-                            rusqlite::types::Type::Blob,
-                            Box::new(err),
-                        )
-                    })?;
-
-                Ok(read::DatabaseEntry {
-                    data: row.get(0)?,
-                    metadata,
-                    must_be_deleted: row.get::<_, Option<bool>>(1)?.unwrap_or(false),
-                    nonce,
-                    expired: row.get::<_, Option<bool>>(3)?.unwrap_or(false),
-                })
-            },
-        )?;
-
-        Ok(entry)
+impl Database {
+    /// Create new SQLite database with the given `method` as well as a [`Handler`] future that
+    /// makes the actual calls.
+    pub fn new(method: Open) -> Result<(Self, impl Future<Output = Result<(), Error>>), Error> {
+        Self::open(Backend::Sqlite, method, Config::default())
     }
 
-    fn delete(&self, id: Id) -> Result<(), Error> {
-        self.conn
-            .execute("DELETE FROM entries WHERE id=?1", params![id.to_i64()])?;
+    /// Create new database on top of `backend` according to `config`, see [`Database::new`].
+    pub fn open(
+        backend: Backend,
+        method: Open,
+        config: Config,
+    ) -> Result<(Self, impl Future<Output = Result<(), Error>>), Error> {
+        let storage: Box<dyn Storage> = match backend {
+            Backend::Sqlite => Box::new(
+                sqlite::Sqlite::open(method, config.access)?
+                    .with_max_storage(config.max_storage)
+                    .with_generator(config.generator)
+                    .with_trash_period(config.trash_period)
+                    .with_actor(config.actor),
+            ),
+            Backend::Memory => Box::new(
+                memory::Memory::default()
+                    .with_max_storage(config.max_storage)
+                    .with_generator(config.generator)
+                    .with_trash_period(config.trash_period)
+                    .with_actor(config.actor),
+            ),
+        };
 
-        Ok(())
+        Self::with_storage(storage, config)
     }
 
-    fn delete_many(&mut self, ids: Vec<Id>) -> Result<usize, Error> {
-        let tx = self.conn.transaction()?;
+    /// Create new database on top of an arbitrary `storage` backend, see [`Database::open`].
+    /// The storage quota, trash period and actor of `config` are up to the backend.
+    pub fn with_storage(
+        storage: Box<dyn Storage>,
+        config: Config,
+    ) -> Result<(Self, impl Future<Output = Result<(), Error>>), Error> {
+        let Config {
+            readers,
+            compression_level,
+            ..
+        } = config;
+
+        let mut dictionaries = Dictionaries::default();
+
+        for dictionary in storage.dictionaries()? {
+            dictionaries.add(dictionary);
+        }
 
-        let mut affected = 0;
+        let dictionaries = Arc::new(RwLock::new(dictionaries));
 
-        for id in ids {
-            affected += tx.execute("DELETE FROM entries WHERE id=?1", params![id.to_i64()])?;
-        }
+        let (sender, receiver) = kanal::bounded(256);
+        let sender = sender.to_async();
 
-        tx.commit()?;
-        Ok(affected)
-    }
+        let (reader, reader_receiver) = kanal::bounded(256);
+        let mut handlers = Vec::with_capacity(readers + 1);
+
+        for _ in 0..readers {
+            let Some(storage) = storage.reader()? else {
+                tracing::debug!("storage backend does not support concurrent readers");
+                break;
+            };
 
-    fn delete_for(&mut self, id: Id, uids: &[i64]) -> Result<(), Error> {
-        if uids.is_empty() {
-            return Err(Error::Delete);
+            handlers.push(Handler {
+                storage,
+                receiver: reader_receiver.clone(),
+            });
         }
 
-        let placeholders = vec!["?"; uids.len()].join(",");
-        let delete_sql = format!("DELETE FROM entries WHERE id=? AND uid IN ({placeholders})");
+        // Without any reader handler read-only commands are served by the writing handler.
+        let reader = if handlers.is_empty() {
+            sender.clone()
+        } else {
+            reader.to_async()
+        };
 
-        let mut params = Vec::with_capacity(uids.len() + 1);
-        params.push(id.to_i64());
-        params.extend_from_slice(uids);
+        handlers.push(Handler { storage, receiver });
 
-        let affected = self.conn.execute(&delete_sql, params_from_iter(&params))?;
+        let fut = async move {
+            let tasks = handlers
+                .into_iter()
+                .map(|handler| tokio::task::spawn_blocking(|| handler.run()))
+                .collect::<Vec<_>>();
 
-        if affected == 0 {
-            return Err(Error::Delete);
-        }
+            for task in tasks {
+                task.await??;
+            }
 
-        Ok(())
+            Ok(())
+        };
+
+        Ok((
+            Self {
+                sender,
+                reader,
+                dictionaries,
+                level: compression_level.unwrap_or(codec::DEFAULT_LEVEL),
+                views: Arc::default(),
+            },
+            fut,
+        ))
     }
 
-    fn next_uid(&self) -> Result<i64, Error> {
-        let uid = self.conn.query_row(
-            "UPDATE uids SET n = n + 1 WHERE id = 0 RETURNING n",
-            [],
-            |row| row.get(0),
-        )?;
+    /// Insert `entry` under a new random id or its slug into the database and optionally set owner
+    /// to `uid`. Returns the id of the new entry on success.
+    pub async fn insert(&self, entry: write::Entry) -> Result<(Id, write::Entry), Error> {
+        let dictionary = self.current_dictionary();
+        let entry = entry
+            .compress(self.level, dictionary.as_deref())
+            .await?
+            .encrypt()
+            .await?;
+
+        let (result, command_result) = oneshot::channel();
+        self.sender
+            .send(Command::Insert { entry, result })
+            .await
+            .map_err(|_| Error::SendError)?;
 
-        Ok(uid)
+        command_result.await?
     }
 
-    fn list(&self) -> Result<Vec<ListEntry>, Error> {
-        let entries = self
-            .conn
-            .prepare("SELECT id, title, nonce, expires, expires < datetime('now') FROM entries")?
-            .query_map([], |row| {
-                Ok(ListEntry {
-                    id: Id::from(row.get::<_, i64>(0)?),
-                    title: row.get(1)?,
-                    is_encrypted: row.get::<_, Option<Vec<u8>>>(2)?.is_some(),
-                    expiration: row.get(3)?,
-                    is_expired: row.get::<_, Option<bool>>(4)?.unwrap_or_default(),
+    /// Insert `entry` with the content read from `reader` instead of its `text`, see
+    /// [`Database::insert`]. Unencrypted content is compressed while it is read and handed to the
+    /// storage backend in chunks, so that it is never held in memory at once. Only its first
+    /// [`INDEXED_SIZE`] bytes are put into the search index. Content to encrypt is read at once.
+    pub async fn insert_stream(
+        &self,
+        mut entry: write::Entry,
+        mut reader: impl AsyncRead + Unpin,
+    ) -> Result<(Id, write::Entry), Error> {
+        if entry.password.is_some() {
+            let mut data = Vec::new();
+            reader.read_to_end(&mut data).await.map_err(Error::Read)?;
+            entry.text = String::from_utf8(data).map_err(|err| err.utf8_error())?;
+            return self.insert(entry).await;
+        }
+
+        let upload = rand::rng().random::<i64>();
+
+        let result = async {
+            let entry = self.upload(upload, entry, reader).await?;
+
+            let (result, command_result) = oneshot::channel();
+            self.sender
+                .send(Command::InsertUpload {
+                    upload,
+                    entry,
+                    result,
                 })
-            })?
-            .collect::<Result<_, _>>()?;
+                .await
+                .map_err(|_| Error::SendError)?;
 
-        Ok(entries)
-    }
+            command_result.await?
+        }
+        .await;
 
-    fn purge(&self) -> Result<Vec<Id>, Error> {
-        let ids = self
-            .conn
-            .prepare("DELETE FROM entries WHERE expires < datetime('now') RETURNING id")?
-            .query_map([], |row| Ok(Id::from(row.get::<_, i64>(0)?)))?
-            .collect::<Result<_, _>>()?;
+        // Uploads abandoned without getting here are removed by the next purge.
+        if result.is_err()
+            && let Err(err) = self.discard(upload).await
+        {
+            tracing::warn!("failed to discard upload: {err}");
+        }
 
-        Ok(ids)
+        result
     }
-}
 
-impl Database {
-    /// Create new database with the given `method` as well as a [`Handler`] future that makes the
-    /// actual calls.
-    pub fn new(method: Open) -> Result<(Self, impl Future<Output = Result<(), Error>>), Error> {
-        let (sender, receiver) = kanal::bounded(256);
-        let sender = sender.to_async();
-        let handler = Handler::new(method, receiver)?;
-        let fut = async move { tokio::task::spawn_blocking(|| handler.run()).await? };
-        Ok((Self { sender }, fut))
-    }
+    /// Compress the content read from `reader` and append it to `upload` in chunks. The content is
+    /// hashed and checked to be valid UTF-8 along the way. The current dictionary is only used if
+    /// the content turns out to be small enough, see [`dictionary::MAX_CONTENT_SIZE`].
+    async fn upload(
+        &self,
+        upload: i64,
+        entry: write::Entry,
+        mut reader: impl AsyncRead + Unpin,
+    ) -> Result<write::Upload, Error> {
+        let mut head = Vec::new();
+        (&mut reader)
+            .take(dictionary::MAX_CONTENT_SIZE as u64 + 1)
+            .read_to_end(&mut head)
+            .await
+            .map_err(Error::Read)?;
+
+        let dictionary = self
+            .current_dictionary()
+            .filter(|_| head.len() <= dictionary::MAX_CONTENT_SIZE);
+        let mut reader = Cursor::new(head).chain(reader);
+
+        let mut encoder = match dictionary.as_deref() {
+            Some(dictionary) => ZstdEncoder::with_dict(
+                Vec::new(),
+                async_compression::Level::Precise(self.level),
+                &dictionary.data,
+            )
+            .map_err(|e| Error::Compression(e.to_string()))?,
+            None => {
+                ZstdEncoder::with_quality(Vec::new(), async_compression::Level::Precise(self.level))
+            }
+        };
 
-    /// Insert `entry` under a new random id into the database and optionally set owner to `uid`.
-    /// Returns the id of the new entry on success.
-    pub async fn insert(&self, entry: write::Entry) -> Result<(Id, write::Entry), Error> {
-        let entry = entry.compress().await?.encrypt().await?;
+        let mut hasher = Sha256::new();
+        let mut size = 0;
+        let mut seq = 0;
+        let mut searchable = String::new();
+        let mut buf = vec![0; CHUNK_SIZE];
+        // Number of bytes at the start of `buf` that belong to an incomplete character.
+        let mut pending = 0;
+
+        loop {
+            let read = reader
+                .read(&mut buf[pending..])
+                .await
+                .map_err(Error::Read)?;
+
+            if read == 0 {
+                std::str::from_utf8(&buf[..pending])?;
+                break;
+            }
+
+            let filled = pending + read;
+
+            let valid = match std::str::from_utf8(&buf[..filled]) {
+                Ok(_) => filled,
+                Err(err) if err.error_len().is_none() => err.valid_up_to(),
+                Err(err) => return Err(err.into()),
+            };
+
+            let text = std::str::from_utf8(&buf[..valid])?;
+            let indexed = INDEXED_SIZE.saturating_sub(searchable.len());
+            searchable.push_str(&text[..text.floor_char_boundary(indexed)]);
+            hasher.update(text);
+            size += valid as u64;
+
+            encoder
+                .write_all(text.as_bytes())
+                .await
+                .map_err(|e| Error::Compression(e.to_string()))?;
+
+            if encoder.get_ref().len() >= UPLOAD_CHUNK_SIZE {
+                self.append(upload, seq, std::mem::take(encoder.get_mut()))
+                    .await?;
+                seq += 1;
+            }
+
+            buf.copy_within(valid..filled, 0);
+            pending = filled - valid;
+        }
 
+        encoder
+            .shutdown()
+            .await
+            .map_err(|e| Error::Compression(e.to_string()))?;
+
+        self.append(upload, seq, encoder.into_inner()).await?;
+
+        Ok(write::Upload {
+            entry,
+            hash: hasher.finalize().to_vec(),
+            size,
+            encoding: Encoding::zstd(self.level, dictionary.as_deref()),
+            searchable,
+        })
+    }
+
+    /// Append `data` as chunk `seq` to `upload`.
+    async fn append(&self, upload: i64, seq: u32, data: Vec<u8>) -> Result<(), Error> {
         let (result, command_result) = oneshot::channel();
         self.sender
-            .send(Command::Insert { entry, result })
+            .send(Command::Append {
+                upload,
+                seq,
+                data,
+                result,
+            })
             .await
             .map_err(|_| Error::SendError)?;
+        command_result.await?
+    }
 
+    /// Remove `upload` without inserting it.
+    async fn discard(&self, upload: i64) -> Result<(), Error> {
+        let (result, command_result) = oneshot::channel();
+        self.sender
+            .send(Command::Discard { upload, result })
+            .await
+            .map_err(|_| Error::SendError)?;
         command_result.await?
     }
 
     /// Get entire entry for `id`.
     pub async fn get(&self, id: Id, password: Option<Password>) -> Result<read::Entry, Error> {
         let (result, command_result) = oneshot::channel();
-        self.sender
+        self.reader
             .send(Command::Get { id, result })
             .await
             .map_err(|_| Error::SendError)?;
@@ -671,33 +1540,156 @@ impl Database {
             return Err(Error::NotFound);
         }
 
-        let read::UncompressedEntry {
-            text,
-            metadata,
-            must_be_deleted,
-        } = entry.decrypt(password).await?.decompress().await?;
+        let entry = entry.decrypt(password).await?;
+        let dictionary = self.dictionary(entry.dictionary()).await?;
+        let read::UncompressedEntry { text, mut metadata } =
+            entry.decompress(dictionary.as_deref()).await?;
 
+        self.add_pending_views(id, &mut metadata);
+        let max_views = metadata.max_views;
         let data = read::Data { text, metadata };
 
-        if must_be_deleted {
-            self.delete(id).await?;
+        // Views are only counted once the content could be decrypted, so that password prompts do
+        // not use up views.
+        if self.view(id, max_views).await? {
             return Ok(read::Entry::Burned(data));
         }
 
         Ok(read::Entry::Regular(data))
     }
 
+    /// Get the content of entry `id` as a stream that is decompressed while it is read. Unencrypted
+    /// content of entries without a view limit is read from the storage backend in chunks.
+    /// Encrypted and view-limited entries fall back to [`Database::get`], i.e. their content is
+    /// decrypted and decompressed as a whole and held in memory before the stream starts.
+    pub async fn stream(&self, id: Id, password: Option<Password>) -> Result<read::Stream, Error> {
+        // A view may delete a view-limited entry, so its content is read before counting it.
+        let Some(mut entry) = self
+            .get_blob(id)
+            .await?
+            .filter(|entry| entry.metadata.max_views.is_none())
+        else {
+            let (read::Entry::Regular(data) | read::Entry::Burned(data)) =
+                self.get(id, password).await?;
+            return Ok(data.into());
+        };
+
+        if entry.expired {
+            self.delete(id).await?;
+            return Err(Error::NotFound);
+        }
+
+        let dictionary = self.dictionary(entry.blob.encoding.dictionary).await?;
+        let codec = entry.blob.encoding.codec;
+        self.add_pending_views(id, &mut entry.metadata);
+        self.view(id, None).await?;
+
+        let chunks = self.chunks(entry.blob, 0);
+
+        let reader: Pin<Box<dyn AsyncRead + Send>> = match (codec, dictionary) {
+            (Codec::Uncompressed, _) => Box::pin(chunks),
+            (Codec::Zstd, Some(dictionary)) => Box::pin(
+                ZstdDecoder::with_dict(chunks, &dictionary.data)
+                    .map_err(|e| Error::Compression(e.to_string()))?,
+            ),
+            (Codec::Zstd, None) => Box::pin(ZstdDecoder::new(chunks)),
+        };
+
+        Ok(read::Stream {
+            reader,
+            metadata: entry.metadata,
+        })
+    }
+
+    /// Get the content of entry `id` as the zstd frame it is stored as, so that it can be passed
+    /// on to clients that decompress it themselves. Returns `None` unless the entry is a single
+    /// unencrypted file without a view limit that is compressed without a dictionary, use
+    /// [`Database::stream`] then.
+    pub async fn stream_zstd(&self, id: Id) -> Result<Option<read::Stream>, Error> {
+        let Some(mut entry) = self.get_blob(id).await?.filter(|entry| {
+            entry.metadata.max_views.is_none()
+                && entry.blob.encoding.codec == Codec::Zstd
+                && entry.blob.encoding.dictionary.is_none()
+                && !entry.metadata.multi_file
+        }) else {
+            return Ok(None);
+        };
+
+        if entry.expired {
+            self.delete(id).await?;
+            return Err(Error::NotFound);
+        }
+
+        self.add_pending_views(id, &mut entry.metadata);
+        self.view(id, None).await?;
+
+        Ok(Some(read::Stream {
+            reader: Box::pin(self.chunks(entry.blob, 0)),
+            metadata: entry.metadata,
+        }))
+    }
+
+    /// Get entry `id` without its content if the storage backend can read it in chunks.
+    async fn get_blob(&self, id: Id) -> Result<Option<read::BlobEntry>, Error> {
+        let (result, command_result) = oneshot::channel();
+        self.reader
+            .send(Command::GetBlob { id, result })
+            .await
+            .map_err(|_| Error::SendError)?;
+        command_result.await?
+    }
+
+    /// Read `blob` starting at `offset` chunk by chunk.
+    fn chunks(&self, blob: read::Blob, offset: u64) -> impl AsyncBufRead + Send + 'static {
+        let chunks = futures_util::stream::try_unfold(
+            (self.clone(), blob, offset),
+            |(db, blob, offset)| async move {
+                if offset >= blob.size {
+                    return Ok(None);
+                }
+
+                let data = db
+                    .read_blob(blob.clone(), offset)
+                    .await
+                    .map_err(std::io::Error::other)?;
+                let offset = offset + data.len() as u64;
+
+                Ok::<_, std::io::Error>(Some((Cursor::new(data), (db, blob, offset))))
+            },
+        );
+
+        StreamReader::new(chunks)
+    }
+
+    /// Read the chunk of `blob` starting at `offset`.
+    async fn read_blob(&self, blob: read::Blob, offset: u64) -> Result<Vec<u8>, Error> {
+        let (result, command_result) = oneshot::channel();
+        self.reader
+            .send(Command::ReadBlob {
+                blob,
+                offset,
+                result,
+            })
+            .await
+            .map_err(|_| Error::SendError)?;
+        command_result.await?
+    }
+
     /// Get metadata of a paste.
     pub async fn get_metadata(&self, id: Id) -> Result<Metadata, Error> {
         let (result, command_result) = oneshot::channel();
-        self.sender
+        self.reader
             .send(Command::GetMetadata { id, result })
             .await
             .map_err(|_| Error::SendError)?;
-        command_result.await?
+
+        let mut metadata = command_result.await??;
+        self.add_pending_views(id, &mut metadata);
+
+        Ok(metadata)
     }
 
-    /// Delete paste with `id`.
+    /// Delete expired paste with `id`.
     async fn delete(&self, id: Id) -> Result<(), Error> {
         let (result, command_result) = oneshot::channel();
         self.sender
@@ -707,7 +1699,7 @@ impl Database {
         command_result.await?
     }
 
-    /// Delete pastes with `ids`.
+    /// Delete pastes with `ids`, moving them to the trash if a trash period is configured.
     pub async fn delete_many(&self, ids: Vec<Id>) -> Result<usize, Error> {
         let (result, command_result) = oneshot::channel();
         self.sender
@@ -717,7 +1709,8 @@ impl Database {
         command_result.await?
     }
 
-    /// Delete paste with `id` if any of `uids` owns it.
+    /// Delete paste with `id` if any of `uids` owns it, moving it to the trash if a trash period
+    /// is configured.
     pub async fn delete_for(&self, id: Id, uids: &[i64]) -> Result<(), Error> {
         let (result, command_result) = oneshot::channel();
         self.sender
@@ -731,83 +1724,572 @@ impl Database {
         command_result.await?
     }
 
-    /// Retrieve next monotonically increasing uid.
-    pub async fn next_uid(&self) -> Result<i64, Error> {
+    /// Get metadata of paste `id` if it is in the trash and can still be restored.
+    pub async fn get_trashed(&self, id: Id) -> Result<Metadata, Error> {
         let (result, command_result) = oneshot::channel();
-        self.sender
-            .send(Command::NextUid { result })
+        self.reader
+            .send(Command::GetTrashed { id, result })
             .await
             .map_err(|_| Error::SendError)?;
         command_result.await?
     }
 
-    /// List all entries.
-    pub async fn list(&self) -> Result<Vec<ListEntry>, Error> {
+    /// Restore paste `id` from the trash regardless of its owner.
+    pub async fn restore(&self, id: Id) -> Result<(), Error> {
         let (result, command_result) = oneshot::channel();
         self.sender
-            .send(Command::List { result })
+            .send(Command::Restore {
+                id,
+                uids: None,
+                result,
+            })
             .await
             .map_err(|_| Error::SendError)?;
         command_result.await?
     }
 
-    /// Purge all expired entries and return their [`Id`]s
-    pub async fn purge(&self) -> Result<Vec<Id>, Error> {
+    /// Restore paste `id` from the trash if any of `uids` owns it.
+    pub async fn restore_for(&self, id: Id, uids: &[i64]) -> Result<(), Error> {
         let (result, command_result) = oneshot::channel();
         self.sender
-            .send(Command::Purge { result })
+            .send(Command::Restore {
+                id,
+                uids: Some(uids.to_vec()),
+                result,
+            })
             .await
             .map_err(|_| Error::SendError)?;
         command_result.await?
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use std::num::NonZeroU32;
 
-    use super::*;
+    /// Replace the content of paste `id` with `entry` if any of `uids` owns it. The previous
+    /// content is kept as a revision. Returns the new revision number.
+    pub async fn edit(&self, id: Id, uids: &[i64], entry: write::Entry) -> Result<u32, Error> {
+        let dictionary = self.current_dictionary();
+        let entry = entry
+            .compress(self.level, dictionary.as_deref())
+            .await?
+            .encrypt()
+            .await?;
 
-    impl read::Entry {
-        /// Unwrap inner data or panic.
-        #[must_use]
-        pub fn unwrap_inner(self) -> read::Data {
-            match self {
-                read::Entry::Regular(data) | read::Entry::Burned(data) => data,
-            }
-        }
+        let (result, command_result) = oneshot::channel();
+        self.sender
+            .send(Command::Edit {
+                id,
+                uids: uids.to_vec(),
+                entry,
+                result,
+            })
+            .await
+            .map_err(|_| Error::SendError)?;
+        command_result.await?
     }
 
-    fn new_db() -> Result<Database, Box<dyn std::error::Error>> {
-        let (db, handler) = Database::new(Open::Memory)?;
-        tokio::spawn(handler);
-        Ok(db)
+    /// Change the title and extension of paste `id` if any of `uids` owns it. `None` keeps the
+    /// current value and an empty string removes it.
+    pub async fn edit_metadata(
+        &self,
+        id: Id,
+        uids: &[i64],
+        title: Option<String>,
+        extension: Option<String>,
+    ) -> Result<(), Error> {
+        let (result, command_result) = oneshot::channel();
+        self.sender
+            .send(Command::EditMetadata {
+                id,
+                uids: uids.to_vec(),
+                title,
+                extension,
+                result,
+            })
+            .await
+            .map_err(|_| Error::SendError)?;
+        command_result.await?
     }
 
-    #[tokio::test]
-    async fn insert() -> Result<(), Box<dyn std::error::Error>> {
-        let db = new_db()?;
+    /// Let paste `id` expire at `expires` or never if any of `uids` owns it. A paste that has
+    /// already expired cannot be revived.
+    pub async fn set_expiration(
+        &self,
+        id: Id,
+        uids: &[i64],
+        expires: Option<SystemTime>,
+    ) -> Result<(), Error> {
+        let (result, command_result) = oneshot::channel();
+        self.sender
+            .send(Command::SetExpiration {
+                id,
+                uids: uids.to_vec(),
+                expires,
+                result,
+            })
+            .await
+            .map_err(|_| Error::SendError)?;
+        command_result.await?
+    }
 
-        let entry = write::Entry {
-            text: "hello world".to_string(),
-            uid: Some(10),
-            ..Default::default()
-        };
+    /// Get revision `rev` of entry `id`. Unlike [`Database::get`] this does not count as a view and
+    /// view-limited entries are therefore not found.
+    pub async fn get_revision(
+        &self,
+        id: Id,
+        rev: u32,
+        password: Option<Password>,
+    ) -> Result<read::Data, Error> {
+        let (result, command_result) = oneshot::channel();
+        self.sender
+            .send(Command::GetRevision { id, rev, result })
+            .await
+            .map_err(|_| Error::SendError)?;
 
-        let (id, _entry) = db.insert(entry).await?;
+        let entry = command_result.await??;
 
-        let entry = db.get(id, None).await?.unwrap_inner();
-        assert_eq!(entry.text, "hello world");
-        assert!(entry.metadata.uid.is_some());
-        assert_eq!(entry.metadata.uid.unwrap(), 10);
+        if entry.expired {
+            self.delete(id).await?;
+            return Err(Error::NotFound);
+        }
 
-        let result = db.get(Id::from(5678u32), None).await;
-        assert!(result.is_err());
+        let entry = entry.decrypt(password).await?;
+        let dictionary = self.dictionary(entry.dictionary()).await?;
+        let read::UncompressedEntry { text, metadata } =
+            entry.decompress(dictionary.as_deref()).await?;
 
-        Ok(())
+        Ok(read::Data { text, metadata })
     }
 
-    #[tokio::test]
+    /// Count a view of `id`, deleting it if it reached its view limit `max_views`. Views of entries
+    /// without a view limit are only counted in memory until [`Database::flush_views`], so that
+    /// reads do not wait for the writer.
+    async fn view(&self, id: Id, max_views: Option<u32>) -> Result<bool, Error> {
+        if max_views.is_none() {
+            let mut views = self.views.lock().unwrap_or_else(PoisonError::into_inner);
+            let now = SystemTime::now();
+            let (count, accessed) = views.entry(id).or_insert((0, now));
+            *count += 1;
+            *accessed = now;
+            return Ok(false);
+        }
+
+        let (result, command_result) = oneshot::channel();
+        self.sender
+            .send(Command::View { id, result })
+            .await
+            .map_err(|_| Error::SendError)?;
+        command_result.await?
+    }
+
+    /// Write the views counted in memory to the storage backend.
+    pub async fn flush_views(&self) -> Result<(), Error> {
+        let views = std::mem::take(&mut *self.views.lock().unwrap_or_else(PoisonError::into_inner))
+            .into_iter()
+            .map(|(id, (count, accessed))| (id, count, accessed))
+            .collect::<Vec<_>>();
+
+        if views.is_empty() {
+            return Ok(());
+        }
+
+        let (result, command_result) = oneshot::channel();
+        self.sender
+            .send(Command::AddViews { views, result })
+            .await
+            .map_err(|_| Error::SendError)?;
+        command_result.await?
+    }
+
+    /// Add the views of `id` that are not yet written to `metadata`.
+    fn add_pending_views(&self, id: Id, metadata: &mut Metadata) {
+        let views = self.views.lock().unwrap_or_else(PoisonError::into_inner);
+
+        if let Some((count, accessed)) = views.get(&id) {
+            metadata.views += count;
+            metadata.accessed = Some(memory::format_datetime(*accessed));
+        }
+    }
+
+    /// Retrieve next monotonically increasing uid.
+    pub async fn next_uid(&self) -> Result<i64, Error> {
+        let (result, command_result) = oneshot::channel();
+        self.sender
+            .send(Command::NextUid { result })
+            .await
+            .map_err(|_| Error::SendError)?;
+        command_result.await?
+    }
+
+    /// List all entries.
+    pub async fn list(&self) -> Result<Vec<ListEntry>, Error> {
+        let (result, command_result) = oneshot::channel();
+        self.reader
+            .send(Command::List { result })
+            .await
+            .map_err(|_| Error::SendError)?;
+
+        let mut entries = command_result.await??;
+        let views = self.views.lock().unwrap_or_else(PoisonError::into_inner);
+
+        for entry in &mut entries {
+            if let Some((_, accessed)) = views.get(&entry.id) {
+                entry.accessed = Some(memory::format_datetime(*accessed));
+            }
+        }
+
+        Ok(entries)
+    }
+
+    /// Purge all expired entries and entries whose trash period ended and return their [`Id`]s
+    pub async fn purge(&self) -> Result<Vec<Id>, Error> {
+        let (result, command_result) = oneshot::channel();
+        self.sender
+            .send(Command::Purge { result })
+            .await
+            .map_err(|_| Error::SendError)?;
+        command_result.await?
+    }
+
+    /// Return audit log records of paste `id` or all pastes created at or after `since`, see
+    /// [`Storage::audit`].
+    pub async fn audit(
+        &self,
+        id: Option<Id>,
+        since: Option<String>,
+    ) -> Result<Vec<audit::Record>, Error> {
+        let (result, command_result) = oneshot::channel();
+        self.reader
+            .send(Command::Audit { id, since, result })
+            .await
+            .map_err(|_| Error::SendError)?;
+        command_result.await?
+    }
+
+    /// Reclaim disk space freed by deleted entries.
+    pub async fn vacuum(&self) -> Result<(), Error> {
+        let (result, command_result) = oneshot::channel();
+        self.sender
+            .send(Command::Vacuum { result })
+            .await
+            .map_err(|_| Error::SendError)?;
+        command_result.await?
+    }
+
+    /// Write a consistent snapshot of the database to `path` while other calls proceed.
+    pub async fn backup(&self, path: PathBuf) -> Result<(), Error> {
+        let (result, command_result) = oneshot::channel();
+        self.reader
+            .send(Command::Backup { path, result })
+            .await
+            .map_err(|_| Error::SendError)?;
+        command_result.await?
+    }
+
+    /// Return all entries including their revisions as stored. Content compressed with a
+    /// dictionary is recompressed without one, so that it can be read elsewhere.
+    pub async fn export(&self) -> Result<Vec<RawEntry>, Error> {
+        let (result, command_result) = oneshot::channel();
+        self.sender
+            .send(Command::Export { result })
+            .await
+            .map_err(|_| Error::SendError)?;
+
+        let mut entries = command_result.await??;
+
+        for entry in &mut entries {
+            for content in std::iter::once(&mut entry.content).chain(&mut entry.revisions) {
+                let Some(dictionary) = self.dictionary(content.encoding.dictionary).await? else {
+                    continue;
+                };
+
+                let data = std::mem::take(&mut content.data);
+                let encoding = content.encoding;
+
+                (content.data, content.encoding) = tokio::task::spawn_blocking(move || {
+                    let text = codec::decode(&data, encoding, Some(&dictionary))?;
+                    codec::encode(&text, encoding.level.unwrap_or(codec::DEFAULT_LEVEL), None)
+                })
+                .await?
+                .map_err(|e| Error::Compression(e.to_string()))?;
+            }
+        }
+
+        Ok(entries)
+    }
+
+    /// Decide how to store the owner uids of entries imported next: as they are if the database is
+    /// empty and remapped to new ones otherwise.
+    pub async fn owners(&self) -> Result<Owners, Error> {
+        if self.list().await?.is_empty() {
+            Ok(Owners::Keep)
+        } else {
+            Ok(Owners::Remap(HashMap::new()))
+        }
+    }
+
+    /// Import `entry` under its original id, see [`Conflict`] for what happens if it is taken. Its
+    /// owner uid is stored according to `owners`, which should be used for all entries of the same
+    /// archive. Returns the id the entry was stored under or `None` if it was skipped.
+    pub async fn import(
+        &self,
+        mut entry: RawEntry,
+        conflict: Conflict,
+        owners: &mut Owners,
+    ) -> Result<Option<Id>, Error> {
+        if let (Owners::Remap(uids), Some(uid)) = (owners, entry.uid) {
+            let uid = match uids.get(&uid) {
+                Some(uid) => *uid,
+                None => {
+                    let new = self.next_uid().await?;
+                    uids.insert(uid, new);
+                    new
+                }
+            };
+
+            entry.uid = Some(uid);
+        }
+
+        let (result, command_result) = oneshot::channel();
+        self.sender
+            .send(Command::Import {
+                entry,
+                conflict,
+                result,
+            })
+            .await
+            .map_err(|_| Error::SendError)?;
+        command_result.await?
+    }
+
+    /// Train a dictionary of at most `max_size` bytes from the current content of all unencrypted
+    /// entries and use it to compress new entries. Returns the id of the new dictionary and the
+    /// number of entries it was trained on.
+    pub async fn train_dictionary(&self, max_size: usize) -> Result<(u32, usize), Error> {
+        let samples = self
+            .export()
+            .await?
+            .into_iter()
+            .filter(|entry| entry.content.nonce.is_none())
+            .map(|entry| entry.content)
+            .collect::<Vec<_>>();
+
+        let count = samples.len();
+
+        let data = tokio::task::spawn_blocking(move || {
+            let samples = samples
+                .iter()
+                .map(|content| codec::decode(&content.data, content.encoding, None))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            dictionary::train(&samples, max_size)
+        })
+        .await?
+        .map_err(|e| Error::Compression(e.to_string()))?;
+
+        let (result, command_result) = oneshot::channel();
+        self.sender
+            .send(Command::AddDictionary {
+                data: data.clone(),
+                result,
+            })
+            .await
+            .map_err(|_| Error::SendError)?;
+
+        let id = command_result.await??;
+
+        self.dictionaries
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .add(Dictionary { id, data });
+
+        Ok((id, count))
+    }
+
+    /// Rewrite all unencrypted content at `level` and, if `use_dictionary` is set, small content
+    /// with the most recent dictionary.
+    pub async fn recompress(
+        &self,
+        level: i32,
+        use_dictionary: bool,
+    ) -> Result<Recompressed, Error> {
+        let dictionary = self.current_dictionary().filter(|_| use_dictionary);
+
+        let (result, command_result) = oneshot::channel();
+        self.sender
+            .send(Command::Recompress {
+                level,
+                dictionary,
+                result,
+            })
+            .await
+            .map_err(|_| Error::SendError)?;
+
+        command_result.await?
+    }
+
+    /// Dictionary to compress new entries with.
+    fn current_dictionary(&self) -> Option<Arc<Dictionary>> {
+        self.dictionaries
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .current
+            .clone()
+    }
+
+    /// Look up dictionary `id`, fetching it from the storage if it was added by another process.
+    async fn dictionary(&self, id: Option<u32>) -> Result<Option<Arc<Dictionary>>, Error> {
+        let Some(id) = id else {
+            return Ok(None);
+        };
+
+        if let Some(dictionary) = self
+            .dictionaries
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .by_id
+            .get(&id)
+        {
+            return Ok(Some(dictionary.clone()));
+        }
+
+        let (result, command_result) = oneshot::channel();
+        self.reader
+            .send(Command::GetDictionary { id, result })
+            .await
+            .map_err(|_| Error::SendError)?;
+
+        let dictionary = command_result.await??;
+
+        Ok(Some(
+            self.dictionaries
+                .write()
+                .unwrap_or_else(PoisonError::into_inner)
+                .add(dictionary),
+        ))
+    }
+
+    /// Search unencrypted entries for the FTS5 `query` and return matches ordered by relevance.
+    pub async fn search(&self, query: &str) -> Result<Vec<SearchEntry>, Error> {
+        let (result, command_result) = oneshot::channel();
+        self.sender
+            .send(Command::Search {
+                query: query.to_string(),
+                result,
+            })
+            .await
+            .map_err(|_| Error::SendError)?;
+        command_result.await?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU32;
+    use std::time::{Duration, SystemTime};
+
+    use crate::id::Slug;
+
+    use super::*;
+
+    impl read::Entry {
+        /// Unwrap inner data or panic.
+        #[must_use]
+        pub fn unwrap_inner(self) -> read::Data {
+            match self {
+                read::Entry::Regular(data) | read::Entry::Burned(data) => data,
+            }
+        }
+    }
+
+    fn new_db() -> Result<Database, Box<dyn std::error::Error>> {
+        let (db, handler) = Database::new(Open::Memory)?;
+        tokio::spawn(handler);
+        Ok(db)
+    }
+
+    #[tokio::test]
+    async fn insert() -> Result<(), Box<dyn std::error::Error>> {
+        let db = new_db()?;
+
+        let entry = write::Entry {
+            text: "hello world".to_string(),
+            uid: Some(10),
+            ..Default::default()
+        };
+
+        let (id, _entry) = db.insert(entry).await?;
+
+        let entry = db.get(id, None).await?.unwrap_inner();
+        assert_eq!(entry.text, "hello world");
+        assert!(entry.metadata.uid.is_some());
+        assert_eq!(entry.metadata.uid.unwrap(), 10);
+
+        let result = db.get(Id::from(5678u32), None).await;
+        assert!(result.is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn insert_with_generator() -> Result<(), Box<dyn std::error::Error>> {
+        let config = Config {
+            generator: Generator::random(crate::id::Alphabet::Unambiguous, 8)?,
+            ..Default::default()
+        };
+        let (db, handler) = Database::open(Backend::Sqlite, Open::Memory, config)?;
+        tokio::spawn(handler);
+
+        let entry = write::Entry {
+            text: "hello world".to_string(),
+            ..Default::default()
+        };
+
+        let (id, _entry) = db.insert(entry).await?;
+        let id = id.to_string();
+        assert_eq!(id.len(), 8);
+        assert_eq!(
+            db.get(id.parse()?, None).await?.unwrap_inner().text,
+            "hello world"
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn insert_slug() -> Result<(), Box<dyn std::error::Error>> {
+        let db = new_db()?;
+        let slug: Slug = "deploy-notes".parse()?;
+
+        let entry = write::Entry {
+            text: "hello world".to_string(),
+            slug: Some(slug),
+            ..Default::default()
+        };
+
+        let (id, _entry) = db.insert(entry).await?;
+        assert_eq!(id.to_string(), "deploy-notes");
+        assert_eq!(db.get(id, None).await?.unwrap_inner().text, "hello world");
+
+        let entry = write::Entry {
+            text: "taken".to_string(),
+            slug: Some(slug),
+            ..Default::default()
+        };
+
+        assert!(matches!(db.insert(entry).await, Err(Error::Conflict)));
+
+        let entry = write::Entry {
+            text: "fork".to_string(),
+            parent: Some(id),
+            ..Default::default()
+        };
+
+        let (fork, _entry) = db.insert(entry).await?;
+        assert_eq!(db.get_metadata(fork).await?.parent, Some(id));
+        assert!(db.list().await?.iter().any(|entry| entry.id == id));
+
+        Ok(())
+    }
+
+    #[tokio::test]
     async fn next_uid() -> Result<(), Box<dyn std::error::Error>> {
         let db = new_db()?;
 
@@ -815,14 +2297,295 @@ mod tests {
         let uid2 = db.next_uid().await?;
         let uid3 = db.next_uid().await?;
 
-        assert!(uid1 < uid2);
-        assert!(uid2 < uid3);
+        assert!(uid1 < uid2);
+        assert!(uid2 < uid3);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn expired_does_not_exist() -> Result<(), Box<dyn std::error::Error>> {
+        let db = new_db()?;
+
+        let entry = write::Entry {
+            expires: Some(NonZeroU32::new(1).unwrap()),
+            ..Default::default()
+        };
+
+        let (id, _entry) = db.insert(entry).await?;
+
+        tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+
+        let result = db.get(id, None).await;
+        assert!(matches!(result, Err(Error::NotFound)));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn max_views() -> Result<(), Box<dyn std::error::Error>> {
+        let db = new_db()?;
+
+        let entry = write::Entry {
+            text: "secret".to_string(),
+            max_views: NonZeroU32::new(3),
+            ..Default::default()
+        };
+
+        let (id, _entry) = db.insert(entry).await?;
+        assert_eq!(db.get_metadata(id).await?.remaining_views(), Some(3));
+
+        assert!(matches!(db.get(id, None).await?, read::Entry::Regular(_)));
+        assert_eq!(db.get_metadata(id).await?.remaining_views(), Some(2));
+
+        assert!(matches!(db.get(id, None).await?, read::Entry::Regular(_)));
+        assert!(matches!(db.get(id, None).await?, read::Entry::Burned(_)));
+        assert!(matches!(db.get(id, None).await, Err(Error::NotFound)));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn flush_views() -> Result<(), Box<dyn std::error::Error>> {
+        let db = new_db()?;
+
+        let entry = write::Entry {
+            text: "hello world".to_string(),
+            ..Default::default()
+        };
+        let (id, _entry) = db.insert(entry).await?;
+
+        db.get(id, None).await?;
+        let data = db.get(id, None).await?.unwrap_inner();
+        assert_eq!(data.metadata.views, 1);
+
+        let metadata = db.get_metadata(id).await?;
+        assert_eq!(metadata.views, 2);
+        assert!(metadata.accessed.is_some());
+
+        db.flush_views().await?;
+        assert!(db.views.lock().unwrap().is_empty());
+
+        let metadata = db.get_metadata(id).await?;
+        assert_eq!(metadata.views, 2);
+        assert!(metadata.accessed.is_some());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn views_not_counted_without_password() -> Result<(), Box<dyn std::error::Error>> {
+        let db = new_db()?;
+
+        let entry = write::Entry {
+            text: "secret".to_string(),
+            password: Some("password".to_string()),
+            max_views: NonZeroU32::new(1),
+            ..Default::default()
+        };
+
+        let (id, _entry) = db.insert(entry).await?;
+
+        assert!(matches!(db.get(id, None).await, Err(Error::NoPassword)));
+        assert_eq!(db.get_metadata(id).await?.views, 0);
+
+        let password = Password::from("password".as_bytes().to_vec());
+        assert!(matches!(
+            db.get(id, Some(password)).await?,
+            read::Entry::Burned(_)
+        ));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn delete() -> Result<(), Box<dyn std::error::Error>> {
+        let db = new_db()?;
+
+        let (id, _entry) = db.insert(write::Entry::default()).await?;
+
+        assert!(db.get(id, None).await.is_ok());
+        assert!(db.delete(id).await.is_ok());
+        assert!(db.get(id, None).await.is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn delete_for() -> Result<(), Box<dyn std::error::Error>> {
+        let db = new_db()?;
+
+        let uid = 42;
+
+        let entry = write::Entry {
+            uid: Some(uid),
+            ..Default::default()
+        };
+        let (id, _entry) = db.insert(entry).await?;
+
+        assert!(db.get(id, None).await.is_ok());
+        assert!(db.delete_for(id, &[uid]).await.is_ok());
+        assert!(db.get(id, None).await.is_err());
+
+        let entry = write::Entry {
+            uid: Some(uid),
+            ..Default::default()
+        };
+        let (id, _entry) = db.insert(entry).await?;
+
+        let incorrect_uid = 99;
+        assert!(matches!(
+            db.delete_for(id, &[incorrect_uid]).await,
+            Err(Error::Delete)
+        ));
+
+        // Multi-uid: passes if any uid in the list matches.
+        let entry = write::Entry {
+            uid: Some(uid),
+            ..Default::default()
+        };
+        let (id, _entry) = db.insert(entry).await?;
+
+        assert!(db.delete_for(id, &[99, uid, 7]).await.is_ok());
+        assert!(db.get(id, None).await.is_err());
+
+        // Empty slice: nothing to authorize against.
+        let entry = write::Entry {
+            uid: Some(uid),
+            ..Default::default()
+        };
+        let (id, _entry) = db.insert(entry).await?;
+
+        assert!(matches!(db.delete_for(id, &[]).await, Err(Error::Delete)));
+
+        assert!(db.get(id, None).await.is_ok());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn trash() -> Result<(), Box<dyn std::error::Error>> {
+        let config = Config {
+            trash_period: Some(Duration::from_secs(1)),
+            ..Default::default()
+        };
+        let (db, handler) = Database::open(Backend::Sqlite, Open::Memory, config)?;
+        tokio::spawn(handler);
+
+        let entry = write::Entry {
+            text: "hello".to_string(),
+            uid: Some(42),
+            ..Default::default()
+        };
+        let (id, _entry) = db.insert(entry).await?;
+
+        db.delete_for(id, &[42]).await?;
+        assert!(matches!(db.get(id, None).await, Err(Error::NotFound)));
+        assert!(matches!(db.get_metadata(id).await, Err(Error::NotFound)));
+        assert!(matches!(db.delete_for(id, &[42]).await, Err(Error::Delete)));
+        assert_eq!(db.get_trashed(id).await?.uid, Some(42));
+        assert!(db.list().await?[0].deleted.is_some());
+
+        assert!(matches!(
+            db.restore_for(id, &[99]).await,
+            Err(Error::Restore)
+        ));
+        db.restore_for(id, &[42]).await?;
+        assert_eq!(db.get(id, None).await?.unwrap_inner().text, "hello");
+        assert!(matches!(db.get_trashed(id).await, Err(Error::NotFound)));
+
+        assert_eq!(db.delete_many(vec![id]).await?, 1);
+        db.restore(id).await?;
+        assert!(db.get_metadata(id).await.is_ok());
+
+        db.delete_many(vec![id]).await?;
+        assert!(db.purge().await?.is_empty());
+
+        tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+
+        assert!(matches!(db.get_trashed(id).await, Err(Error::NotFound)));
+        assert!(matches!(db.restore(id).await, Err(Error::NotFound)));
+        assert_eq!(db.purge().await?, vec![id]);
+        assert!(db.list().await?.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn audit() -> Result<(), Box<dyn std::error::Error>> {
+        let config = Config {
+            trash_period: Some(Duration::from_secs(60)),
+            actor: "test",
+            ..Default::default()
+        };
+        let (db, handler) = Database::open(Backend::Sqlite, Open::Memory, config)?;
+        tokio::spawn(handler);
+
+        let entry = write::Entry {
+            uid: Some(42),
+            max_views: NonZeroU32::new(1),
+            ..Default::default()
+        };
+        let (burned, _entry) = db.insert(entry).await?;
+        db.get(burned, None).await?;
+
+        let entry = write::Entry {
+            uid: Some(42),
+            ..Default::default()
+        };
+        let (deleted, _entry) = db.insert(entry).await?;
+        db.delete_for(deleted, &[42]).await?;
+        db.restore(deleted).await?;
+
+        let entry = write::Entry {
+            expires: NonZeroU32::new(1),
+            ..Default::default()
+        };
+        let (expired, _entry) = db.insert(entry).await?;
+        tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+        assert!(matches!(db.get(expired, None).await, Err(Error::NotFound)));
+
+        let events = |records: Vec<audit::Record>| {
+            records
+                .into_iter()
+                .map(|record| (record.id, record.event))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            events(db.audit(None, None).await?),
+            vec![
+                (burned, audit::Event::Create),
+                (burned, audit::Event::Burn),
+                (deleted, audit::Event::Create),
+                (deleted, audit::Event::Delete),
+                (deleted, audit::Event::Restore),
+                (expired, audit::Event::Create),
+                (expired, audit::Event::Expire),
+            ]
+        );
+
+        let records = db.audit(Some(burned), None).await?;
+        assert_eq!(records.len(), 2);
+        assert!(
+            records
+                .iter()
+                .all(|record| record.uid == Some(42) && record.actor == "test")
+        );
+
+        let since = records[0].created.clone();
+        assert_eq!(db.audit(None, Some(since)).await?.len(), 7);
+        assert!(
+            db.audit(None, Some(String::from("9999-01-01")))
+                .await?
+                .is_empty()
+        );
 
         Ok(())
     }
 
     #[tokio::test]
-    async fn expired_does_not_exist() -> Result<(), Box<dyn std::error::Error>> {
+    async fn purge() -> Result<(), Box<dyn std::error::Error>> {
         let db = new_db()?;
 
         let entry = write::Entry {
@@ -834,119 +2597,467 @@ mod tests {
 
         tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
 
-        let result = db.get(id, None).await;
-        assert!(matches!(result, Err(Error::NotFound)));
+        let ids = db.purge().await?;
+        assert_eq!(ids.len(), 1);
+        assert_eq!(ids[0], id);
 
         Ok(())
     }
 
     #[tokio::test]
-    async fn delete() -> Result<(), Box<dyn std::error::Error>> {
+    async fn get_metadata() -> Result<(), Box<dyn std::error::Error>> {
         let db = new_db()?;
 
-        let (id, _entry) = db.insert(write::Entry::default()).await?;
+        let entry = write::Entry {
+            text: "test content".to_string(),
+            uid: Some(42),
+            title: Some("Test Title".to_string()),
+            expires: Some(NonZeroU32::new(3600).unwrap()),
+            ..Default::default()
+        };
 
-        assert!(db.get(id, None).await.is_ok());
-        assert!(db.delete(id).await.is_ok());
-        assert!(db.get(id, None).await.is_err());
+        let (id, _entry) = db.insert(entry).await?;
+
+        let metadata = db.get_metadata(id).await?;
+        assert_eq!(metadata.uid, Some(42));
+        assert_eq!(metadata.title, Some("Test Title".to_string()));
+
+        let expiration = metadata.expiration.unwrap().duration.as_secs();
+        assert!(expiration <= 3600);
+        // We have a problem if the test takes more than 10 seconds.
+        assert!(expiration >= 3590);
 
         Ok(())
     }
 
     #[tokio::test]
-    async fn delete_for() -> Result<(), Box<dyn std::error::Error>> {
+    async fn expires_at() -> Result<(), Box<dyn std::error::Error>> {
         let db = new_db()?;
+        let now = SystemTime::now();
 
-        let uid = 42;
+        let entry = write::Entry {
+            expires: Some(NonZeroU32::new(60).unwrap()),
+            expires_at: Some(now + Duration::from_secs(7200)),
+            ..Default::default()
+        };
+
+        let (id, _entry) = db.insert(entry).await?;
+
+        let expiration = db.get_metadata(id).await?.expiration.unwrap();
+        assert!((7190..=7200).contains(&expiration.duration.as_secs()));
 
         let entry = write::Entry {
-            uid: Some(uid),
+            expires_at: Some(now - Duration::from_secs(60)),
             ..Default::default()
         };
+
         let (id, _entry) = db.insert(entry).await?;
+        assert!(matches!(db.get(id, None).await, Err(Error::NotFound)));
 
-        assert!(db.get(id, None).await.is_ok());
-        assert!(db.delete_for(id, &[uid]).await.is_ok());
-        assert!(db.get(id, None).await.is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn edit_metadata() -> Result<(), Box<dyn std::error::Error>> {
+        let db = new_db()?;
 
         let entry = write::Entry {
-            uid: Some(uid),
+            text: "key: value".to_string(),
+            title: Some("notes".to_string()),
+            uid: Some(42),
             ..Default::default()
         };
+
         let (id, _entry) = db.insert(entry).await?;
+        assert_eq!(db.get_metadata(id).await?.extension, None);
 
-        let incorrect_uid = 99;
         assert!(matches!(
-            db.delete_for(id, &[incorrect_uid]).await,
-            Err(Error::Delete)
+            db.edit_metadata(id, &[7], None, Some("yaml".to_string()))
+                .await,
+            Err(Error::Edit)
         ));
 
-        // Multi-uid: passes if any uid in the list matches.
+        db.edit_metadata(id, &[42], None, Some("yaml".to_string()))
+            .await?;
+        let metadata = db.get_metadata(id).await?;
+        assert_eq!(metadata.title.as_deref(), Some("notes"));
+        assert_eq!(metadata.extension.as_deref(), Some("yaml"));
+
+        db.edit_metadata(id, &[42], Some("config".to_string()), None)
+            .await?;
+        assert_eq!(db.search("notes").await?.len(), 0);
+        assert_eq!(db.search("config").await?.len(), 1);
+
+        db.edit_metadata(id, &[42], Some(String::new()), Some(String::new()))
+            .await?;
+        let metadata = db.get_metadata(id).await?;
+        assert_eq!(metadata.title, None);
+        assert_eq!(metadata.extension, None);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn set_expiration() -> Result<(), Box<dyn std::error::Error>> {
+        let db = new_db()?;
+
         let entry = write::Entry {
-            uid: Some(uid),
+            uid: Some(42),
+            expires: Some(NonZeroU32::new(60).unwrap()),
             ..Default::default()
         };
+
         let (id, _entry) = db.insert(entry).await?;
 
-        assert!(db.delete_for(id, &[99, uid, 7]).await.is_ok());
-        assert!(db.get(id, None).await.is_err());
+        let expires = SystemTime::now() + Duration::from_secs(7200);
+        assert!(matches!(
+            db.set_expiration(id, &[7], Some(expires)).await,
+            Err(Error::Edit)
+        ));
+
+        db.set_expiration(id, &[7, 42], Some(expires)).await?;
+        let expiration = db.get_metadata(id).await?.expiration.unwrap();
+        assert!((7190..=7200).contains(&expiration.duration.as_secs()));
+
+        db.set_expiration(id, &[42], None).await?;
+        assert!(db.get_metadata(id).await?.expiration.is_none());
 
-        // Empty slice: nothing to authorize against.
         let entry = write::Entry {
-            uid: Some(uid),
+            uid: Some(42),
+            expires_at: Some(SystemTime::now() - Duration::from_secs(60)),
             ..Default::default()
         };
+
         let (id, _entry) = db.insert(entry).await?;
+        assert!(matches!(
+            db.set_expiration(id, &[42], None).await,
+            Err(Error::Edit)
+        ));
+        assert!(matches!(db.get(id, None).await, Err(Error::NotFound)));
 
-        assert!(matches!(db.delete_for(id, &[]).await, Err(Error::Delete)));
+        Ok(())
+    }
 
-        assert!(db.get(id, None).await.is_ok());
+    #[tokio::test]
+    async fn stats() -> Result<(), Box<dyn std::error::Error>> {
+        let db = new_db()?;
+
+        let entry = write::Entry {
+            text: "a".repeat(1000),
+            ..Default::default()
+        };
+
+        let (id, _entry) = db.insert(entry).await?;
+
+        let metadata = db.get_metadata(id).await?;
+        assert!(metadata.created.is_some());
+        assert!(metadata.accessed.is_none());
+        assert_eq!(metadata.size, Some(1000));
+        assert!(metadata.compressed_size.is_some_and(|size| size < 1000));
+
+        db.get(id, None).await?;
+
+        let metadata = db.get_metadata(id).await?;
+        assert!(metadata.accessed.is_some());
+
+        let entries = db.list().await?;
+        assert_eq!(entries[0].size, Some(1000));
+        assert_eq!(entries[0].accessed, metadata.accessed);
 
         Ok(())
     }
 
     #[tokio::test]
-    async fn purge() -> Result<(), Box<dyn std::error::Error>> {
+    async fn edit() -> Result<(), Box<dyn std::error::Error>> {
         let db = new_db()?;
 
         let entry = write::Entry {
-            expires: Some(NonZeroU32::new(1).unwrap()),
+            text: "first".to_string(),
+            uid: Some(42),
             ..Default::default()
         };
 
         let (id, _entry) = db.insert(entry).await?;
+        assert_eq!(db.get_metadata(id).await?.revision, 1);
 
-        tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+        let entry = write::Entry {
+            text: "second".to_string(),
+            ..Default::default()
+        };
 
-        let ids = db.purge().await?;
-        assert_eq!(ids.len(), 1);
-        assert_eq!(ids[0], id);
+        assert_eq!(db.edit(id, &[42], entry).await?, 2);
+
+        let entry = db.get(id, None).await?.unwrap_inner();
+        assert_eq!(entry.text, "second");
+        assert_eq!(entry.metadata.revision, 2);
+
+        assert_eq!(db.get_revision(id, 1, None).await?.text, "first");
+        assert_eq!(db.get_revision(id, 2, None).await?.text, "second");
+        assert!(matches!(
+            db.get_revision(id, 0, None).await,
+            Err(Error::NotFound)
+        ));
+        assert!(matches!(
+            db.get_revision(id, 3, None).await,
+            Err(Error::NotFound)
+        ));
+
+        let entry = write::Entry {
+            text: "third".to_string(),
+            ..Default::default()
+        };
+
+        assert!(matches!(db.edit(id, &[99], entry).await, Err(Error::Edit)));
+        assert_eq!(db.get(id, None).await?.unwrap_inner().text, "second");
 
         Ok(())
     }
 
     #[tokio::test]
-    async fn get_metadata() -> Result<(), Box<dyn std::error::Error>> {
+    async fn edit_max_views() -> Result<(), Box<dyn std::error::Error>> {
         let db = new_db()?;
 
         let entry = write::Entry {
-            text: "test content".to_string(),
+            text: "secret".to_string(),
             uid: Some(42),
-            title: Some("Test Title".to_string()),
-            expires: Some(NonZeroU32::new(3600).unwrap()),
+            max_views: NonZeroU32::new(1),
             ..Default::default()
         };
 
         let (id, _entry) = db.insert(entry).await?;
 
-        let metadata = db.get_metadata(id).await?;
-        assert_eq!(metadata.uid, Some(42));
-        assert_eq!(metadata.title, Some("Test Title".to_string()));
+        assert!(matches!(
+            db.edit(id, &[42], write::Entry::default()).await,
+            Err(Error::Edit)
+        ));
+        assert!(matches!(
+            db.get_revision(id, 1, None).await,
+            Err(Error::NotFound)
+        ));
 
-        let expiration = metadata.expiration.unwrap().duration.as_secs();
-        assert!(expiration <= 3600);
-        // We have a problem if the test takes more than 10 seconds.
-        assert!(expiration >= 3590);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn delete_removes_revisions() -> Result<(), Box<dyn std::error::Error>> {
+        let db = new_db()?;
+
+        let entry = write::Entry {
+            uid: Some(42),
+            ..Default::default()
+        };
+
+        let (id, _entry) = db.insert(entry).await?;
+        db.edit(id, &[42], write::Entry::default()).await?;
+        db.delete_for(id, &[42]).await?;
+
+        assert!(matches!(
+            db.get_revision(id, 1, None).await,
+            Err(Error::NotFound)
+        ));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn parent() -> Result<(), Box<dyn std::error::Error>> {
+        let db = new_db()?;
+
+        let (parent, _entry) = db.insert(write::Entry::default()).await?;
+        assert!(db.get_metadata(parent).await?.parent.is_none());
+
+        let entry = write::Entry {
+            parent: Some(parent),
+            ..Default::default()
+        };
+
+        let (id, _entry) = db.insert(entry).await?;
+        assert_eq!(db.get_metadata(id).await?.parent, Some(parent));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn insert_stream() -> Result<(), Box<dyn std::error::Error>> {
+        let db = new_db()?;
+
+        // Multi-byte characters end up split across chunks.
+        let text = format!("needle {}", "€uro ".repeat(500_000));
+
+        let entry = write::Entry {
+            title: Some("large".to_string()),
+            ..Default::default()
+        };
+        let (id, _entry) = db.insert_stream(entry, text.as_bytes()).await?;
+
+        let mut stream = db.stream(id, None).await?;
+        assert_eq!(stream.metadata.title.as_deref(), Some("large"));
+        assert!(stream.files().await?.is_none());
+
+        let mut streamed = String::new();
+        stream.reader.read_to_string(&mut streamed).await?;
+        assert_eq!(streamed, text);
+
+        assert_eq!(db.get(id, None).await?.unwrap_inner().text, text);
+        assert_eq!(db.search("needle").await?.len(), 1);
+
+        let (copy, _entry) = db
+            .insert_stream(write::Entry::default(), text.as_bytes())
+            .await?;
+        db.delete(id).await?;
+        assert_eq!(db.get(copy, None).await?.unwrap_inner().text, text);
+
+        let result = db
+            .insert_stream(write::Entry::default(), &b"hello \xff world"[..])
+            .await;
+        assert!(matches!(result, Err(Error::Utf8(_))));
+
+        let result = db
+            .insert_stream(write::Entry::default(), &"hello €".as_bytes()[..7])
+            .await;
+        assert!(matches!(result, Err(Error::Utf8(_))));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn stream_dropped() -> Result<(), Box<dyn std::error::Error>> {
+        let db = new_db()?;
+        let entry = write::Entry {
+            text: "hello world\n".repeat(1000),
+            ..Default::default()
+        };
+        let (id, _entry) = db.insert(entry).await?;
+
+        // A stream that is dropped while a chunk is being read no longer awaits the result.
+        let blob = db.get_blob(id).await?.ok_or("no blob")?.blob;
+        let (result, command_result) = oneshot::channel();
+        drop(command_result);
+        db.reader
+            .send(Command::ReadBlob {
+                blob,
+                offset: 0,
+                result,
+            })
+            .await?;
+
+        assert_eq!(db.get_metadata(id).await?.size, Some(12000));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn stream_zstd() -> Result<(), Box<dyn std::error::Error>> {
+        let db = new_db()?;
+        let text = "hello world\n".repeat(1000);
+
+        let entry = write::Entry {
+            text: text.clone(),
+            ..Default::default()
+        };
+        let (id, _entry) = db.insert(entry).await?;
+
+        let mut stream = db.stream_zstd(id).await?.unwrap();
+        let mut frame = Vec::new();
+        stream.reader.read_to_end(&mut frame).await?;
+        assert_eq!(zstd::decode_all(frame.as_slice())?, text.as_bytes());
+        assert_eq!(db.get_metadata(id).await?.views, 1);
+
+        let entry = write::Entry {
+            text: text.clone(),
+            password: Some("password".to_string()),
+            ..Default::default()
+        };
+        let (encrypted, _entry) = db.insert(entry).await?;
+        assert!(db.stream_zstd(encrypted).await?.is_none());
+
+        let mut entry = write::Entry::default();
+        entry.set_files(&[
+            crate::files::File {
+                name: "a.txt".to_string(),
+                text: text.clone(),
+            },
+            crate::files::File {
+                name: "b.txt".to_string(),
+                text: text.clone(),
+            },
+        ])?;
+        let (files, _entry) = db.insert(entry).await?;
+        assert!(db.stream_zstd(files).await?.is_none());
+
+        // Regular content that happens to look like a multi-file paste is just text.
+        let entry = write::Entry {
+            text: db.get(files, None).await?.unwrap_inner().text,
+            ..Default::default()
+        };
+        let (text_only, _entry) = db.insert(entry).await?;
+        assert!(db.stream_zstd(text_only).await?.is_some());
+        assert!(
+            db.get(text_only, None)
+                .await?
+                .unwrap_inner()
+                .files()
+                .is_none()
+        );
+
+        let entry = write::Entry {
+            text: "hi".to_string(),
+            ..Default::default()
+        };
+        let (tiny, _entry) = db.insert(entry).await?;
+        assert!(db.stream_zstd(tiny).await?.is_none());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn search() -> Result<(), Box<dyn std::error::Error>> {
+        let db = new_db()?;
+
+        let entry = write::Entry {
+            text: "fn main() { println!(\"hello\"); }".to_string(),
+            title: Some("hello world".to_string()),
+            uid: Some(42),
+            ..Default::default()
+        };
+        let (plain, _entry) = db.insert(entry).await?;
+
+        let entry = write::Entry {
+            text: "fn main() { println!(\"secret\"); }".to_string(),
+            password: Some("password".to_string()),
+            ..Default::default()
+        };
+        db.insert(entry).await?;
+
+        let entry = write::Entry {
+            text: "println".to_string(),
+            expires: Some(NonZeroU32::new(1).unwrap()),
+            ..Default::default()
+        };
+        let (expiring, _entry) = db.insert(entry).await?;
+
+        let results = db.search("main").await?;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, plain);
+        assert_eq!(results[0].title.as_deref(), Some("hello world"));
+        assert!(results[0].snippet.contains("hello"));
+
+        assert!(db.search("secret").await?.is_empty());
+
+        let entry = write::Entry {
+            text: "edited".to_string(),
+            ..Default::default()
+        };
+        db.edit(plain, &[42], entry).await?;
+        assert!(db.search("main").await?.is_empty());
+        assert_eq!(db.search("edited").await?.len(), 1);
+
+        tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+        assert!(db.search("println").await?.is_empty());
+        assert_eq!(db.purge().await?, vec![expiring]);
+
+        db.delete_many(vec![plain]).await?;
+        assert!(db.search("edited").await?.is_empty());
 
         Ok(())
     }
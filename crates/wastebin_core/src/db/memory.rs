@@ -0,0 +1,1002 @@
+//! Volatile storage backend without any SQLite dependency.
+
+use std::collections::HashMap;
+use std::collections::hash_map::Entry as MapEntry;
+use std::num::NonZeroU64;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use chacha20poly1305::XNonce;
+
+use crate::audit::{self, Event};
+use crate::codec::Encoding;
+use crate::db::read::{DatabaseEntry, ListEntry, Metadata};
+use crate::db::{Error, Storage, write};
+use crate::expiration::Expiration;
+use crate::id::{Generator, Id};
+
+/// Storage backend that keeps entries in a [`HashMap`] and loses them on reload. It does not store
+/// dictionaries, so entries are never compressed with one.
+#[derive(Default)]
+pub struct Memory {
+    entries: HashMap<i64, Entry>,
+    /// Deleted entries, when they were deleted and until when they can be restored
+    trash: HashMap<i64, (SystemTime, SystemTime, Entry)>,
+    uid: i64,
+    /// Upper bound for the size of all stored data in bytes
+    max_storage: Option<NonZeroU64>,
+    /// Generator of new identifiers
+    generator: Generator,
+    /// How long deleted entries stay restorable, deleted entries are removed right away if `None`
+    trash_period: Option<Duration>,
+    /// Audit log, oldest first
+    audit: Vec<audit::Record>,
+    /// Actor recorded with audit log events
+    actor: &'static str,
+    /// Data appended to unfinished uploads and when they were started
+    uploads: HashMap<i64, (SystemTime, Vec<u8>)>,
+}
+
+/// A stored entry.
+struct Entry {
+    id: Id,
+    data: Vec<u8>,
+    nonce: Option<XNonce>,
+    encoding: Encoding,
+    /// Data holds the files of a multi-file paste
+    multi_file: bool,
+    /// Data, nonce, encoding and whether the data holds files of previous revisions, oldest first
+    revisions: Vec<(Vec<u8>, Option<XNonce>, Encoding, bool)>,
+    uid: Option<i64>,
+    title: Option<String>,
+    extension: Option<String>,
+    parent: Option<Id>,
+    expires: Option<SystemTime>,
+    max_views: Option<u32>,
+    views: u32,
+    created: SystemTime,
+    accessed: Option<SystemTime>,
+    size: u64,
+    compressed_size: u64,
+}
+
+/// Format `time` like SQLite's `datetime()`, i.e. `YYYY-MM-DD HH:MM:SS` in UTC.
+pub(crate) fn format_datetime(time: SystemTime) -> String {
+    let secs = time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    let (days, secs) = (secs / 86400, secs % 86400);
+
+    // Civil date from days since epoch, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + u64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02}",
+        secs / 3600,
+        secs % 3600 / 60,
+        secs % 60
+    )
+}
+
+fn len_u64(data: &[u8]) -> u64 {
+    u64::try_from(data.len()).unwrap_or(u64::MAX)
+}
+
+impl Memory {
+    /// Limit the size of all stored data to `max_storage` bytes, see [`Memory::make_room`].
+    #[must_use]
+    pub fn with_max_storage(mut self, max_storage: Option<NonZeroU64>) -> Self {
+        self.max_storage = max_storage;
+        self
+    }
+
+    /// Generate identifiers of new entries with `generator`.
+    #[must_use]
+    pub fn with_generator(mut self, generator: Generator) -> Self {
+        self.generator = generator;
+        self
+    }
+
+    /// Keep deleted entries restorable for `trash_period` before they are purged.
+    #[must_use]
+    pub fn with_trash_period(mut self, trash_period: Option<Duration>) -> Self {
+        self.trash_period = trash_period;
+        self
+    }
+
+    /// Record audit log events as caused by `actor`.
+    #[must_use]
+    pub fn with_actor(mut self, actor: &'static str) -> Self {
+        self.actor = actor;
+        self
+    }
+
+    /// Append `event` of entry `id` owned by `uid` to the audit log.
+    fn record(&mut self, event: Event, id: Id, uid: Option<i64>) {
+        self.audit.push(audit::Record {
+            id,
+            uid,
+            event,
+            actor: self.actor.to_string(),
+            created: format_datetime(SystemTime::now()),
+        });
+    }
+
+    /// Remove entry `id` if any of `uids` owns it or regardless of its owner if `uids` is `None`.
+    /// The entry is moved to the trash if a trash period is configured.
+    fn remove(&mut self, id: Id, uids: Option<&[i64]>) -> bool {
+        let MapEntry::Occupied(entry) = self.entries.entry(id.to_i64()) else {
+            return false;
+        };
+
+        if uids.is_some_and(|uids| !entry.get().is_owned_by(uids)) {
+            return false;
+        }
+
+        let (key, entry) = entry.remove_entry();
+        self.record(Event::Delete, entry.id, entry.uid);
+
+        if let Some(trash_period) = self.trash_period {
+            let now = SystemTime::now();
+            self.trash.insert(key, (now, now + trash_period, entry));
+        }
+
+        true
+    }
+
+    /// Evict entries in the trash, oldest first, and entries that expire, soonest first, until
+    /// `size` additional bytes fit next to stored and uploaded data into the storage quota. Entry `keep` is never evicted and
+    /// nothing is evicted if there is not enough space in the end.
+    fn make_room(&mut self, size: u64, keep: Option<Id>) -> Result<(), Error> {
+        let Some(max_storage) = self.max_storage else {
+            return Ok(());
+        };
+
+        let used = self
+            .entries
+            .values()
+            .chain(self.trash.values().map(|(_, _, entry)| entry))
+            .map(Entry::stored_size)
+            .chain(self.uploads.values().map(|(_, data)| len_u64(data)))
+            .sum::<u64>();
+        let mut needed = used.saturating_add(size).saturating_sub(max_storage.get());
+
+        if needed == 0 {
+            return Ok(());
+        }
+
+        let mut trashed = self
+            .trash
+            .iter()
+            .map(|(key, (deleted, _, entry))| (*deleted, *key, entry.id, entry.stored_size()))
+            .collect::<Vec<_>>();
+
+        trashed.sort_unstable_by_key(|(deleted, key, _, _)| (*deleted, *key));
+
+        let mut evict_trash = Vec::new();
+
+        for (_, key, id, stored_size) in trashed {
+            evict_trash.push((key, id));
+            needed = needed.saturating_sub(stored_size);
+
+            if needed == 0 {
+                break;
+            }
+        }
+
+        let evict_trash = |memory: &mut Self| {
+            for (key, id) in evict_trash {
+                if let Some((_, _, entry)) = memory.trash.remove(&key) {
+                    memory.record(Event::Evict, id, entry.uid);
+                    tracing::info!("evicted deleted paste {id} to stay within storage quota");
+                }
+            }
+        };
+
+        if needed == 0 {
+            evict_trash(self);
+            return Ok(());
+        }
+
+        let mut candidates = self
+            .entries
+            .iter()
+            .filter(|(id, _)| keep.is_none_or(|keep| keep.to_i64() != **id))
+            .filter_map(|(id, entry)| {
+                Some((
+                    entry.expires?,
+                    entry.created,
+                    *id,
+                    entry.id,
+                    entry.stored_size(),
+                ))
+            })
+            .collect::<Vec<_>>();
+
+        candidates.sort_unstable_by_key(|(expires, created, key, _, _)| (*expires, *created, *key));
+
+        let mut evict = Vec::new();
+
+        for (_, _, key, id, stored_size) in candidates {
+            evict.push((key, id));
+            needed = needed.saturating_sub(stored_size);
+
+            if needed == 0 {
+                evict_trash(self);
+
+                for (key, id) in evict {
+                    if let Some(entry) = self.entries.remove(&key) {
+                        self.record(Event::Evict, id, entry.uid);
+                        tracing::info!("evicted paste {id} to stay within storage quota");
+                    }
+                }
+
+                return Ok(());
+            }
+        }
+
+        Err(Error::QuotaExceeded)
+    }
+
+    /// Insert `entry` with `data` of the uncompressed `size` as its content.
+    fn insert_content(
+        &mut self,
+        entry: write::Entry,
+        data: Vec<u8>,
+        nonce: Option<XNonce>,
+        encoding: Encoding,
+        size: u64,
+    ) -> Result<(Id, write::Entry), Error> {
+        let now = SystemTime::now();
+        let mut reclaimed = (None, None);
+
+        let id = match entry.slug {
+            Some(slug) => {
+                let id = Id::from(slug);
+
+                // An expired entry or one that can no longer be restored from the trash does not
+                // hold on to its slug even if it has not been purged yet.
+                if self
+                    .entries
+                    .get(&id.to_i64())
+                    .is_some_and(|existing| !existing.is_expired(now))
+                    || self
+                        .trash
+                        .get(&id.to_i64())
+                        .is_some_and(|(_, purge_after, _)| *purge_after > now)
+                {
+                    return Err(Error::Conflict);
+                }
+
+                reclaimed = (
+                    self.entries.remove(&id.to_i64()),
+                    self.trash.remove(&id.to_i64()),
+                );
+
+                id
+            }
+            None => loop {
+                let id = self.generator.generate();
+
+                if !self.entries.contains_key(&id.to_i64())
+                    && !self.trash.contains_key(&id.to_i64())
+                {
+                    break id;
+                }
+            },
+        };
+
+        let (expired, trashed) = reclaimed;
+
+        if let Err(err) = self.make_room(len_u64(&data), None) {
+            // Like the rolled back transaction of the SQLite backend, a failed insert leaves the
+            // previous holder of the slug in place.
+            if let Some(expired) = expired {
+                self.entries.insert(id.to_i64(), expired);
+            }
+
+            if let Some(trashed) = trashed {
+                self.trash.insert(id.to_i64(), trashed);
+            }
+
+            return Err(err);
+        }
+
+        if let Some(expired) = expired {
+            self.record(Event::Purge, id, expired.uid);
+        }
+
+        if let Some((_, _, trashed)) = trashed {
+            self.record(Event::Purge, id, trashed.uid);
+        }
+
+        let stored = Entry {
+            id,
+            compressed_size: len_u64(&data),
+            size,
+            data,
+            nonce,
+            encoding,
+            multi_file: entry.multi_file,
+            revisions: Vec::new(),
+            uid: entry.uid,
+            title: entry.title.clone(),
+            extension: entry.extension.clone(),
+            parent: entry.parent,
+            expires: entry.expires_at.or_else(|| {
+                entry
+                    .expires
+                    .map(|expires| now + Duration::from_secs(expires.get().into()))
+            }),
+            max_views: entry.max_views.map(std::num::NonZeroU32::get),
+            views: 0,
+            created: now,
+            accessed: None,
+        };
+
+        self.entries.insert(id.to_i64(), stored);
+        self.record(Event::Create, id, entry.uid);
+
+        Ok((id, entry))
+    }
+}
+
+impl Entry {
+    /// Size of the data of this entry and its revisions.
+    fn stored_size(&self) -> u64 {
+        self.revisions
+            .iter()
+            .map(|(data, _, _, _)| len_u64(data))
+            .sum::<u64>()
+            .saturating_add(len_u64(&self.data))
+    }
+
+    fn is_expired(&self, now: SystemTime) -> bool {
+        self.expires.is_some_and(|expires| expires < now)
+    }
+
+    fn is_owned_by(&self, uids: &[i64]) -> bool {
+        self.uid.is_some_and(|uid| uids.contains(&uid))
+    }
+
+    fn metadata(&self, now: SystemTime) -> Metadata {
+        let expiration = self
+            .expires
+            .and_then(|expires| expires.duration_since(now).ok())
+            .map(|remaining| (remaining + Duration::from_millis(500)).as_secs())
+            .filter(|secs| *secs > 0)
+            .map(|secs| Expiration {
+                duration: Duration::from_secs(secs),
+                default: false,
+            });
+
+        Metadata {
+            uid: self.uid,
+            title: self.title.clone(),
+            extension: self.extension.clone(),
+            expiration,
+            max_views: self.max_views,
+            views: self.views,
+            revision: u32::try_from(self.revisions.len()).map_or(u32::MAX, |n| n.saturating_add(1)),
+            parent: self.parent,
+            created: Some(format_datetime(self.created)),
+            accessed: self.accessed.map(format_datetime),
+            size: Some(self.size),
+            compressed_size: Some(self.compressed_size),
+            multi_file: self.multi_file,
+        }
+    }
+}
+
+impl Storage for Memory {
+    fn insert(
+        &mut self,
+        write::DatabaseEntry {
+            entry,
+            data,
+            nonce,
+            encoding,
+        }: write::DatabaseEntry,
+    ) -> Result<(Id, write::Entry), Error> {
+        let size = len_u64(entry.text.as_bytes());
+        self.insert_content(entry, data, nonce, encoding, size)
+    }
+
+    fn append(&mut self, upload: i64, _seq: u32, data: Vec<u8>) -> Result<(), Error> {
+        self.make_room(len_u64(&data), None)?;
+
+        self.uploads
+            .entry(upload)
+            .or_insert_with(|| (SystemTime::now(), Vec::new()))
+            .1
+            .extend_from_slice(&data);
+
+        Ok(())
+    }
+
+    fn insert_upload(
+        &mut self,
+        upload: i64,
+        write::Upload {
+            entry,
+            size,
+            encoding,
+            ..
+        }: write::Upload,
+    ) -> Result<(Id, write::Entry), Error> {
+        let data = self
+            .uploads
+            .remove(&upload)
+            .map(|(_, data)| data)
+            .unwrap_or_default();
+        self.insert_content(entry, data, None, encoding, size)
+    }
+
+    fn discard(&mut self, upload: i64) -> Result<(), Error> {
+        self.uploads.remove(&upload);
+        Ok(())
+    }
+
+    fn get(&self, id: Id) -> Result<DatabaseEntry, Error> {
+        let now = SystemTime::now();
+        let entry = self.entries.get(&id.to_i64()).ok_or(Error::NotFound)?;
+
+        Ok(DatabaseEntry {
+            data: entry.data.clone(),
+            metadata: entry.metadata(now),
+            expired: entry.is_expired(now),
+            nonce: entry.nonce,
+            encoding: entry.encoding,
+        })
+    }
+
+    fn get_metadata(&self, id: Id) -> Result<Metadata, Error> {
+        self.entries
+            .get(&id.to_i64())
+            .map(|entry| entry.metadata(SystemTime::now()))
+            .ok_or(Error::NotFound)
+    }
+
+    fn delete(&mut self, id: Id) -> Result<(), Error> {
+        if let Some(entry) = self.entries.remove(&id.to_i64()) {
+            self.record(Event::Expire, id, entry.uid);
+        }
+
+        Ok(())
+    }
+
+    fn delete_many(&mut self, ids: Vec<Id>) -> Result<usize, Error> {
+        Ok(ids.into_iter().filter(|id| self.remove(*id, None)).count())
+    }
+
+    fn delete_for(&mut self, id: Id, uids: &[i64]) -> Result<(), Error> {
+        if self.remove(id, Some(uids)) {
+            Ok(())
+        } else {
+            Err(Error::Delete)
+        }
+    }
+
+    fn get_trashed(&self, id: Id) -> Result<Metadata, Error> {
+        let now = SystemTime::now();
+
+        self.trash
+            .get(&id.to_i64())
+            .filter(|(_, purge_after, _)| *purge_after > now)
+            .map(|(_, _, entry)| entry.metadata(now))
+            .ok_or(Error::NotFound)
+    }
+
+    fn restore(&mut self, id: Id, uids: Option<&[i64]>) -> Result<(), Error> {
+        let now = SystemTime::now();
+
+        let restorable = self
+            .trash
+            .get(&id.to_i64())
+            .is_some_and(|(_, purge_after, entry)| {
+                *purge_after > now && uids.is_none_or(|uids| entry.is_owned_by(uids))
+            });
+
+        if !restorable {
+            return Err(if uids.is_some() {
+                Error::Restore
+            } else {
+                Error::NotFound
+            });
+        }
+
+        let (_, _, entry) = self.trash.remove(&id.to_i64()).ok_or(Error::NotFound)?;
+        self.record(Event::Restore, id, entry.uid);
+        self.entries.insert(id.to_i64(), entry);
+
+        Ok(())
+    }
+
+    fn edit(
+        &mut self,
+        id: Id,
+        uids: &[i64],
+        write::DatabaseEntry {
+            entry,
+            data,
+            nonce,
+            encoding,
+        }: write::DatabaseEntry,
+    ) -> Result<u32, Error> {
+        let now = SystemTime::now();
+
+        let editable = self.entries.get(&id.to_i64()).is_some_and(|stored| {
+            stored.is_owned_by(uids) && !stored.is_expired(now) && stored.max_views.is_none()
+        });
+
+        if !editable {
+            return Err(Error::Edit);
+        }
+
+        // The current content is kept as a revision, so the new one adds to the total.
+        self.make_room(len_u64(&data), Some(id))?;
+
+        let stored = self.entries.get_mut(&id.to_i64()).ok_or(Error::Edit)?;
+
+        let previous = std::mem::replace(&mut stored.data, data);
+        stored
+            .revisions
+            .push((previous, stored.nonce, stored.encoding, stored.multi_file));
+        stored.nonce = nonce;
+        stored.encoding = encoding;
+        stored.multi_file = entry.multi_file;
+        stored.size = len_u64(entry.text.as_bytes());
+        stored.compressed_size = len_u64(&stored.data);
+
+        Ok(stored.metadata(now).revision)
+    }
+
+    fn edit_metadata(
+        &mut self,
+        id: Id,
+        uids: &[i64],
+        title: Option<String>,
+        extension: Option<String>,
+    ) -> Result<(), Error> {
+        let now = SystemTime::now();
+
+        match self.entries.get_mut(&id.to_i64()) {
+            Some(stored) if stored.is_owned_by(uids) && !stored.is_expired(now) => {
+                if let Some(title) = title {
+                    stored.title = (!title.is_empty()).then_some(title);
+                }
+
+                if let Some(extension) = extension {
+                    stored.extension = (!extension.is_empty()).then_some(extension);
+                }
+
+                Ok(())
+            }
+            _ => Err(Error::Edit),
+        }
+    }
+
+    fn set_expiration(
+        &mut self,
+        id: Id,
+        uids: &[i64],
+        expires: Option<SystemTime>,
+    ) -> Result<(), Error> {
+        let now = SystemTime::now();
+
+        match self.entries.get_mut(&id.to_i64()) {
+            Some(stored) if stored.is_owned_by(uids) && !stored.is_expired(now) => {
+                stored.expires = expires;
+                Ok(())
+            }
+            _ => Err(Error::Edit),
+        }
+    }
+
+    fn get_revision(&self, id: Id, rev: u32) -> Result<DatabaseEntry, Error> {
+        let mut entry = self.get(id)?;
+
+        // Never hand out view-limited content without counting the view.
+        if entry.metadata.max_views.is_some() || rev == 0 || rev > entry.metadata.revision {
+            return Err(Error::NotFound);
+        }
+
+        if rev < entry.metadata.revision {
+            (
+                entry.data,
+                entry.nonce,
+                entry.encoding,
+                entry.metadata.multi_file,
+            ) = usize::try_from(rev - 1)
+                .ok()
+                .and_then(|idx| self.entries.get(&id.to_i64())?.revisions.get(idx))
+                .cloned()
+                .ok_or(Error::NotFound)?;
+        }
+
+        Ok(entry)
+    }
+
+    fn view(&mut self, id: Id) -> Result<bool, Error> {
+        let entry = self.entries.get_mut(&id.to_i64()).ok_or(Error::NotFound)?;
+        entry.views += 1;
+        entry.accessed = Some(SystemTime::now());
+
+        if entry
+            .max_views
+            .is_some_and(|max_views| entry.views >= max_views)
+        {
+            let uid = entry.uid;
+            self.entries.remove(&id.to_i64());
+            self.record(Event::Burn, id, uid);
+            return Ok(true);
+        }
+
+        Ok(false)
+    }
+
+    fn add_views(&mut self, views: &[(Id, u32, SystemTime)]) -> Result<(), Error> {
+        for (id, count, accessed) in views {
+            if let Some(entry) = self.entries.get_mut(&id.to_i64()) {
+                entry.views += count;
+                entry.accessed = Some(*accessed);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn next_uid(&mut self) -> Result<i64, Error> {
+        self.uid += 1;
+        Ok(self.uid)
+    }
+
+    fn list(&self) -> Result<Vec<ListEntry>, Error> {
+        let now = SystemTime::now();
+
+        let entries = self.entries.values().map(|entry| (None, entry));
+        let trash = self
+            .trash
+            .values()
+            .map(|(deleted, _, entry)| (Some(*deleted), entry));
+
+        Ok(entries
+            .chain(trash)
+            .map(|(deleted, entry)| ListEntry {
+                id: entry.id,
+                title: entry.title.clone(),
+                is_encrypted: entry.nonce.is_some(),
+                expiration: entry.expires.map(format_datetime),
+                is_expired: entry.is_expired(now),
+                created: Some(format_datetime(entry.created)),
+                accessed: entry.accessed.map(format_datetime),
+                size: Some(entry.size),
+                compressed_size: Some(entry.compressed_size),
+                deleted: deleted.map(format_datetime),
+            })
+            .collect())
+    }
+
+    fn purge(&mut self) -> Result<Vec<Id>, Error> {
+        let now = SystemTime::now();
+        let mut purged = Vec::new();
+
+        self.entries.retain(|_, entry| {
+            let expired = entry.is_expired(now);
+
+            if expired {
+                purged.push((entry.id, entry.uid));
+            }
+
+            !expired
+        });
+
+        self.trash.retain(|_, (_, purge_after, entry)| {
+            let restorable = *purge_after > now;
+
+            if !restorable {
+                purged.push((entry.id, entry.uid));
+            }
+
+            restorable
+        });
+
+        for (id, uid) in &purged {
+            self.record(Event::Purge, *id, *uid);
+        }
+
+        // Uploads that take this long have been abandoned.
+        self.uploads
+            .retain(|_, (started, _)| *started + Duration::from_secs(3600) > now);
+
+        Ok(purged.into_iter().map(|(id, _)| id).collect())
+    }
+
+    fn audit(&self, id: Option<Id>, since: Option<&str>) -> Result<Vec<audit::Record>, Error> {
+        Ok(self
+            .audit
+            .iter()
+            .filter(|record| id.is_none_or(|id| record.id == id))
+            .filter(|record| since.is_none_or(|since| record.created.as_str() >= since))
+            .cloned()
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU32;
+
+    use crate::db::{Backend, Config, Database, Open, read};
+
+    use super::*;
+
+    fn new_db() -> Result<Database, Box<dyn std::error::Error>> {
+        let (db, handler) = Database::open(Backend::Memory, Open::Memory, Config::default())?;
+        tokio::spawn(handler);
+        Ok(db)
+    }
+
+    #[test]
+    fn datetime() {
+        assert_eq!(format_datetime(UNIX_EPOCH), "1970-01-01 00:00:00");
+        assert_eq!(
+            format_datetime(UNIX_EPOCH + Duration::from_secs(1_709_251_199)),
+            "2024-02-29 23:59:59"
+        );
+    }
+
+    #[tokio::test]
+    async fn insert_edit_delete() -> Result<(), Box<dyn std::error::Error>> {
+        let db = new_db()?;
+
+        let entry = write::Entry {
+            text: "first".to_string(),
+            uid: Some(42),
+            title: Some("title".to_string()),
+            expires: NonZeroU32::new(3600),
+            ..Default::default()
+        };
+
+        let (id, _entry) = db.insert(entry).await?;
+
+        let metadata = db.get_metadata(id).await?;
+        assert_eq!(metadata.title.as_deref(), Some("title"));
+        assert_eq!(metadata.size, Some(5));
+        assert!(metadata.accessed.is_none());
+        assert!(metadata.expiration.unwrap().duration.as_secs() > 3590);
+
+        let entry = write::Entry {
+            text: "second".to_string(),
+            ..Default::default()
+        };
+
+        assert_eq!(db.edit(id, &[42], entry).await?, 2);
+        assert!(matches!(
+            db.edit(id, &[99], write::Entry::default()).await,
+            Err(Error::Edit)
+        ));
+
+        assert!(matches!(
+            db.set_expiration(id, &[99], None).await,
+            Err(Error::Edit)
+        ));
+        db.set_expiration(id, &[42], None).await?;
+        assert!(db.get_metadata(id).await?.expiration.is_none());
+
+        let data = db.get(id, None).await?.unwrap_inner();
+        assert_eq!(data.text, "second");
+        assert_eq!(db.get_revision(id, 1, None).await?.text, "first");
+        assert!(db.get_metadata(id).await?.accessed.is_some());
+
+        let entries = db.list().await?;
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].id, id);
+
+        assert!(matches!(db.delete_for(id, &[99]).await, Err(Error::Delete)));
+        db.delete_for(id, &[42]).await?;
+        assert!(matches!(db.get(id, None).await, Err(Error::NotFound)));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn max_views_and_purge() -> Result<(), Box<dyn std::error::Error>> {
+        let db = new_db()?;
+
+        let entry = write::Entry {
+            text: "secret".to_string(),
+            password: Some("password".to_string()),
+            max_views: NonZeroU32::new(1),
+            ..Default::default()
+        };
+
+        let (id, _entry) = db.insert(entry).await?;
+        assert!(matches!(db.get(id, None).await, Err(Error::NoPassword)));
+
+        let password = crate::crypto::Password::from("password".as_bytes().to_vec());
+        assert!(matches!(
+            db.get(id, Some(password)).await?,
+            read::Entry::Burned(_)
+        ));
+        assert!(db.list().await?.is_empty());
+
+        let entry = write::Entry {
+            expires: NonZeroU32::new(1),
+            ..Default::default()
+        };
+
+        let (id, _entry) = db.insert(entry).await?;
+        tokio::time::sleep(Duration::from_secs(2)).await;
+        assert_eq!(db.purge().await?, vec![id]);
+
+        assert!(matches!(db.search("foo").await, Err(Error::Unsupported)));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn slugs() -> Result<(), Box<dyn std::error::Error>> {
+        let db = new_db()?;
+        let slug = "deploy-notes".parse()?;
+
+        let entry = || write::Entry {
+            text: String::from("hello"),
+            slug: Some(slug),
+            ..Default::default()
+        };
+
+        let (id, _) = db.insert(entry()).await?;
+        assert_eq!(id, Id::from(slug));
+        assert!(matches!(db.insert(entry()).await, Err(Error::Conflict)));
+        assert_eq!(db.list().await?[0].id, id);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn trash() -> Result<(), Box<dyn std::error::Error>> {
+        let config = Config {
+            trash_period: Some(Duration::from_secs(1)),
+            ..Default::default()
+        };
+        let (db, handler) = Database::open(Backend::Memory, Open::Memory, config)?;
+        tokio::spawn(handler);
+
+        let slug = "deploy-notes".parse()?;
+        let entry = || write::Entry {
+            text: String::from("hello"),
+            uid: Some(42),
+            slug: Some(slug),
+            ..Default::default()
+        };
+
+        let (id, _) = db.insert(entry()).await?;
+        db.delete_for(id, &[42]).await?;
+        assert!(matches!(db.get(id, None).await, Err(Error::NotFound)));
+        assert!(matches!(db.insert(entry()).await, Err(Error::Conflict)));
+        assert!(db.list().await?[0].deleted.is_some());
+
+        assert!(matches!(
+            db.restore_for(id, &[99]).await,
+            Err(Error::Restore)
+        ));
+        db.restore_for(id, &[42]).await?;
+        assert_eq!(db.get_metadata(id).await?.uid, Some(42));
+
+        assert_eq!(db.delete_many(vec![id]).await?, 1);
+        assert!(db.purge().await?.is_empty());
+        tokio::time::sleep(Duration::from_secs(2)).await;
+
+        assert!(matches!(db.get_trashed(id).await, Err(Error::NotFound)));
+        assert!(matches!(db.restore(id).await, Err(Error::NotFound)));
+        assert_eq!(db.insert(entry()).await?.0, id);
+        assert!(db.purge().await?.is_empty());
+
+        let events = db
+            .audit(Some(id), None)
+            .await?
+            .into_iter()
+            .map(|record| record.event)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            events,
+            vec![
+                Event::Create,
+                Event::Delete,
+                Event::Restore,
+                Event::Delete,
+                Event::Purge,
+                Event::Create,
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn quota() -> Result<(), Box<dyn std::error::Error>> {
+        let config = Config {
+            max_storage: NonZeroU64::new(1024),
+            ..Default::default()
+        };
+        let (db, handler) = Database::open(Backend::Memory, Open::Memory, config)?;
+        tokio::spawn(handler);
+
+        let text = || (0..50).map(|_| Id::rand().to_string()).collect::<String>();
+
+        let entry = write::Entry {
+            text: text(),
+            expires: NonZeroU32::new(60),
+            ..Default::default()
+        };
+        let (expiring, _entry) = db.insert(entry).await?;
+
+        let entry = write::Entry {
+            text: text(),
+            ..Default::default()
+        };
+        let (permanent, _entry) = db.insert(entry).await?;
+
+        let entry = write::Entry {
+            text: text(),
+            ..Default::default()
+        };
+        db.insert(entry).await?;
+
+        assert!(matches!(db.get(expiring, None).await, Err(Error::NotFound)));
+
+        let entry = write::Entry {
+            text: text(),
+            ..Default::default()
+        };
+        assert!(matches!(db.insert(entry).await, Err(Error::QuotaExceeded)));
+        assert!(db.get(permanent, None).await.is_ok());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn quota_keeps_slug_holder() -> Result<(), Box<dyn std::error::Error>> {
+        let config = Config {
+            max_storage: NonZeroU64::new(1024),
+            trash_period: Some(Duration::from_secs(1)),
+            ..Default::default()
+        };
+        let (db, handler) = Database::open(Backend::Memory, Open::Memory, config)?;
+        tokio::spawn(handler);
+
+        let slug = "deploy-notes".parse()?;
+        let entry = write::Entry {
+            text: String::from("hello"),
+            uid: Some(42),
+            slug: Some(slug),
+            ..Default::default()
+        };
+        let (id, _) = db.insert(entry).await?;
+        db.delete_for(id, &[42]).await?;
+        tokio::time::sleep(Duration::from_secs(2)).await;
+
+        let entry = write::Entry {
+            text: (0..200).map(|_| Id::rand().to_string()).collect(),
+            slug: Some(slug),
+            ..Default::default()
+        };
+        assert!(matches!(db.insert(entry).await, Err(Error::QuotaExceeded)));
+
+        assert!(db.list().await?[0].deleted.is_some());
+        assert!(
+            db.audit(Some(id), None)
+                .await?
+                .iter()
+                .all(|record| record.event != Event::Purge)
+        );
+
+        Ok(())
+    }
+}
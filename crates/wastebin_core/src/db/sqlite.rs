@@ -0,0 +1,2503 @@
+//! SQLite storage backend.
+
+use std::collections::HashMap;
+use std::collections::hash_map::Entry as MapEntry;
+use std::ffi::c_int;
+use std::io::Cursor;
+use std::num::{NonZeroU32, NonZeroU64};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use chacha20poly1305::XNonce;
+use rusqlite::backup::Backup;
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSqlOutput, Value, ValueRef};
+use rusqlite::{
+    Connection, MAIN_DB, OpenFlags, OptionalExtension, ToSql, Transaction, params, params_from_iter,
+};
+use rusqlite_migration::{HookError, HookResult, M, Migrations};
+use sha2::{Digest, Sha256};
+
+use crate::audit::{self, Event};
+use crate::codec::{self, Codec, Encoding};
+use crate::db::read::{self, BlobEntry, DatabaseEntry, ListEntry, Metadata, SearchEntry};
+use crate::db::{
+    Access, Conflict, Error, Open, RawContent, RawEntry, Recompressed, Storage, write,
+};
+use crate::dictionary::{self, Dictionary};
+use crate::expiration::Expiration;
+use crate::files;
+use crate::id::{Generator, Id, Slug};
+
+/// Storage backend that keeps entries in an SQLite database.
+pub struct Sqlite {
+    conn: Connection,
+    /// Database file, `None` for in-memory databases
+    path: Option<PathBuf>,
+    /// Upper bound for the size of all stored data in bytes
+    max_storage: Option<NonZeroU64>,
+    /// Generator of new identifiers
+    generator: Generator,
+    /// How long deleted entries stay restorable, deleted entries are removed right away if `None`
+    trash_period: Option<Duration>,
+    /// Actor recorded with audit log events
+    actor: &'static str,
+}
+
+/// Content of a new entry.
+enum Content<'a> {
+    /// Encrypted `data` stored with the entry
+    Encrypted {
+        data: Vec<u8>,
+        nonce: &'a [u8],
+        encoding: Encoding,
+    },
+    /// Compressed `data` of `text` shared with all entries of the same content
+    Shared {
+        text: &'a [u8],
+        data: Vec<u8>,
+        encoding: Encoding,
+    },
+    /// Compressed data appended to `upload` with SHA-256 `hash` of the content
+    Upload {
+        upload: i64,
+        hash: &'a [u8],
+        encoding: Encoding,
+    },
+}
+
+/// Read an optional slug from column `idx` of `row`.
+fn slug_from_row(row: &rusqlite::Row<'_>, idx: usize) -> Result<Option<Slug>, rusqlite::Error> {
+    row.get::<_, Option<String>>(idx)?
+        .map(|slug| slug.parse::<Slug>())
+        .transpose()
+        .map_err(|err| {
+            rusqlite::Error::FromSqlConversionFailure(
+                idx,
+                rusqlite::types::Type::Text,
+                Box::new(err),
+            )
+        })
+}
+
+/// Read an [`Id`] from the slug at `slug_idx` or the integer id at `idx` if there is no slug.
+fn id_from_row(
+    row: &rusqlite::Row<'_>,
+    idx: usize,
+    slug_idx: usize,
+) -> Result<Id, rusqlite::Error> {
+    match slug_from_row(row, slug_idx)? {
+        Some(slug) => Ok(Id::from(slug)),
+        None => Ok(Id::from(row.get::<_, i64>(idx)?)),
+    }
+}
+
+/// Read the optional parent [`Id`] like [`id_from_row`].
+fn parent_from_row(
+    row: &rusqlite::Row<'_>,
+    idx: usize,
+    slug_idx: usize,
+) -> Result<Option<Id>, rusqlite::Error> {
+    match slug_from_row(row, slug_idx)? {
+        Some(slug) => Ok(Some(Id::from(slug))),
+        None => Ok(row.get::<_, Option<i64>>(idx)?.map(Id::from)),
+    }
+}
+
+/// Read an optional nonce from column `idx` of `row`.
+fn nonce_from_row(row: &rusqlite::Row<'_>, idx: usize) -> Result<Option<XNonce>, rusqlite::Error> {
+    row.get::<_, Option<Vec<u8>>>(idx)?
+        .map(|v| XNonce::try_from(v.as_slice()))
+        .transpose()
+        .map_err(|err| {
+            rusqlite::Error::FromSqlConversionFailure(
+                idx,
+                rusqlite::types::Type::Blob,
+                Box::new(err),
+            )
+        })
+}
+
+impl ToSql for Codec {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(self.as_str().into())
+    }
+}
+
+impl FromSql for Codec {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        value
+            .as_str()?
+            .parse()
+            .map_err(|err| FromSqlError::Other(Box::new(err)))
+    }
+}
+
+impl ToSql for Event {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(self.as_str().into())
+    }
+}
+
+impl FromSql for Event {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        value
+            .as_str()?
+            .parse()
+            .map_err(|err| FromSqlError::Other(Box::new(err)))
+    }
+}
+
+/// Run `sql` ending in `RETURNING id, slug, uid` and return the id and owner of every affected
+/// entry.
+fn affected<P: rusqlite::Params>(
+    conn: &Connection,
+    sql: &str,
+    params: P,
+) -> Result<Vec<(Id, Option<i64>)>, rusqlite::Error> {
+    conn.prepare(sql)?
+        .query_map(params, |row| Ok((id_from_row(row, 0, 1)?, row.get(2)?)))?
+        .collect()
+}
+
+/// Append `event` caused by `actor` to the audit log for each of the `affected` entries.
+fn record(
+    conn: &Connection,
+    actor: &str,
+    event: Event,
+    affected: &[(Id, Option<i64>)],
+) -> Result<(), rusqlite::Error> {
+    let mut stmt = conn.prepare_cached(
+        "INSERT INTO audit (id, slug, uid, event, actor, created_at) VALUES (?1, ?2, ?3, ?4, ?5, datetime('now'))",
+    )?;
+
+    for (id, uid) in affected {
+        stmt.execute(params![id.to_i64(), id.slug(), uid, event, actor])?;
+    }
+
+    Ok(())
+}
+
+/// Read the encoding from the codec, level and dictionary columns starting at `idx` of `row`.
+/// Content without a recorded codec was written with zstd.
+fn encoding_from_row(row: &rusqlite::Row<'_>, idx: usize) -> Result<Encoding, rusqlite::Error> {
+    Ok(Encoding {
+        codec: row.get::<_, Option<Codec>>(idx)?.unwrap_or_default(),
+        level: row.get(idx + 1)?,
+        dictionary: row.get(idx + 2)?,
+    })
+}
+
+/// Seconds of `time` since the Unix epoch, clamped to zero for times before it.
+fn unix_time(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH).map_or(0, |duration| {
+        i64::try_from(duration.as_secs()).unwrap_or(i64::MAX)
+    })
+}
+
+/// Store the compressed `data` of unencrypted `text` in a blob shared by all entries with the same
+/// content and return the blob's hash. An existing blob keeps its data and the `encoding` it was
+/// stored with.
+fn store_blob(
+    tx: &Transaction,
+    text: &[u8],
+    data: &[u8],
+    encoding: Encoding,
+) -> Result<Vec<u8>, rusqlite::Error> {
+    let hash = Sha256::digest(text).to_vec();
+
+    tx.execute(
+        "INSERT INTO blobs (hash, data, refs, codec, level, dictionary) VALUES (?1, ?2, 1, ?3, ?4, ?5) ON CONFLICT(hash) DO UPDATE SET refs = refs + 1",
+        params![hash, data, encoding.codec, encoding.level, encoding.dictionary],
+    )?;
+
+    Ok(hash)
+}
+
+/// Move the data appended to `upload` into a blob like [`store_blob`] and return the blob's hash.
+/// The data is copied chunk by chunk with incremental blob I/O, so that it is never held in memory
+/// at once.
+fn store_upload(
+    tx: &Transaction,
+    upload: i64,
+    hash: &[u8],
+    encoding: Encoding,
+) -> Result<Vec<u8>, rusqlite::Error> {
+    let shared = tx.execute(
+        "UPDATE blobs SET refs = refs + 1 WHERE hash=?1",
+        params![hash],
+    )? > 0;
+
+    if !shared {
+        let size: i64 = tx.query_row(
+            "SELECT COALESCE(SUM(length(data)), 0) FROM uploads WHERE id=?1",
+            params![upload],
+            |row| row.get(0),
+        )?;
+
+        tx.execute(
+            "INSERT INTO blobs (hash, data, refs, codec, level, dictionary) VALUES (?1, zeroblob(?2), 1, ?3, ?4, ?5)",
+            params![hash, size, encoding.codec, encoding.level, encoding.dictionary],
+        )?;
+
+        let mut blob = tx.blob_open(MAIN_DB, c"blobs", c"data", tx.last_insert_rowid(), false)?;
+        let mut chunks = tx.prepare("SELECT data FROM uploads WHERE id=?1 ORDER BY seq")?;
+        let mut rows = chunks.query(params![upload])?;
+        let mut offset = 0;
+
+        while let Some(row) = rows.next()? {
+            let chunk = row.get_ref(0)?.as_blob()?;
+            blob.write_at(chunk, offset)?;
+            offset += chunk.len();
+        }
+    }
+
+    tx.execute("DELETE FROM uploads WHERE id=?1", params![upload])?;
+
+    Ok(hash.to_vec())
+}
+
+/// Evict entries in the trash, oldest first, and entries that expire, soonest first, until `size`
+/// additional bytes fit into `max_storage`. The bytes in use are the running total of stored and
+/// uploaded data that triggers keep in the `storage` table. Entry `keep` is never evicted. Evictions are recorded
+/// as caused by `actor` and rolled back together with `tx` if there is not enough space in the end.
+fn make_room(
+    tx: &Transaction,
+    max_storage: NonZeroU64,
+    size: usize,
+    keep: Option<Id>,
+    actor: &str,
+) -> Result<(), Error> {
+    let fits = || -> Result<bool, rusqlite::Error> {
+        let used: u64 = tx.query_row("SELECT used FROM storage WHERE id = 0", [], |row| {
+            row.get(0)
+        })?;
+
+        Ok(used.saturating_add(u64::try_from(size).unwrap_or(u64::MAX)) <= max_storage.get())
+    };
+
+    if fits()? {
+        return Ok(());
+    }
+
+    let candidates = tx
+        .prepare("SELECT id, slug, uid FROM entries WHERE (expires IS NOT NULL OR deleted_at IS NOT NULL) AND id IS NOT ?1 ORDER BY deleted_at IS NULL, deleted_at, expires, created_at")?
+        .query_map(params![keep.map(Id::to_i64)], |row| {
+            Ok((id_from_row(row, 0, 1)?, row.get(2)?))
+        })?
+        .collect::<Result<Vec<(Id, Option<i64>)>, _>>()?;
+
+    for (id, uid) in candidates {
+        tx.execute("DELETE FROM entries WHERE id=?1", params![id.to_i64()])?;
+        record(tx, actor, Event::Evict, &[(id, uid)])?;
+        tracing::info!("evicted paste {id} to stay within storage quota");
+
+        if fits()? {
+            return Ok(());
+        }
+    }
+
+    Err(Error::QuotaExceeded)
+}
+
+/// Read dictionary `id`.
+fn get_dictionary(conn: &Connection, id: u32) -> Result<Dictionary, rusqlite::Error> {
+    conn.query_row(
+        "SELECT data FROM dictionaries WHERE id=?1",
+        params![id],
+        |row| {
+            Ok(Dictionary {
+                id,
+                data: row.get(0)?,
+            })
+        },
+    )
+}
+
+/// Compress the plain text of all entries.
+fn compress_text(tx: &Transaction) -> HookResult {
+    let mut stmt = tx.prepare("SELECT id, text FROM entries")?;
+
+    let rows = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<Vec<(u32, String)>, _>>()?;
+
+    tracing::debug!("compressing {} rows", rows.len());
+
+    for (id, text) in rows {
+        let cursor = Cursor::new(text);
+        let data = zstd::stream::encode_all(cursor, zstd::DEFAULT_COMPRESSION_LEVEL)
+            .map_err(|e| HookError::Hook(e.to_string()))?;
+
+        tx.execute(
+            "UPDATE entries SET data = ?1 WHERE id = ?2",
+            params![data, id],
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Add the text of all unencrypted entries to the search index.
+fn index_entries(tx: &Transaction) -> HookResult {
+    let mut stmt = tx.prepare("SELECT id, title, data FROM entries WHERE nonce IS NULL")?;
+
+    let rows = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+        .collect::<Result<Vec<(i64, Option<String>, Vec<u8>)>, _>>()?;
+
+    tracing::debug!("indexing {} rows", rows.len());
+
+    for (id, title, data) in rows {
+        let text = zstd::stream::decode_all(Cursor::new(data))
+            .map_err(|e| HookError::Hook(e.to_string()))?;
+
+        tx.execute(
+            "INSERT INTO entries_fts (rowid, title, text) VALUES (?1, ?2, ?3)",
+            params![id, title, String::from_utf8_lossy(&text)],
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Record the uncompressed size of all unencrypted entries.
+fn record_sizes(tx: &Transaction) -> HookResult {
+    let mut stmt = tx.prepare("SELECT id, data FROM entries WHERE nonce IS NULL")?;
+
+    let rows = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<Vec<(i64, Vec<u8>)>, _>>()?;
+
+    for (id, data) in rows {
+        let text = zstd::stream::decode_all(Cursor::new(data))
+            .map_err(|e| HookError::Hook(e.to_string()))?;
+
+        tx.execute(
+            "UPDATE entries SET size = ?1 WHERE id = ?2",
+            params![text.len(), id],
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Move the content of all unencrypted entries to shared blobs.
+fn move_to_blobs(tx: &Transaction) -> HookResult {
+    let mut stmt = tx.prepare("SELECT id, data FROM entries WHERE nonce IS NULL")?;
+
+    let rows = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<Vec<(i64, Vec<u8>)>, _>>()?;
+
+    tracing::debug!("moving {} rows to blobs", rows.len());
+
+    for (id, data) in rows {
+        let text = zstd::stream::decode_all(Cursor::new(&data))
+            .map_err(|e| HookError::Hook(e.to_string()))?;
+
+        let hash = store_blob(tx, &text, &data, Encoding::default())?;
+
+        tx.execute(
+            "UPDATE entries SET data = NULL, blob = ?1 WHERE id = ?2",
+            params![hash, id],
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Migration script of the database schema.
+struct Script {
+    /// File name without extension
+    name: &'static str,
+    up: &'static str,
+    /// Script reverting `up`, `None` if that is not possible without losing pastes
+    down: Option<&'static str>,
+    /// Code run after `up`
+    hook: Option<fn(&Transaction) -> HookResult>,
+}
+
+/// Define a [`Script`] from the files named `$name` in `migrations` and, if `down` is given, in
+/// `migrations/down`.
+macro_rules! script {
+    ($name:literal) => {
+        script!($name, None, None)
+    };
+    ($name:literal, down) => {
+        script!(
+            $name,
+            Some(include_str!(concat!("../migrations/down/", $name, ".sql"))),
+            None
+        )
+    };
+    ($name:literal, hook = $hook:expr) => {
+        script!($name, None, Some($hook))
+    };
+    ($name:literal, $down:expr, $hook:expr) => {
+        Script {
+            name: $name,
+            up: include_str!(concat!("../migrations/", $name, ".sql")),
+            down: $down,
+            hook: $hook,
+        }
+    };
+}
+
+/// All migrations in the order they are applied. The schema version of a database is the number
+/// of migrations applied to it.
+static SCRIPTS: &[Script] = &[
+    script!("0001-initial"),
+    script!("0002-add-created-column"),
+    script!("0003-drop-created-add-uid-column"),
+    script!("0004-add-compressed-column", hook = compress_text),
+    script!("0005-drop-text-column"),
+    script!("0006-add-nonce-column"),
+    script!("0007-add-title-column"),
+    script!("0008-add-revisions-table"),
+    script!("0009-add-parent-column"),
+    script!("0010-add-search-index", hook = index_entries),
+    script!("0011-add-stats-columns", hook = record_sizes),
+    script!("0012-replace-burn-with-max-views"),
+    script!("0013-add-blobs-table", hook = move_to_blobs),
+    script!("0014-add-storage-table"),
+    script!("0015-add-dictionaries-table"),
+    script!("0016-add-codec-columns"),
+    script!("0017-add-multi-file-column"),
+    // Slugs cannot be kept in earlier versions, so pastes would lose their URLs.
+    script!("0018-add-slug-column"),
+    script!("0019-add-extension-column", down),
+    script!("0020-add-deleted-column", down),
+    script!("0021-add-audit-table", down),
+    script!("0022-add-uploads-table", down),
+];
+
+/// [`SCRIPTS`] as migrations to run.
+fn definitions() -> Migrations<'static> {
+    Migrations::new(
+        SCRIPTS
+            .iter()
+            .map(|script| {
+                let m = match script.hook {
+                    Some(hook) => M::up_with_hook(script.up, hook),
+                    None => M::up(script.up),
+                };
+
+                match script.down {
+                    Some(down) => m.down(down),
+                    None => m,
+                }
+            })
+            .collect(),
+    )
+}
+
+/// Read the schema version of the database behind `conn`.
+fn user_version(conn: &Connection) -> Result<usize, Error> {
+    let version: i64 = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+    usize::try_from(version).map_err(|_| Error::UnknownVersion(version))
+}
+
+/// A migration of the database schema.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Migration {
+    /// Schema version after the migration is applied
+    pub version: usize,
+    /// Name of the migration
+    pub name: &'static str,
+    /// Whether the migration can be reverted
+    pub reversible: bool,
+}
+
+/// A step of migrating a database from one schema version to another.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Step {
+    /// Apply the migration
+    Apply(Migration),
+    /// Revert the migration
+    Revert(Migration),
+}
+
+/// Return all migrations known to this build, oldest first. The version of the last one is the
+/// latest schema version.
+#[must_use]
+pub fn migrations() -> Vec<Migration> {
+    (1..)
+        .zip(SCRIPTS)
+        .map(|(version, script)| Migration {
+            version,
+            name: script.name,
+            reversible: script.down.is_some(),
+        })
+        .collect()
+}
+
+/// Read the schema version of the database file at `path` without changing it.
+pub fn schema_version(path: &Path) -> Result<usize, Error> {
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    user_version(&conn)
+}
+
+/// Return the steps taking a database from schema version `current` to `target`. Fails with
+/// [`Error::UnknownVersion`] if either version is unknown to this build and with
+/// [`Error::Irreversible`] if a migration would have to be reverted that cannot be.
+pub fn plan(current: usize, target: usize) -> Result<Vec<Step>, Error> {
+    let migrations = migrations();
+
+    for version in [current, target] {
+        if version > migrations.len() {
+            return Err(Error::UnknownVersion(
+                i64::try_from(version).unwrap_or(i64::MAX),
+            ));
+        }
+    }
+
+    if target >= current {
+        return Ok(migrations[current..target]
+            .iter()
+            .copied()
+            .map(Step::Apply)
+            .collect());
+    }
+
+    migrations[target..current]
+        .iter()
+        .rev()
+        .map(|migration| {
+            if migration.reversible {
+                Ok(Step::Revert(*migration))
+            } else {
+                Err(Error::Irreversible(migration.name))
+            }
+        })
+        .collect()
+}
+
+/// Migrate the database file at `path` to schema version `target` and return the steps taken.
+/// Either all steps are taken or none.
+pub fn migrate(path: &Path, target: usize) -> Result<Vec<Step>, Error> {
+    let mut conn = Connection::open(path)?;
+    let steps = plan(user_version(&conn)?, target)?;
+
+    if !steps.is_empty() {
+        definitions().to_version(&mut conn, target)?;
+    }
+
+    Ok(steps)
+}
+
+impl Sqlite {
+    /// Limit the size of all stored data to `max_storage` bytes, see [`make_room`].
+    #[must_use]
+    pub fn with_max_storage(mut self, max_storage: Option<NonZeroU64>) -> Self {
+        self.max_storage = max_storage;
+        self
+    }
+
+    /// Generate identifiers of new entries with `generator`.
+    #[must_use]
+    pub fn with_generator(mut self, generator: Generator) -> Self {
+        self.generator = generator;
+        self
+    }
+
+    /// Keep deleted entries restorable for `trash_period` before they are purged.
+    #[must_use]
+    pub fn with_trash_period(mut self, trash_period: Option<Duration>) -> Self {
+        self.trash_period = trash_period;
+        self
+    }
+
+    /// Record audit log events as caused by `actor`.
+    #[must_use]
+    pub fn with_actor(mut self, actor: &'static str) -> Self {
+        self.actor = actor;
+        self
+    }
+
+    /// `datetime()` modifier of the point in time until which entries deleted now can be
+    /// restored.
+    fn trash_modifier(&self) -> String {
+        format!(
+            "+{} seconds",
+            self.trash_period.unwrap_or_default().as_secs()
+        )
+    }
+
+    /// Get the metadata of entry `id` matching the additional `condition`.
+    fn metadata(&self, id: Id, condition: &str) -> Result<Metadata, Error> {
+        let metadata = self.conn.query_row(
+            &format!("SELECT uid, title, CAST(ROUND((julianday(expires) - julianday('now')) * 86400) AS INTEGER), max_views, (SELECT COUNT(*) FROM revisions WHERE revisions.id = entries.id), parent, created_at, accessed_at, size, compressed_size, views, (SELECT slug FROM entries AS parents WHERE parents.id = entries.parent), extension, multi_file FROM entries WHERE id=?1 AND {condition}"),
+            params![id.to_i64()],
+            |row| {
+                let expiration = row.get::<_, Option<i64>>(2)?
+                    .filter(|secs| *secs > 0)
+                    .and_then(|secs| u64::try_from(secs).ok())
+                    .map(|secs| Expiration { duration: Duration::from_secs(secs), default: false });
+
+                Ok(Metadata {
+                    uid: row.get(0)?,
+                    title: row.get::<_, Option<String>>(1)?,
+                    extension: row.get(12)?,
+                    expiration,
+                    max_views: row.get(3)?,
+                    views: row.get(10)?,
+                    revision: row.get::<_, u32>(4)? + 1,
+                    parent: parent_from_row(row, 5, 11)?,
+                    created: row.get(6)?,
+                    accessed: row.get(7)?,
+                    size: row.get(8)?,
+                    compressed_size: row.get(9)?,
+                    multi_file: row.get(13)?,
+                })
+            }
+        )?;
+
+        Ok(metadata)
+    }
+
+    /// Open the database with the given `method` and treat its schema according to `access`.
+    /// In-memory databases are always migrated to the latest version.
+    pub fn open(method: Open, access: Access) -> Result<Self, Error> {
+        tracing::debug!("opening {method:?} with {access:?} access");
+
+        let (mut conn, path) = match method {
+            Open::Memory => {
+                let mut conn = Connection::open_in_memory()?;
+                definitions().to_latest(&mut conn)?;
+                (conn, None)
+            }
+            Open::Path(path) if access == Access::ReadOnly => {
+                let conn = Connection::open_with_flags(
+                    &path,
+                    OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+                )?;
+
+                (conn, Some(path))
+            }
+            Open::Path(path) => {
+                let conn = Connection::open(&path)?;
+
+                // Let readers proceed while a write is in progress.
+                conn.pragma_update_and_check(None, "journal_mode", "WAL", |row| {
+                    row.get::<_, String>(0)
+                })?;
+
+                (conn, Some(path))
+            }
+        };
+
+        if path.is_some() {
+            let current = user_version(&conn)?;
+            let latest = SCRIPTS.len();
+
+            match access {
+                Access::Migrate => definitions().to_latest(&mut conn)?,
+                Access::ReadWrite if current != latest => {
+                    return Err(Error::Schema { current, latest });
+                }
+                Access::ReadOnly if current < latest => {
+                    return Err(Error::Schema { current, latest });
+                }
+                Access::ReadOnly if current > latest => {
+                    tracing::warn!(
+                        "database schema version {current} is newer than version {latest} of this build"
+                    );
+                }
+                Access::ReadWrite | Access::ReadOnly => {}
+            }
+        }
+
+        Ok(Self {
+            conn,
+            path,
+            max_storage: None,
+            generator: Generator::default(),
+            trash_period: None,
+            actor: "",
+        })
+    }
+
+    /// Insert `entry` with `content`, see [`Storage::insert`]. `size` and `compressed_size` are
+    /// recorded as they are and `searchable` is put into the search index.
+    fn insert_with(
+        &mut self,
+        entry: &write::Entry,
+        content: Content<'_>,
+        size: u64,
+        compressed_size: usize,
+        searchable: Option<&str>,
+    ) -> Result<Id, Error> {
+        let mut counter = 0;
+        let title = entry.title.clone();
+        let parent = entry.parent.map(Id::to_i64);
+        let max_views = entry.max_views.map(NonZeroU32::get);
+        let tx = self.conn.transaction()?;
+
+        if let Some(max_storage) = self.max_storage {
+            // Uploaded data is already counted and only moves into a blob.
+            let additional = match content {
+                Content::Upload { .. } => 0,
+                _ => compressed_size,
+            };
+
+            make_room(&tx, max_storage, additional, None, self.actor)?;
+        }
+
+        let (data, nonce, blob, encoding) = match content {
+            Content::Encrypted {
+                data,
+                nonce,
+                encoding,
+            } => (Some(data), Some(nonce), None, Some(encoding)),
+            Content::Shared {
+                text,
+                data,
+                encoding,
+            } => (
+                None,
+                None,
+                Some(store_blob(&tx, text, &data, encoding)?),
+                None,
+            ),
+            Content::Upload {
+                upload,
+                hash,
+                encoding,
+            } => (
+                None,
+                None,
+                Some(store_upload(&tx, upload, hash, encoding)?),
+                None,
+            ),
+        };
+
+        let codec = encoding.map(|encoding| encoding.codec);
+        let level = encoding.and_then(|encoding| encoding.level);
+        let dictionary = encoding.and_then(|encoding| encoding.dictionary);
+
+        // Time value and modifier of the `datetime()` call computing the expiration.
+        let expires = match (entry.expires_at, entry.expires) {
+            (Some(expires_at), _) => Some((
+                Value::Integer(unix_time(expires_at)),
+                String::from("unixepoch"),
+            )),
+            (None, Some(expires)) => Some((
+                Value::from(String::from("now")),
+                format!("{expires} seconds"),
+            )),
+            (None, None) => None,
+        };
+
+        // An expired entry or one that can no longer be restored from the trash does not hold on
+        // to its slug even if it has not been purged yet.
+        if let Some(slug) = entry.slug {
+            let purged = affected(
+                &tx,
+                "DELETE FROM entries WHERE id=?1 AND (expires < datetime('now') OR purge_after <= datetime('now')) RETURNING id, slug, uid",
+                params![Id::from(slug).to_i64()],
+            )?;
+            record(&tx, self.actor, Event::Purge, &purged)?;
+        }
+
+        loop {
+            let id = entry
+                .slug
+                .map_or_else(|| self.generator.generate(), Id::from);
+
+            let result = match &expires {
+                None => tx.execute(
+                    "INSERT INTO entries (id, uid, data, max_views, nonce, title, parent, created_at, size, compressed_size, blob, codec, level, dictionary, slug, extension, multi_file) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, datetime('now'), ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)",
+                    params![id.to_i64(), entry.uid, data, max_views, nonce, title, parent, size, compressed_size, blob, codec, level, dictionary, id.slug(), entry.extension, entry.multi_file],
+                ),
+                Some((time, modifier)) => tx.execute(
+                    "INSERT INTO entries (id, uid, data, max_views, nonce, expires, title, parent, created_at, size, compressed_size, blob, codec, level, dictionary, slug, extension, multi_file) VALUES (?1, ?2, ?3, ?4, ?5, datetime(?6, ?7), ?8, ?9, datetime('now'), ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18)",
+                    params![
+                        id.to_i64(),
+                        entry.uid,
+                        data,
+                        max_views,
+                        nonce,
+                        time,
+                        modifier,
+                        title,
+                        parent,
+                        size,
+                        compressed_size,
+                        blob,
+                        codec,
+                        level,
+                        dictionary,
+                        id.slug(),
+                        entry.extension,
+                        entry.multi_file,
+                    ],
+                ),
+            };
+
+            match result {
+                Err(rusqlite::Error::SqliteFailure(
+                    rusqlite::ffi::Error {
+                        code,
+                        extended_code,
+                    },
+                    Some(ref _message),
+                )) if code == rusqlite::ErrorCode::ConstraintViolation
+                    && extended_code == rusqlite::ffi::SQLITE_CONSTRAINT_PRIMARYKEY
+                    && entry.slug.is_some() =>
+                {
+                    break Err(Error::Conflict);
+                }
+                Err(rusqlite::Error::SqliteFailure(
+                    rusqlite::ffi::Error {
+                        code,
+                        extended_code,
+                    },
+                    Some(ref _message),
+                )) if code == rusqlite::ErrorCode::ConstraintViolation
+                    && extended_code == rusqlite::ffi::SQLITE_CONSTRAINT_PRIMARYKEY
+                    && counter < 10 =>
+                {
+                    // Retry if ID is already existent
+                    counter += 1;
+                    continue;
+                }
+                Err(err) => break Err(err)?,
+                Ok(rows) => {
+                    debug_assert!(rows == 1);
+
+                    if let Some(searchable) = searchable {
+                        tx.execute(
+                            "INSERT INTO entries_fts (rowid, title, text) VALUES (?1, ?2, ?3)",
+                            params![id.to_i64(), title, searchable],
+                        )?;
+                    }
+
+                    record(&tx, self.actor, Event::Create, &[(id, entry.uid)])?;
+                    tx.commit()?;
+                    return Ok(id);
+                }
+            }
+        }
+    }
+}
+
+impl Storage for Sqlite {
+    fn insert(
+        &mut self,
+        write::DatabaseEntry {
+            entry,
+            data,
+            nonce,
+            encoding,
+        }: write::DatabaseEntry,
+    ) -> Result<(Id, write::Entry), Error> {
+        let size = entry.text.len() as u64;
+        let compressed_size = data.len();
+
+        // Encrypted data differs on every insert and is stored per entry.
+        let (content, searchable) = match &nonce {
+            None => (
+                Content::Shared {
+                    text: entry.text.as_bytes(),
+                    data,
+                    encoding,
+                },
+                Some(entry.searchable().into_owned()),
+            ),
+            Some(nonce) => (
+                Content::Encrypted {
+                    data,
+                    nonce: nonce.as_slice(),
+                    encoding,
+                },
+                None,
+            ),
+        };
+
+        let id = self.insert_with(
+            &entry,
+            content,
+            size,
+            compressed_size,
+            searchable.as_deref(),
+        )?;
+
+        Ok((id, entry))
+    }
+
+    fn append(&mut self, upload: i64, seq: u32, data: Vec<u8>) -> Result<(), Error> {
+        let tx = self.conn.transaction()?;
+
+        if let Some(max_storage) = self.max_storage {
+            make_room(&tx, max_storage, data.len(), None, self.actor)?;
+        }
+
+        tx.execute(
+            "INSERT INTO uploads (id, seq, data, created_at) VALUES (?1, ?2, ?3, datetime('now'))",
+            params![upload, seq, data],
+        )?;
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    fn insert_upload(
+        &mut self,
+        upload: i64,
+        write::Upload {
+            entry,
+            hash,
+            size,
+            encoding,
+            searchable,
+        }: write::Upload,
+    ) -> Result<(Id, write::Entry), Error> {
+        let compressed_size = self.conn.query_row(
+            "SELECT COALESCE(SUM(length(data)), 0) FROM uploads WHERE id=?1",
+            params![upload],
+            |row| row.get(0),
+        )?;
+
+        let content = Content::Upload {
+            upload,
+            hash: &hash,
+            encoding,
+        };
+
+        let id = self.insert_with(&entry, content, size, compressed_size, Some(&searchable))?;
+
+        Ok((id, entry))
+    }
+
+    fn discard(&mut self, upload: i64) -> Result<(), Error> {
+        self.conn
+            .execute("DELETE FROM uploads WHERE id=?1", params![upload])?;
+
+        Ok(())
+    }
+
+    fn get_metadata(&self, id: Id) -> Result<Metadata, Error> {
+        self.metadata(id, "deleted_at IS NULL")
+    }
+
+    fn get_trashed(&self, id: Id) -> Result<Metadata, Error> {
+        self.metadata(id, "purge_after > datetime('now')")
+    }
+
+    fn get(&self, id: Id) -> Result<DatabaseEntry, Error> {
+        let metadata = self.get_metadata(id)?;
+        let entry = self.conn.query_row(
+            "SELECT COALESCE(entries.data, blobs.data), nonce, expires < datetime('now'), COALESCE(entries.codec, blobs.codec), COALESCE(entries.level, blobs.level), COALESCE(entries.dictionary, blobs.dictionary) FROM entries LEFT JOIN blobs ON blobs.hash = entries.blob WHERE id=?1",
+            params![id.to_i64()],
+            |row| {
+                Ok(DatabaseEntry {
+                    data: row.get(0)?,
+                    metadata,
+                    nonce: nonce_from_row(row, 1)?,
+                    expired: row.get::<_, Option<bool>>(2)?.unwrap_or(false),
+                    encoding: encoding_from_row(row, 3)?,
+                })
+            },
+        )?;
+
+        Ok(entry)
+    }
+
+    fn get_blob(&self, id: Id) -> Result<Option<BlobEntry>, Error> {
+        let metadata = self.get_metadata(id)?;
+        let entry = self.conn.query_row(
+            "SELECT blobs.hash, length(blobs.data), expires < datetime('now'), blobs.codec, blobs.level, blobs.dictionary FROM entries JOIN blobs ON blobs.hash = entries.blob WHERE id=?1",
+            params![id.to_i64()],
+            |row| {
+                Ok(BlobEntry {
+                    blob: read::Blob {
+                        hash: row.get(0)?,
+                        size: row.get(1)?,
+                        encoding: encoding_from_row(row, 3)?,
+                    },
+                    metadata,
+                    expired: row.get::<_, Option<bool>>(2)?.unwrap_or(false),
+                })
+            },
+        ).optional()?;
+
+        Ok(entry)
+    }
+
+    fn read_blob(&self, blob: &read::Blob, offset: u64, len: usize) -> Result<Vec<u8>, Error> {
+        // Blobs are only ever rewritten by recompressing them, which changes their encoding or size.
+        let rowid = self.conn.query_row(
+            "SELECT rowid FROM blobs WHERE hash=?1 AND length(data)=?2 AND COALESCE(codec, 'zstd')=?3 AND level IS ?4 AND dictionary IS ?5",
+            params![blob.hash, blob.size, blob.encoding.codec, blob.encoding.level, blob.encoding.dictionary],
+            |row| row.get(0),
+        )?;
+
+        let offset = usize::try_from(offset).map_err(|_| Error::NotFound)?;
+        let remaining = usize::try_from(blob.size)
+            .map_err(|_| Error::NotFound)?
+            .saturating_sub(offset);
+        let mut data = vec![0; len.min(remaining)];
+
+        self.conn
+            .blob_open(MAIN_DB, c"blobs", c"data", rowid, true)?
+            .read_at_exact(&mut data, offset)?;
+
+        Ok(data)
+    }
+
+    fn delete(&mut self, id: Id) -> Result<(), Error> {
+        let tx = self.conn.transaction()?;
+        let expired = affected(
+            &tx,
+            "DELETE FROM entries WHERE id=?1 RETURNING id, slug, uid",
+            params![id.to_i64()],
+        )?;
+        record(&tx, self.actor, Event::Expire, &expired)?;
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    fn delete_many(&mut self, ids: Vec<Id>) -> Result<usize, Error> {
+        let trash_modifier = self.trash_period.is_some().then(|| self.trash_modifier());
+        let tx = self.conn.transaction()?;
+
+        let mut deleted = Vec::new();
+
+        for id in ids {
+            let id = id.to_i64();
+
+            deleted.extend(match &trash_modifier {
+                Some(modifier) => affected(
+                    &tx,
+                    "UPDATE entries SET deleted_at = datetime('now'), purge_after = datetime('now', ?2) WHERE id=?1 AND deleted_at IS NULL RETURNING id, slug, uid",
+                    params![id, modifier],
+                )?,
+                None => affected(
+                    &tx,
+                    "DELETE FROM entries WHERE id=?1 RETURNING id, slug, uid",
+                    params![id],
+                )?,
+            });
+        }
+
+        record(&tx, self.actor, Event::Delete, &deleted)?;
+        tx.commit()?;
+        Ok(deleted.len())
+    }
+
+    fn delete_for(&mut self, id: Id, uids: &[i64]) -> Result<(), Error> {
+        if uids.is_empty() {
+            return Err(Error::Delete);
+        }
+
+        let placeholders = vec!["?"; uids.len()].join(",");
+        let id = id.to_i64();
+        let modifier = self.trash_modifier();
+        let mut params: Vec<&dyn ToSql> = vec![&id];
+
+        let delete_sql = if self.trash_period.is_some() {
+            params.insert(0, &modifier);
+            format!(
+                "UPDATE entries SET deleted_at = datetime('now'), purge_after = datetime('now', ?) WHERE id=? AND uid IN ({placeholders}) AND deleted_at IS NULL RETURNING id, slug, uid"
+            )
+        } else {
+            format!(
+                "DELETE FROM entries WHERE id=? AND uid IN ({placeholders}) RETURNING id, slug, uid"
+            )
+        };
+
+        params.extend(uids.iter().map(|uid| uid as &dyn ToSql));
+
+        let tx = self.conn.transaction()?;
+        let deleted = affected(&tx, &delete_sql, params.as_slice())?;
+
+        if deleted.is_empty() {
+            return Err(Error::Delete);
+        }
+
+        record(&tx, self.actor, Event::Delete, &deleted)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn restore(&mut self, id: Id, uids: Option<&[i64]>) -> Result<(), Error> {
+        let mut restore_sql = String::from(
+            "UPDATE entries SET deleted_at = NULL, purge_after = NULL WHERE id=? AND purge_after > datetime('now')",
+        );
+        let id = id.to_i64();
+        let mut params: Vec<&dyn ToSql> = vec![&id];
+
+        if let Some(uids) = uids {
+            let placeholders = vec!["?"; uids.len()].join(",");
+            restore_sql.push_str(&format!(" AND uid IN ({placeholders})"));
+            params.extend(uids.iter().map(|uid| uid as &dyn ToSql));
+        }
+
+        restore_sql.push_str(" RETURNING id, slug, uid");
+
+        let tx = self.conn.transaction()?;
+        let restored = affected(&tx, &restore_sql, params.as_slice())?;
+
+        if restored.is_empty() {
+            return Err(if uids.is_some() {
+                Error::Restore
+            } else {
+                Error::NotFound
+            });
+        }
+
+        record(&tx, self.actor, Event::Restore, &restored)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn edit(
+        &mut self,
+        id: Id,
+        uids: &[i64],
+        write::DatabaseEntry {
+            entry,
+            data,
+            nonce,
+            encoding,
+        }: write::DatabaseEntry,
+    ) -> Result<u32, Error> {
+        if uids.is_empty() {
+            return Err(Error::Edit);
+        }
+
+        let placeholders = vec!["?"; uids.len()].join(",");
+        let owned_sql = format!(
+            "SELECT COUNT(*) FROM entries WHERE id=? AND uid IN ({placeholders}) AND (expires IS NULL OR expires > datetime('now')) AND max_views IS NULL AND deleted_at IS NULL"
+        );
+
+        let mut params = Vec::with_capacity(uids.len() + 1);
+        params.push(id.to_i64());
+        params.extend_from_slice(uids);
+
+        let tx = self.conn.transaction()?;
+
+        let owned: i64 = tx.query_row(&owned_sql, params_from_iter(&params), |row| row.get(0))?;
+
+        if owned == 0 {
+            return Err(Error::Edit);
+        }
+
+        // The current content is kept as a revision, so the new one adds to the total.
+        if let Some(max_storage) = self.max_storage {
+            make_room(&tx, max_storage, data.len(), Some(id), self.actor)?;
+        }
+
+        let archived: u32 = tx.query_row(
+            "INSERT INTO revisions (id, rev, data, nonce, codec, level, dictionary, multi_file) SELECT id, (SELECT COUNT(*) FROM revisions WHERE id=?1) + 1, COALESCE(entries.data, blobs.data), nonce, COALESCE(entries.codec, blobs.codec), COALESCE(entries.level, blobs.level), COALESCE(entries.dictionary, blobs.dictionary), multi_file FROM entries LEFT JOIN blobs ON blobs.hash = entries.blob WHERE id=?1 RETURNING rev",
+            params![id.to_i64()],
+            |row| row.get(0),
+        )?;
+
+        let compressed_size = data.len();
+
+        let (data, blob, encoding) = match nonce {
+            None => (
+                None,
+                Some(store_blob(&tx, entry.text.as_bytes(), &data, encoding)?),
+                None,
+            ),
+            Some(_) => (Some(data), None, Some(encoding)),
+        };
+
+        tx.execute(
+            "UPDATE entries SET data=?1, nonce=?2, size=?3, compressed_size=?4, blob=?5, codec=?6, level=?7, dictionary=?8, multi_file=?9 WHERE id=?10",
+            params![
+                data,
+                nonce.as_ref().map(|n| n.as_slice()),
+                entry.text.len(),
+                compressed_size,
+                blob,
+                encoding.map(|encoding| encoding.codec),
+                encoding.and_then(|encoding| encoding.level),
+                encoding.and_then(|encoding| encoding.dictionary),
+                entry.multi_file,
+                id.to_i64()
+            ],
+        )?;
+
+        tx.execute(
+            "DELETE FROM entries_fts WHERE rowid=?1",
+            params![id.to_i64()],
+        )?;
+
+        if nonce.is_none() {
+            tx.execute(
+                "INSERT INTO entries_fts (rowid, title, text) SELECT id, title, ?1 FROM entries WHERE id=?2",
+                params![entry.searchable(), id.to_i64()],
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(archived + 1)
+    }
+
+    fn edit_metadata(
+        &mut self,
+        id: Id,
+        uids: &[i64],
+        title: Option<String>,
+        extension: Option<String>,
+    ) -> Result<(), Error> {
+        if uids.is_empty() {
+            return Err(Error::Edit);
+        }
+
+        let placeholders = vec!["?"; uids.len()].join(",");
+        let update_sql = format!(
+            "UPDATE entries SET title = NULLIF(COALESCE(?, title), ''), extension = NULLIF(COALESCE(?, extension), '') WHERE id=? AND uid IN ({placeholders}) AND (expires IS NULL OR expires > datetime('now')) AND deleted_at IS NULL"
+        );
+
+        let mut params: Vec<&dyn ToSql> = vec![&title, &extension];
+        let id = id.to_i64();
+        params.push(&id);
+        params.extend(uids.iter().map(|uid| uid as &dyn ToSql));
+
+        let tx = self.conn.transaction()?;
+
+        if tx.execute(&update_sql, params.as_slice())? == 0 {
+            return Err(Error::Edit);
+        }
+
+        tx.execute(
+            "UPDATE entries_fts SET title = (SELECT title FROM entries WHERE id=?1) WHERE rowid=?1",
+            params![id],
+        )?;
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn set_expiration(
+        &mut self,
+        id: Id,
+        uids: &[i64],
+        expires: Option<SystemTime>,
+    ) -> Result<(), Error> {
+        if uids.is_empty() {
+            return Err(Error::Edit);
+        }
+
+        let placeholders = vec!["?"; uids.len()].join(",");
+        let update_sql = format!(
+            "UPDATE entries SET expires = datetime(?, 'unixepoch') WHERE id=? AND uid IN ({placeholders}) AND (expires IS NULL OR expires > datetime('now')) AND deleted_at IS NULL"
+        );
+
+        let mut params = Vec::with_capacity(uids.len() + 2);
+        params.push(expires.map(unix_time));
+        params.push(Some(id.to_i64()));
+        params.extend(uids.iter().copied().map(Some));
+
+        let affected = self.conn.execute(&update_sql, params_from_iter(&params))?;
+
+        if affected == 0 {
+            return Err(Error::Edit);
+        }
+
+        Ok(())
+    }
+
+    fn get_revision(&self, id: Id, rev: u32) -> Result<DatabaseEntry, Error> {
+        let mut entry = self.get(id)?;
+
+        // Never hand out view-limited content without counting the view.
+        if entry.metadata.max_views.is_some() || rev == 0 || rev > entry.metadata.revision {
+            return Err(Error::NotFound);
+        }
+
+        if rev < entry.metadata.revision {
+            (
+                entry.data,
+                entry.nonce,
+                entry.encoding,
+                entry.metadata.multi_file,
+            ) = self.conn.query_row(
+                "SELECT data, nonce, codec, level, dictionary, multi_file FROM revisions WHERE id=?1 AND rev=?2",
+                params![id.to_i64(), rev],
+                |row| {
+                    Ok((
+                        row.get(0)?,
+                        nonce_from_row(row, 1)?,
+                        encoding_from_row(row, 2)?,
+                        row.get(5)?,
+                    ))
+                },
+            )?;
+        }
+
+        Ok(entry)
+    }
+
+    fn view(&mut self, id: Id) -> Result<bool, Error> {
+        let tx = self.conn.transaction()?;
+        let (views, max_views): (u32, Option<u32>) = tx.query_row(
+            "UPDATE entries SET views = views + 1, accessed_at = datetime('now') WHERE id=?1 RETURNING views, max_views",
+            params![id.to_i64()],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+
+        let burned = max_views.is_some_and(|max_views| views >= max_views);
+
+        if burned {
+            let burned = affected(
+                &tx,
+                "DELETE FROM entries WHERE id=?1 RETURNING id, slug, uid",
+                params![id.to_i64()],
+            )?;
+            record(&tx, self.actor, Event::Burn, &burned)?;
+        }
+
+        tx.commit()?;
+        Ok(burned)
+    }
+
+    fn add_views(&mut self, views: &[(Id, u32, SystemTime)]) -> Result<(), Error> {
+        let tx = self.conn.transaction()?;
+
+        {
+            let mut stmt = tx.prepare(
+                "UPDATE entries SET views = views + ?2, accessed_at = datetime(?3, 'unixepoch') WHERE id=?1",
+            )?;
+
+            for (id, count, accessed) in views {
+                let accessed = accessed
+                    .duration_since(UNIX_EPOCH)
+                    .map_or(0, |d| d.as_secs());
+                stmt.execute(params![id.to_i64(), count, accessed])?;
+            }
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn next_uid(&mut self) -> Result<i64, Error> {
+        let uid = self.conn.query_row(
+            "UPDATE uids SET n = n + 1 WHERE id = 0 RETURNING n",
+            [],
+            |row| row.get(0),
+        )?;
+
+        Ok(uid)
+    }
+
+    fn list(&self) -> Result<Vec<ListEntry>, Error> {
+        let entries = self
+            .conn
+            .prepare("SELECT id, slug, title, nonce, expires, expires < datetime('now'), created_at, accessed_at, size, compressed_size, deleted_at FROM entries")?
+            .query_map([], |row| {
+                Ok(ListEntry {
+                    id: id_from_row(row, 0, 1)?,
+                    title: row.get(2)?,
+                    is_encrypted: row.get::<_, Option<Vec<u8>>>(3)?.is_some(),
+                    expiration: row.get(4)?,
+                    is_expired: row.get::<_, Option<bool>>(5)?.unwrap_or_default(),
+                    created: row.get(6)?,
+                    accessed: row.get(7)?,
+                    size: row.get(8)?,
+                    compressed_size: row.get(9)?,
+                    deleted: row.get(10)?,
+                })
+            })?
+            .collect::<Result<_, _>>()?;
+
+        Ok(entries)
+    }
+
+    fn purge(&mut self) -> Result<Vec<Id>, Error> {
+        let tx = self.conn.transaction()?;
+        let purged = affected(
+            &tx,
+            "DELETE FROM entries WHERE expires < datetime('now') OR purge_after <= datetime('now') RETURNING id, slug, uid",
+            [],
+        )?;
+        record(&tx, self.actor, Event::Purge, &purged)?;
+
+        // Uploads that take this long have been abandoned.
+        tx.execute(
+            "DELETE FROM uploads WHERE created_at < datetime('now', '-1 hour')",
+            [],
+        )?;
+
+        tx.commit()?;
+
+        Ok(purged.into_iter().map(|(id, _)| id).collect())
+    }
+
+    fn audit(&self, id: Option<Id>, since: Option<&str>) -> Result<Vec<audit::Record>, Error> {
+        let records = self
+            .conn
+            .prepare("SELECT id, slug, uid, event, actor, created_at FROM audit WHERE (?1 IS NULL OR id = ?1) AND (?2 IS NULL OR created_at >= ?2) ORDER BY seq")?
+            .query_map(params![id.map(Id::to_i64), since], |row| {
+                Ok(audit::Record {
+                    id: id_from_row(row, 0, 1)?,
+                    uid: row.get(2)?,
+                    event: row.get(3)?,
+                    actor: row.get(4)?,
+                    created: row.get(5)?,
+                })
+            })?
+            .collect::<Result<_, _>>()?;
+
+        Ok(records)
+    }
+
+    fn search(&self, query: &str) -> Result<Vec<SearchEntry>, Error> {
+        let entries = self
+            .conn
+            .prepare("SELECT entries_fts.rowid, entries.slug, entries_fts.title, snippet(entries_fts, 1, '', '', '…', 16) FROM entries_fts JOIN entries ON entries.id = entries_fts.rowid WHERE entries_fts MATCH ?1 AND entries.deleted_at IS NULL AND (entries.expires IS NULL OR entries.expires >= datetime('now')) ORDER BY rank")?
+            .query_map(params![query], |row| {
+                Ok(SearchEntry {
+                    id: id_from_row(row, 0, 1)?,
+                    title: row.get(2)?,
+                    snippet: row.get(3)?,
+                })
+            })?
+            .collect::<Result<_, _>>()?;
+
+        Ok(entries)
+    }
+
+    fn vacuum(&mut self) -> Result<(), Error> {
+        let auto_vacuum: u32 = self
+            .conn
+            .query_row("PRAGMA auto_vacuum", [], |row| row.get(0))?;
+
+        // Switching to incremental auto-vacuum only takes effect after a full vacuum.
+        if auto_vacuum != 2 {
+            tracing::info!("enabling incremental auto-vacuum");
+            self.conn
+                .execute_batch("PRAGMA auto_vacuum = INCREMENTAL; VACUUM;")?;
+        }
+
+        // Every step frees a single page, so run the pragma to completion.
+        let mut stmt = self.conn.prepare("PRAGMA incremental_vacuum")?;
+        let mut rows = stmt.query([])?;
+        while rows.next()?.is_some() {}
+
+        Ok(())
+    }
+
+    fn backup(&self, path: &Path) -> Result<(), Error> {
+        let mut dst = Connection::open(path)?;
+        let backup = Backup::new(&self.conn, &mut dst)?;
+
+        // Copy all pages in a single step, so that concurrent writes cannot restart the backup.
+        backup.run_to_completion(c_int::MAX, Duration::from_millis(100), None)?;
+
+        Ok(())
+    }
+
+    fn export(&self) -> Result<Vec<RawEntry>, Error> {
+        let mut revisions = self.conn.prepare(
+            "SELECT data, nonce, codec, level, dictionary, multi_file FROM revisions WHERE id=?1 ORDER BY rev",
+        )?;
+
+        self.conn
+            .prepare("SELECT id, uid, title, parent, created_at, expires, max_views, views, COALESCE(entries.data, blobs.data), nonce, COALESCE(entries.codec, blobs.codec), COALESCE(entries.level, blobs.level), COALESCE(entries.dictionary, blobs.dictionary), slug, (SELECT slug FROM entries AS parents WHERE parents.id = entries.parent), extension, multi_file FROM entries LEFT JOIN blobs ON blobs.hash = entries.blob WHERE deleted_at IS NULL")?
+            .query_map([], |row| {
+                let id: i64 = row.get(0)?;
+
+                let revisions = revisions
+                    .query_map(params![id], |row| {
+                        Ok(RawContent {
+                            data: row.get(0)?,
+                            nonce: nonce_from_row(row, 1)?,
+                            encoding: encoding_from_row(row, 2)?,
+                            multi_file: row.get(5)?,
+                        })
+                    })?
+                    .collect::<Result<_, _>>()?;
+
+                Ok(RawEntry {
+                    id: id_from_row(row, 0, 13)?,
+                    uid: row.get(1)?,
+                    title: row.get(2)?,
+                    extension: row.get(15)?,
+                    parent: parent_from_row(row, 3, 14)?,
+                    created: row.get(4)?,
+                    expires: row.get(5)?,
+                    max_views: row.get(6)?,
+                    views: row.get(7)?,
+                    content: RawContent {
+                        data: row.get(8)?,
+                        nonce: nonce_from_row(row, 9)?,
+                        encoding: encoding_from_row(row, 10)?,
+                        multi_file: row.get(16)?,
+                    },
+                    revisions,
+                })
+            })?
+            .collect::<Result<_, _>>()
+            .map_err(Error::from)
+    }
+
+    fn import(&mut self, entry: RawEntry, conflict: Conflict) -> Result<Option<Id>, Error> {
+        let tx = self.conn.transaction()?;
+
+        let exists = |id: Id| {
+            tx.query_row(
+                "SELECT EXISTS(SELECT 1 FROM entries WHERE id=?1)",
+                params![id.to_i64()],
+                |row| row.get::<_, bool>(0),
+            )
+        };
+
+        let mut id = entry.id;
+
+        if exists(id)? {
+            match conflict {
+                Conflict::Fail => return Err(Error::Conflict),
+                Conflict::Skip => return Ok(None),
+                Conflict::Replace => {
+                    let replaced = affected(
+                        &tx,
+                        "DELETE FROM entries WHERE id=?1 RETURNING id, slug, uid",
+                        params![id.to_i64()],
+                    )?;
+                    record(&tx, self.actor, Event::Delete, &replaced)?;
+                }
+                Conflict::NewId => {
+                    while exists(id)? {
+                        id = self.generator.generate();
+                    }
+                }
+            }
+        }
+
+        let RawContent {
+            data,
+            nonce,
+            encoding,
+            multi_file,
+        } = entry.content;
+        let compressed_size = data.len();
+
+        if let Some(max_storage) = self.max_storage {
+            let size = entry
+                .revisions
+                .iter()
+                .map(|revision| revision.data.len())
+                .fold(compressed_size, usize::saturating_add);
+
+            make_room(&tx, max_storage, size, None, self.actor)?;
+        }
+
+        let (data, blob, text, encoding) = match nonce {
+            None => {
+                let dictionary = encoding
+                    .dictionary
+                    .map(|id| get_dictionary(&tx, id))
+                    .transpose()?;
+                let text = codec::decode(&data, encoding, dictionary.as_ref())
+                    .map_err(|e| Error::Compression(e.to_string()))?;
+                let blob = store_blob(&tx, &text, &data, encoding)?;
+                (None, Some(blob), Some(text), None)
+            }
+            Some(_) => (Some(data), None, None, Some(encoding)),
+        };
+
+        tx.execute(
+            "INSERT INTO entries (id, uid, data, max_views, views, nonce, expires, title, parent, created_at, size, compressed_size, blob, codec, level, dictionary, slug, extension, multi_file) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19)",
+            params![
+                id.to_i64(),
+                entry.uid,
+                data,
+                entry.max_views,
+                entry.views,
+                nonce.as_ref().map(|n| n.as_slice()),
+                entry.expires,
+                entry.title,
+                entry.parent.map(Id::to_i64),
+                entry.created,
+                text.as_ref().map(Vec::len),
+                compressed_size,
+                blob,
+                encoding.map(|encoding| encoding.codec),
+                encoding.and_then(|encoding| encoding.level),
+                encoding.and_then(|encoding| encoding.dictionary),
+                id.slug(),
+                entry.extension,
+                multi_file,
+            ],
+        )?;
+
+        if let Some(text) = text {
+            tx.execute(
+                "INSERT INTO entries_fts (rowid, title, text) VALUES (?1, ?2, ?3)",
+                params![
+                    id.to_i64(),
+                    entry.title,
+                    if multi_file {
+                        files::searchable(&String::from_utf8_lossy(&text))
+                    } else {
+                        String::from_utf8_lossy(&text).into_owned()
+                    }
+                ],
+            )?;
+        }
+
+        for (
+            rev,
+            RawContent {
+                data,
+                nonce,
+                encoding,
+                multi_file,
+            },
+        ) in (1u32..).zip(entry.revisions)
+        {
+            tx.execute(
+                "INSERT INTO revisions (id, rev, data, nonce, codec, level, dictionary, multi_file) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![
+                    id.to_i64(),
+                    rev,
+                    data,
+                    nonce.as_ref().map(|n| n.as_slice()),
+                    encoding.codec,
+                    encoding.level,
+                    encoding.dictionary,
+                    multi_file,
+                ],
+            )?;
+        }
+
+        // Keep new local owners apart from imported ones.
+        if let Some(uid) = entry.uid {
+            tx.execute("UPDATE uids SET n = MAX(n, ?1) WHERE id = 0", params![uid])?;
+        }
+
+        record(&tx, self.actor, Event::Create, &[(id, entry.uid)])?;
+        tx.commit()?;
+
+        Ok(Some(id))
+    }
+
+    fn dictionaries(&self) -> Result<Vec<Dictionary>, Error> {
+        self.conn
+            .prepare("SELECT id, data FROM dictionaries ORDER BY id")?
+            .query_map([], |row| {
+                Ok(Dictionary {
+                    id: row.get(0)?,
+                    data: row.get(1)?,
+                })
+            })?
+            .collect::<Result<_, _>>()
+            .map_err(Error::from)
+    }
+
+    fn get_dictionary(&self, id: u32) -> Result<Dictionary, Error> {
+        Ok(get_dictionary(&self.conn, id)?)
+    }
+
+    fn add_dictionary(&mut self, data: Vec<u8>) -> Result<u32, Error> {
+        Ok(self.conn.query_row(
+            "INSERT INTO dictionaries (data, created_at) VALUES (?1, datetime('now')) RETURNING id",
+            params![data],
+            |row| row.get(0),
+        )?)
+    }
+
+    fn recompress(
+        &mut self,
+        level: i32,
+        dictionary: Option<&Dictionary>,
+    ) -> Result<Recompressed, Error> {
+        let tx = self.conn.transaction()?;
+        let mut dictionaries = HashMap::new();
+
+        let mut recode = |data: &[u8], encoding: Encoding| -> Result<(Vec<u8>, Encoding), Error> {
+            let previous = match encoding.dictionary {
+                Some(id) => Some(match dictionaries.entry(id) {
+                    MapEntry::Occupied(entry) => entry.into_mut(),
+                    MapEntry::Vacant(entry) => entry.insert(get_dictionary(&tx, id)?),
+                }),
+                None => None,
+            };
+
+            let text = codec::decode(data, encoding, previous.map(|dictionary| &*dictionary))
+                .map_err(|e| Error::Compression(e.to_string()))?;
+
+            codec::encode(&text, level, dictionary::for_size(dictionary, text.len()))
+                .map_err(|e| Error::Compression(e.to_string()))
+        };
+
+        let mut recompressed = Recompressed::default();
+
+        let hashes = tx
+            .prepare("SELECT hash FROM blobs")?
+            .query_map([], |row| row.get::<_, Vec<u8>>(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for hash in hashes {
+            let (data, encoding) = tx.query_row(
+                "SELECT data, codec, level, dictionary FROM blobs WHERE hash=?1",
+                params![hash],
+                |row| Ok((row.get::<_, Vec<u8>>(0)?, encoding_from_row(row, 1)?)),
+            )?;
+
+            let (data, encoding) = recode(&data, encoding)?;
+
+            tx.execute(
+                "UPDATE blobs SET data=?1, codec=?2, level=?3, dictionary=?4 WHERE hash=?5",
+                params![
+                    data,
+                    encoding.codec,
+                    encoding.level,
+                    encoding.dictionary,
+                    hash
+                ],
+            )?;
+            tx.execute(
+                "UPDATE entries SET compressed_size=?1 WHERE blob=?2",
+                params![data.len(), hash],
+            )?;
+
+            recompressed.rewritten += 1;
+        }
+
+        let revisions = tx
+            .prepare("SELECT id, rev FROM revisions WHERE nonce IS NULL")?
+            .query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, u32>(1)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for (id, rev) in revisions {
+            let (data, encoding) = tx.query_row(
+                "SELECT data, codec, level, dictionary FROM revisions WHERE id=?1 AND rev=?2",
+                params![id, rev],
+                |row| Ok((row.get::<_, Vec<u8>>(0)?, encoding_from_row(row, 1)?)),
+            )?;
+
+            let (data, encoding) = recode(&data, encoding)?;
+
+            tx.execute(
+                "UPDATE revisions SET data=?1, codec=?2, level=?3, dictionary=?4 WHERE id=?5 AND rev=?6",
+                params![
+                    data,
+                    encoding.codec,
+                    encoding.level,
+                    encoding.dictionary,
+                    id,
+                    rev
+                ],
+            )?;
+
+            recompressed.rewritten += 1;
+        }
+
+        recompressed.skipped = tx.query_row(
+            "SELECT (SELECT COUNT(*) FROM entries WHERE nonce IS NOT NULL) + (SELECT COUNT(*) FROM revisions WHERE nonce IS NOT NULL)",
+            [],
+            |row| row.get(0),
+        )?;
+
+        tx.commit()?;
+
+        Ok(recompressed)
+    }
+
+    fn reader(&self) -> Result<Option<Box<dyn Storage>>, Error> {
+        let Some(path) = &self.path else {
+            return Ok(None);
+        };
+
+        let conn = Connection::open_with_flags(
+            path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )?;
+
+        Ok(Some(Box::new(Self {
+            conn,
+            path: Some(path.clone()),
+            max_storage: None,
+            generator: Generator::default(),
+            trash_period: self.trash_period,
+            actor: self.actor,
+        })))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::db::{Backend, Config, Database, Owners};
+
+    use super::*;
+
+    /// Remove database at `path` including its write-ahead log.
+    fn remove_db(path: &std::path::Path) -> std::io::Result<()> {
+        for suffix in ["-wal", "-shm"] {
+            let mut file = path.as_os_str().to_owned();
+            file.push(suffix);
+            let _ = std::fs::remove_file(file);
+        }
+
+        std::fs::remove_file(path)
+    }
+
+    fn blob_refs(path: &std::path::Path) -> Result<Vec<i64>, rusqlite::Error> {
+        let conn = Connection::open(path)?;
+        let mut stmt = conn.prepare("SELECT refs FROM blobs ORDER BY refs")?;
+        stmt.query_map([], |row| row.get(0))?.collect()
+    }
+
+    /// Running total of the `storage` table and the actual size of all stored data.
+    fn storage_used(path: &std::path::Path) -> Result<(i64, i64), rusqlite::Error> {
+        Connection::open(path)?.query_row(
+            "SELECT (SELECT used FROM storage), (SELECT COALESCE(SUM(length(data)), 0) FROM entries) + (SELECT COALESCE(SUM(length(data)), 0) FROM blobs) + (SELECT COALESCE(SUM(length(data)), 0) FROM revisions) + (SELECT COALESCE(SUM(length(data)), 0) FROM uploads)",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+    }
+
+    #[test]
+    fn audit_is_append_only() -> Result<(), Box<dyn std::error::Error>> {
+        let storage = Sqlite::open(Open::Memory, Access::Migrate)?.with_actor("test");
+        record(
+            &storage.conn,
+            storage.actor,
+            Event::Create,
+            &[(Id::from(1u32), None)],
+        )?;
+
+        assert!(storage.conn.execute("DELETE FROM audit", []).is_err());
+        assert!(
+            storage
+                .conn
+                .execute("UPDATE audit SET event = 'burn'", [])
+                .is_err()
+        );
+        assert_eq!(storage.audit(None, None)?[0].event, Event::Create);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn migrate_down_and_up() -> Result<(), Box<dyn std::error::Error>> {
+        let path = std::env::temp_dir().join(format!("wastebin-migrate-{}.db", Id::rand()));
+        let latest = SCRIPTS.len();
+
+        let (db, handler) = Database::new(Open::Path(path.clone()))?;
+        tokio::spawn(handler);
+        let entry = write::Entry {
+            text: "hello".to_string(),
+            ..Default::default()
+        };
+        let (id, _entry) = db.insert(entry).await?;
+        drop(db);
+
+        assert_eq!(schema_version(&path)?, latest);
+        assert!(matches!(
+            plan(latest, 17),
+            Err(Error::Irreversible("0018-add-slug-column"))
+        ));
+        assert!(matches!(
+            plan(latest, latest + 1),
+            Err(Error::UnknownVersion(_))
+        ));
+
+        let steps = migrate(&path, 18)?;
+        assert_eq!(steps.len(), latest - 18);
+        assert!(matches!(steps[0], Step::Revert(Migration { version, .. }) if version == latest));
+        assert_eq!(schema_version(&path)?, 18);
+
+        for access in [Access::ReadWrite, Access::ReadOnly] {
+            assert!(matches!(
+                Sqlite::open(Open::Path(path.clone()), access),
+                Err(Error::Schema { current: 18, .. })
+            ));
+        }
+
+        assert_eq!(migrate(&path, latest)?.len(), latest - 18);
+        assert!(migrate(&path, latest)?.is_empty());
+
+        let storage = Sqlite::open(Open::Path(path.clone()), Access::ReadOnly)?;
+        assert_eq!(storage.get_metadata(id)?.size, Some(5));
+
+        let (running, actual) = storage_used(&path)?;
+        assert_eq!(running, actual);
+
+        // A newer schema can still be inspected but not written to.
+        Connection::open(&path)?.pragma_update(None, "user_version", latest + 1)?;
+        assert!(Sqlite::open(Open::Path(path.clone()), Access::ReadOnly).is_ok());
+        assert!(matches!(
+            Sqlite::open(Open::Path(path.clone()), Access::ReadWrite),
+            Err(Error::Schema { .. })
+        ));
+
+        remove_db(&path)?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn dedup() -> Result<(), Box<dyn std::error::Error>> {
+        let path = std::env::temp_dir().join(format!("wastebin-dedup-{}.db", Id::rand()));
+        let (db, handler) = Database::new(Open::Path(path.clone()))?;
+        tokio::spawn(handler);
+
+        let log = "the same log line\n".repeat(100);
+
+        let entry = write::Entry {
+            text: log.clone(),
+            uid: Some(42),
+            ..Default::default()
+        };
+        let (first, _entry) = db.insert(entry).await?;
+
+        let entry = write::Entry {
+            text: log.clone(),
+            ..Default::default()
+        };
+        let (second, _entry) = db.insert(entry).await?;
+
+        let entry = write::Entry {
+            text: log.clone(),
+            password: Some("password".to_string()),
+            ..Default::default()
+        };
+        let (encrypted, _entry) = db.insert(entry).await?;
+
+        assert_eq!(blob_refs(&path)?, vec![2]);
+        assert_eq!(db.get(second, None).await?.unwrap_inner().text, log);
+
+        let (running, actual) = storage_used(&path)?;
+        assert!(running > 0);
+        assert_eq!(running, actual);
+
+        let entry = write::Entry {
+            text: "edited".to_string(),
+            ..Default::default()
+        };
+        db.edit(first, &[42], entry).await?;
+        assert_eq!(blob_refs(&path)?, vec![1, 1]);
+        assert_eq!(db.get_revision(first, 1, None).await?.text, log);
+
+        let (running, actual) = storage_used(&path)?;
+        assert_eq!(running, actual);
+
+        db.delete_many(vec![first, second, encrypted]).await?;
+        assert!(blob_refs(&path)?.is_empty());
+        assert_eq!(storage_used(&path)?, (0, 0));
+
+        remove_db(&path)?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn readers() -> Result<(), Box<dyn std::error::Error>> {
+        let path = std::env::temp_dir().join(format!("wastebin-readers-{}.db", Id::rand()));
+        let (db, handler) = Database::open(
+            Backend::Sqlite,
+            Open::Path(path.clone()),
+            Config {
+                readers: 2,
+                ..Default::default()
+            },
+        )?;
+        tokio::spawn(handler);
+
+        let journal_mode: String =
+            Connection::open(&path)?.query_row("PRAGMA journal_mode", [], |row| row.get(0))?;
+        assert_eq!(journal_mode, "wal");
+
+        let entry = write::Entry {
+            text: "hello".to_string(),
+            ..Default::default()
+        };
+        let (id, _entry) = db.insert(entry).await?;
+
+        let reads = (0..8)
+            .map(|_| {
+                let db = db.clone();
+                tokio::spawn(async move { db.get(id, None).await })
+            })
+            .collect::<Vec<_>>();
+
+        for read in reads {
+            assert_eq!(read.await??.unwrap_inner().text, "hello");
+        }
+
+        let metadata = db.get_metadata(id).await?;
+        assert_eq!(metadata.views, 8);
+        assert!(metadata.accessed.is_some());
+        assert_eq!(db.list().await?.len(), 1);
+
+        remove_db(&path)?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn vacuum() -> Result<(), Box<dyn std::error::Error>> {
+        let path = std::env::temp_dir().join(format!("wastebin-vacuum-{}.db", Id::rand()));
+        let (db, handler) = Database::new(Open::Path(path.clone()))?;
+        tokio::spawn(handler);
+
+        let free_pages = || -> Result<u64, rusqlite::Error> {
+            Connection::open(&path)?.query_row("PRAGMA freelist_count", [], |row| row.get(0))
+        };
+
+        db.vacuum().await?;
+
+        let mut ids = Vec::new();
+
+        for _ in 0..64 {
+            let entry = write::Entry {
+                text: (0..512)
+                    .map(|_| rand::random::<u64>().to_string())
+                    .collect(),
+                ..Default::default()
+            };
+            ids.push(db.insert(entry).await?.0);
+        }
+
+        db.delete_many(ids).await?;
+        assert!(free_pages()? > 0);
+
+        db.vacuum().await?;
+        assert_eq!(free_pages()?, 0);
+
+        remove_db(&path)?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn backup() -> Result<(), Box<dyn std::error::Error>> {
+        let path = std::env::temp_dir().join(format!("wastebin-backup-{}.db", Id::rand()));
+        let output = path.with_extension("backup.db");
+        let (db, handler) = Database::open(
+            Backend::Sqlite,
+            Open::Path(path.clone()),
+            Config {
+                readers: 1,
+                ..Default::default()
+            },
+        )?;
+        tokio::spawn(handler);
+
+        let entry = write::Entry {
+            text: "hello".to_string(),
+            ..Default::default()
+        };
+        let (id, _entry) = db.insert(entry).await?;
+
+        db.backup(output.clone()).await?;
+
+        let (backup, handler) = Database::new(Open::Path(output.clone()))?;
+        tokio::spawn(handler);
+        assert_eq!(backup.get(id, None).await?.unwrap_inner().text, "hello");
+
+        remove_db(&path)?;
+        remove_db(&output)?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn export_import() -> Result<(), Box<dyn std::error::Error>> {
+        let (db, handler) = Database::new(Open::Memory)?;
+        tokio::spawn(handler);
+
+        let entry = write::Entry {
+            text: "first".to_string(),
+            extension: Some("rs".to_string()),
+            title: Some("title".to_string()),
+            uid: Some(42),
+            ..Default::default()
+        };
+        let (plain, _entry) = db.insert(entry).await?;
+
+        let entry = write::Entry {
+            text: "second".to_string(),
+            ..Default::default()
+        };
+        db.edit(plain, &[42], entry).await?;
+
+        let entry = write::Entry {
+            text: "secret".to_string(),
+            password: Some("password".to_string()),
+            ..Default::default()
+        };
+        let (encrypted, _entry) = db.insert(entry).await?;
+
+        let records = db
+            .export()
+            .await?
+            .into_iter()
+            .map(crate::archive::Record::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let (imported, handler) = Database::new(Open::Memory)?;
+        tokio::spawn(handler);
+
+        let mut owners = imported.owners().await?;
+        assert_eq!(owners, Owners::Keep);
+
+        let entries = records
+            .into_iter()
+            .map(RawEntry::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for entry in entries.clone() {
+            let id = entry.id;
+            assert_eq!(
+                imported
+                    .import(entry.clone(), Conflict::Fail, &mut owners)
+                    .await?,
+                Some(id)
+            );
+            assert!(matches!(
+                imported
+                    .import(entry.clone(), Conflict::Fail, &mut owners)
+                    .await,
+                Err(Error::Conflict)
+            ));
+            assert_eq!(
+                imported
+                    .import(entry.clone(), Conflict::Skip, &mut owners)
+                    .await?,
+                None
+            );
+            assert!(
+                imported
+                    .import(entry, Conflict::NewId, &mut owners)
+                    .await?
+                    .is_some_and(|new| new != id)
+            );
+        }
+
+        // Restored owners keep their uids and new ones do not collide with them.
+        assert!(imported.next_uid().await? > 42);
+
+        let data = imported.get(plain, None).await?.unwrap_inner();
+        assert_eq!(data.text, "second");
+        assert_eq!(data.metadata.title.as_deref(), Some("title"));
+        assert_eq!(data.metadata.extension.as_deref(), Some("rs"));
+        assert_eq!(data.metadata.uid, Some(42));
+        assert_eq!(imported.get_revision(plain, 1, None).await?.text, "first");
+        assert_eq!(imported.search("second").await?.len(), 2);
+
+        let password = crate::crypto::Password::from("password".as_bytes().to_vec());
+        let data = imported
+            .get(encrypted, Some(password))
+            .await?
+            .unwrap_inner();
+        assert_eq!(data.text, "secret");
+
+        // Merged owners get new uids, so that local users do not own imported entries.
+        let mut owners = db.owners().await?;
+        assert!(matches!(owners, Owners::Remap(_)));
+
+        for entry in entries {
+            let id = db
+                .import(entry, Conflict::Replace, &mut owners)
+                .await?
+                .unwrap();
+            assert_ne!(db.get_metadata(id).await?.uid, Some(42));
+        }
+
+        assert!(matches!(owners, Owners::Remap(uids) if uids.len() == 1));
+        assert!(matches!(
+            db.edit(plain, &[42], write::Entry::default()).await,
+            Err(Error::Edit)
+        ));
+
+        let events = db.audit(Some(plain), None).await?;
+        assert!(events.iter().any(|record| record.event == Event::Delete));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn trash_deadline() -> Result<(), Box<dyn std::error::Error>> {
+        let path = std::env::temp_dir().join(format!("wastebin-trash-{}.db", Id::rand()));
+        let config = Config {
+            trash_period: Some(Duration::from_secs(60)),
+            ..Default::default()
+        };
+        let (db, handler) = Database::open(Backend::Sqlite, Open::Path(path.clone()), config)?;
+        tokio::spawn(handler);
+
+        let (id, _entry) = db.insert(write::Entry::default()).await?;
+        db.delete_many(vec![id]).await?;
+
+        // The deadline is stored with the entry, not taken from the trash period of the process.
+        let (other, handler) = Database::new(Open::Path(path.clone()))?;
+        tokio::spawn(handler);
+
+        assert!(other.purge().await?.is_empty());
+        assert!(other.get_trashed(id).await.is_ok());
+        other.restore(id).await?;
+        assert!(db.get_metadata(id).await.is_ok());
+
+        remove_db(&path)?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn quota() -> Result<(), Box<dyn std::error::Error>> {
+        let config = Config {
+            max_storage: NonZeroU64::new(1024),
+            ..Default::default()
+        };
+        let (db, handler) = Database::open(Backend::Sqlite, Open::Memory, config)?;
+        tokio::spawn(handler);
+
+        // Random text barely compresses and takes up about 400 bytes each.
+        let text = || (0..50).map(|_| Id::rand().to_string()).collect::<String>();
+
+        let entry = write::Entry {
+            text: text(),
+            ..Default::default()
+        };
+        let (permanent, _entry) = db.insert(entry).await?;
+
+        let entry = write::Entry {
+            text: text(),
+            expires: NonZeroU32::new(60),
+            ..Default::default()
+        };
+        let (expiring, _entry) = db.insert(entry).await?;
+
+        // Evicts the expiring entry but never the permanent one.
+        let entry = write::Entry {
+            text: text(),
+            expires: NonZeroU32::new(3600),
+            ..Default::default()
+        };
+        let (newest, _entry) = db.insert(entry).await?;
+
+        assert!(matches!(db.get(expiring, None).await, Err(Error::NotFound)));
+        assert!(db.get(permanent, None).await.is_ok());
+
+        // Nothing can be evicted to make room for an entry that is too large.
+        let entry = write::Entry {
+            text: text() + &text(),
+            ..Default::default()
+        };
+        assert!(matches!(db.insert(entry).await, Err(Error::QuotaExceeded)));
+        assert!(db.get(newest, None).await.is_ok());
+
+        // Streamed content is refused before it is stored.
+        let streamed = text() + &text();
+        assert!(matches!(
+            db.insert_stream(write::Entry::default(), streamed.as_bytes())
+                .await,
+            Err(Error::QuotaExceeded)
+        ));
+        assert!(db.get(newest, None).await.is_ok());
+
+        // Imports are subject to the same quota.
+        let (other, handler) = Database::open(Backend::Sqlite, Open::Memory, Config::default())?;
+        tokio::spawn(handler);
+
+        let entry = write::Entry {
+            text: text() + &text(),
+            ..Default::default()
+        };
+        other.insert(entry).await?;
+        let exported = other.export().await?.pop().unwrap();
+        let mut owners = db.owners().await?;
+        assert!(matches!(
+            db.import(exported, Conflict::NewId, &mut owners).await,
+            Err(Error::QuotaExceeded)
+        ));
+        assert!(db.get(newest, None).await.is_ok());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn dictionary() -> Result<(), Box<dyn std::error::Error>> {
+        let path = std::env::temp_dir().join(format!("wastebin-dictionary-{}.db", Id::rand()));
+        let (db, handler) = Database::new(Open::Path(path.clone()))?;
+        tokio::spawn(handler);
+
+        let line = |n: u32| format!("[{n}] INFO request GET /api/v1/users/{n} completed in {n} ms");
+
+        let entry = write::Entry {
+            text: line(0),
+            uid: Some(42),
+            ..Default::default()
+        };
+        let (old, _entry) = db.insert(entry).await?;
+        let old_size = db.get_metadata(old).await?.compressed_size;
+
+        for n in 1..1000 {
+            let entry = write::Entry {
+                text: line(n),
+                ..Default::default()
+            };
+            db.insert(entry).await?;
+        }
+
+        let (dictionary, samples) = db.train_dictionary(4096).await?;
+        assert_eq!(samples, 1000);
+
+        let entry = write::Entry {
+            text: line(1000),
+            ..Default::default()
+        };
+        let (new, _entry) = db.insert(entry).await?;
+        assert!(db.get_metadata(new).await?.compressed_size < old_size);
+        assert_eq!(db.get(new, None).await?.unwrap_inner().text, line(1000));
+        assert!(db.stream_zstd(new).await?.is_none());
+
+        // Large content is compressed without the dictionary, also when streamed.
+        let large = (0..200).map(line).collect::<Vec<_>>().join("\n");
+        let entry = write::Entry {
+            text: large.clone(),
+            ..Default::default()
+        };
+        let (inserted, _entry) = db.insert(entry).await?;
+        assert!(db.stream_zstd(inserted).await?.is_some());
+
+        let (streamed, _entry) = db
+            .insert_stream(write::Entry::default(), large.as_bytes())
+            .await?;
+        assert!(db.stream_zstd(streamed).await?.is_some());
+
+        let (streamed, _entry) = db
+            .insert_stream(write::Entry::default(), line(1003).as_bytes())
+            .await?;
+        assert!(db.stream_zstd(streamed).await?.is_none());
+        assert_eq!(
+            db.get(streamed, None).await?.unwrap_inner().text,
+            line(1003)
+        );
+
+        // Entries compressed without and with a dictionary stay readable side by side.
+        let entry = write::Entry {
+            text: line(1001),
+            ..Default::default()
+        };
+        db.edit(old, &[42], entry).await?;
+        assert_eq!(db.get(old, None).await?.unwrap_inner().text, line(1001));
+        assert_eq!(db.get_revision(old, 1, None).await?.text, line(0));
+
+        let exported = db.export().await?;
+        assert!(
+            exported
+                .iter()
+                .all(|entry| entry.content.encoding.dictionary.is_none())
+        );
+
+        // Another instance loads the dictionary when opening the database.
+        let (reopened, handler) = Database::new(Open::Path(path.clone()))?;
+        tokio::spawn(handler);
+        assert_eq!(
+            reopened.get(new, None).await?.unwrap_inner().text,
+            line(1000)
+        );
+
+        let (second, _samples) = reopened.train_dictionary(4096).await?;
+        assert!(second > dictionary);
+
+        // The first instance fetches dictionaries added by another instance on demand.
+        let entry = write::Entry {
+            text: line(1002),
+            ..Default::default()
+        };
+        let (newest, _entry) = reopened.insert(entry).await?;
+        assert_eq!(db.get(newest, None).await?.unwrap_inner().text, line(1002));
+
+        remove_db(&path)?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn recompress() -> Result<(), Box<dyn std::error::Error>> {
+        let path = std::env::temp_dir().join(format!("wastebin-recompress-{}.db", Id::rand()));
+        let config = Config {
+            compression_level: Some(1),
+            ..Default::default()
+        };
+        let (db, handler) = Database::open(Backend::Sqlite, Open::Path(path.clone()), config)?;
+        tokio::spawn(handler);
+
+        let codecs =
+            |path: &std::path::Path| -> Result<Vec<(String, Option<i32>)>, rusqlite::Error> {
+                let conn = Connection::open(path)?;
+                let mut stmt =
+                    conn.prepare("SELECT codec, level FROM blobs ORDER BY codec, level")?;
+                stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+                    .collect()
+            };
+
+        let text = "hello world ".repeat(100);
+
+        let entry = write::Entry {
+            text: "hi".to_string(),
+            ..Default::default()
+        };
+        let (tiny, _entry) = db.insert(entry).await?;
+        assert_eq!(db.get_metadata(tiny).await?.compressed_size, Some(2));
+
+        let entry = write::Entry {
+            text: text.clone(),
+            uid: Some(42),
+            ..Default::default()
+        };
+        let (large, _entry) = db.insert(entry).await?;
+
+        let entry = write::Entry {
+            text: "secret".to_string(),
+            password: Some("password".to_string()),
+            ..Default::default()
+        };
+        let (encrypted, _entry) = db.insert(entry).await?;
+
+        assert_eq!(
+            codecs(&path)?,
+            vec![("none".to_string(), None), ("zstd".to_string(), Some(1))]
+        );
+
+        // Content written before codecs were recorded is read as zstd.
+        Connection::open(&path)?.execute(
+            "UPDATE blobs SET codec=NULL, level=NULL WHERE codec='zstd'",
+            [],
+        )?;
+        assert_eq!(db.get(large, None).await?.unwrap_inner().text, text);
+
+        let entry = write::Entry {
+            text: text.to_uppercase(),
+            ..Default::default()
+        };
+        db.edit(large, &[42], entry).await?;
+
+        let recompressed = db.recompress(19, false).await?;
+        // Two blobs and the revision holding the previous content.
+        assert_eq!(recompressed.rewritten, 3);
+        assert_eq!(recompressed.skipped, 1);
+
+        assert_eq!(
+            codecs(&path)?,
+            vec![("none".to_string(), None), ("zstd".to_string(), Some(19))]
+        );
+
+        assert_eq!(db.get(tiny, None).await?.unwrap_inner().text, "hi");
+        assert_eq!(
+            db.get(large, None).await?.unwrap_inner().text,
+            text.to_uppercase()
+        );
+        assert_eq!(db.get_revision(large, 1, None).await?.text, text);
+
+        let password = crate::crypto::Password::from("password".as_bytes().to_vec());
+        let data = db.get(encrypted, Some(password)).await?.unwrap_inner();
+        assert_eq!(data.text, "secret");
+
+        remove_db(&path)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,108 @@
+//! Trained zstd dictionaries. Small pastes barely compress on their own because every zstd frame
+//! starts from scratch. A dictionary trained on existing pastes primes the compressor with common
+//! content, so that a few hundred bytes compress about as well as large pastes.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Default upper bound for the size of a trained dictionary, same as the zstd CLI.
+pub const DEFAULT_MAX_SIZE: usize = 112_640;
+
+/// Largest content in bytes that is compressed with a dictionary. Larger content compresses well
+/// on its own and is kept a plain zstd frame that can be served as is.
+pub const MAX_CONTENT_SIZE: usize = 4096;
+
+/// A dictionary as stored in the database.
+#[derive(Debug)]
+pub struct Dictionary {
+    /// Version identifier referenced by entries compressed with this dictionary
+    pub id: u32,
+    /// Raw dictionary content
+    pub data: Vec<u8>,
+}
+
+/// Dictionaries known to a [`crate::db::Database`].
+#[derive(Debug, Default)]
+pub(crate) struct Dictionaries {
+    /// Dictionary used to compress new entries, i.e. the most recently trained one
+    pub current: Option<Arc<Dictionary>>,
+    /// All dictionaries by id
+    pub by_id: HashMap<u32, Arc<Dictionary>>,
+}
+
+impl Dictionaries {
+    /// Add `dictionary` and make it the current one if it is newer than the current one.
+    pub fn add(&mut self, dictionary: Dictionary) -> Arc<Dictionary> {
+        let dictionary = Arc::new(dictionary);
+
+        if self
+            .current
+            .as_ref()
+            .is_none_or(|current| current.id < dictionary.id)
+        {
+            self.current = Some(dictionary.clone());
+        }
+
+        self.by_id.insert(dictionary.id, dictionary.clone());
+        dictionary
+    }
+}
+
+/// `dictionary` if content of `size` bytes is small enough to be compressed with it.
+pub fn for_size(dictionary: Option<&Dictionary>, size: usize) -> Option<&Dictionary> {
+    dictionary.filter(|_| size <= MAX_CONTENT_SIZE)
+}
+
+/// Train a dictionary of at most `max_size` bytes from `samples`.
+pub fn train(samples: &[Vec<u8>], max_size: usize) -> std::io::Result<Vec<u8>> {
+    zstd::dict::from_samples(samples, max_size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec;
+
+    #[test]
+    fn train_and_decode() -> Result<(), Box<dyn std::error::Error>> {
+        let samples = (0..1000)
+            .map(|n| format!("[{n}] INFO request GET /api/v1/users/{n} completed in {n} ms").into())
+            .collect::<Vec<Vec<u8>>>();
+
+        let dictionary = Dictionary {
+            id: 1,
+            data: train(&samples, 4096)?,
+        };
+
+        let text = b"[1234] INFO request GET /api/v1/users/1234 completed in 1234 ms";
+        let (plain, _encoding) = codec::encode(text, codec::DEFAULT_LEVEL, None)?;
+        let (compressed, encoding) = codec::encode(text, codec::DEFAULT_LEVEL, Some(&dictionary))?;
+
+        assert_eq!(encoding.dictionary, Some(1));
+        assert!(compressed.len() < plain.len());
+        assert_eq!(
+            codec::decode(&compressed, encoding, Some(&dictionary))?,
+            text
+        );
+        assert!(codec::decode(&compressed, encoding, None).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn current_is_newest() {
+        let mut dictionaries = Dictionaries::default();
+
+        dictionaries.add(Dictionary {
+            id: 2,
+            data: Vec::new(),
+        });
+        dictionaries.add(Dictionary {
+            id: 1,
+            data: Vec::new(),
+        });
+
+        assert_eq!(dictionaries.current.map(|current| current.id), Some(2));
+        assert_eq!(dictionaries.by_id.len(), 2);
+    }
+}
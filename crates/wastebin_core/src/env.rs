@@ -2,26 +2,60 @@
 pub mod vars {
     /// Address and port the server binds to.
     pub const ADDRESS_PORT: &str = "WASTEBIN_ADDRESS_PORT";
+    /// Seconds between scheduled backups.
+    pub const BACKUP_INTERVAL: &str = "WASTEBIN_BACKUP_INTERVAL";
+    /// Directory for scheduled backups.
+    pub const BACKUP_PATH: &str = "WASTEBIN_BACKUP_PATH";
+    /// Number of scheduled backups to keep.
+    pub const BACKUP_RETENTION: &str = "WASTEBIN_BACKUP_RETENTION";
     /// Base URL to use for the QR code link.
     pub const BASE_URL: &str = "WASTEBIN_BASE_URL";
     /// Number of cached items.
     pub const CACHE_SIZE: &str = "WASTEBIN_CACHE_SIZE";
+    /// zstd compression level for new pastes.
+    pub const COMPRESSION_LEVEL: &str = "WASTEBIN_COMPRESSION_LEVEL";
     /// Path to the database file.
     pub const DATABASE_PATH: &str = "WASTEBIN_DATABASE_PATH";
     /// Time before a request times out.
     pub const HTTP_TIMEOUT: &str = "WASTEBIN_HTTP_TIMEOUT";
+    /// Generator of paste identifiers.
+    pub const ID_GENERATOR: &str = "WASTEBIN_ID_GENERATOR";
+    /// Length of generated paste identifiers.
+    pub const ID_LENGTH: &str = "WASTEBIN_ID_LENGTH";
     /// Maximum body size.
     pub const MAX_BODY_SIZE: &str = "WASTEBIN_MAX_BODY_SIZE";
+    /// Maximum size of unencrypted content streamed into the database.
+    pub const MAX_STREAM_SIZE: &str = "WASTEBIN_MAX_STREAM_SIZE";
+    /// Maximum size of all stored paste data.
+    pub const MAX_STORAGE: &str = "WASTEBIN_MAX_STORAGE";
     /// Password salt for encryption.
     pub const PASSWORD_SALT: &str = "WASTEBIN_PASSWORD_SALT";
     /// Expirations list.
     pub const PASTE_EXPIRATIONS: &str = "WASTEBIN_PASTE_EXPIRATIONS";
+    /// Seconds between purges of expired pastes.
+    pub const PURGE_INTERVAL: &str = "WASTEBIN_PURGE_INTERVAL";
+    /// Reclaim disk space after purging.
+    pub const PURGE_VACUUM: &str = "WASTEBIN_PURGE_VACUUM";
+    /// Number of read-only database connections.
+    pub const READER_POOL_SIZE: &str = "WASTEBIN_READER_POOL_SIZE";
     /// Signing key for signed cookie store.
     pub const SIGNING_KEY: &str = "WASTEBIN_SIGNING_KEY";
+    /// Allow custom identifiers.
+    pub const SLUGS: &str = "WASTEBIN_SLUGS";
+    /// Characters allowed in custom identifiers.
+    pub const SLUG_CHARS: &str = "WASTEBIN_SLUG_CHARS";
+    /// Maximum length of custom identifiers.
+    pub const SLUG_MAX_LENGTH: &str = "WASTEBIN_SLUG_MAX_LENGTH";
+    /// Minimum length of custom identifiers.
+    pub const SLUG_MIN_LENGTH: &str = "WASTEBIN_SLUG_MIN_LENGTH";
+    /// Storage backend.
+    pub const STORAGE: &str = "WASTEBIN_STORAGE";
     /// Theme to use.
     pub const THEME: &str = "WASTEBIN_THEME";
     /// Title.
     pub const TITLE: &str = "WASTEBIN_TITLE";
+    /// Seconds deleted pastes stay restorable.
+    pub const TRASH_PERIOD: &str = "WASTEBIN_TRASH_PERIOD";
     /// Unix socket path the server binds to.
     pub const SOCKET_PATH: &str = "WASTEBIN_UNIX_SOCKET_PATH";
 }
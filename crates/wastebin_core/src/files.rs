@@ -0,0 +1,155 @@
+//! Pastes consisting of several named files. The files of such a paste are stored as a single
+//! piece of content, the files serialized as JSON, so that compression, encryption, deduplication
+//! and revisions apply to them like to any other paste. File names are encrypted along with the
+//! content. Entries record whether their content holds files, which is how multi-file pastes are
+//! told apart from regular ones.
+
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+/// Maximum length of a file name in bytes.
+pub const MAX_NAME_LENGTH: usize = 255;
+
+/// Errors of invalid files.
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("no files given")]
+    Empty,
+    #[error("invalid file name `{0}`")]
+    Name(String),
+    #[error("duplicate file name `{0}`")]
+    Duplicate(String),
+}
+
+/// A named file of a multi-file paste.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct File {
+    /// File name including its extension
+    pub name: String,
+    /// Content
+    pub text: String,
+}
+
+impl File {
+    /// Extension of the file name used to pick the syntax, if any.
+    #[must_use]
+    pub fn extension(&self) -> Option<&str> {
+        self.name
+            .rsplit_once('.')
+            .filter(|(stem, ext)| !stem.is_empty() && !ext.is_empty())
+            .map(|(_, ext)| ext)
+    }
+}
+
+/// Return `true` if `name` can be used as a file name. Names are restricted to ASCII letters,
+/// digits, `.`, `-`, `_` and `+`, so that they can be used as URL path segments as they are.
+#[must_use]
+pub fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= MAX_NAME_LENGTH
+        && name != "."
+        && name != ".."
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'.' | b'-' | b'_' | b'+'))
+}
+
+/// Check that there is at least one file and that all names are valid and unique.
+pub fn validate(files: &[File]) -> Result<(), Error> {
+    if files.is_empty() {
+        return Err(Error::Empty);
+    }
+
+    let mut names = HashSet::new();
+
+    for file in files {
+        if !is_valid_name(&file.name) {
+            return Err(Error::Name(file.name.clone()));
+        }
+
+        if !names.insert(file.name.as_str()) {
+            return Err(Error::Duplicate(file.name.clone()));
+        }
+    }
+
+    Ok(())
+}
+
+/// Validate `files` and serialize them into the content of a multi-file paste.
+pub fn encode(files: &[File]) -> Result<String, Error> {
+    validate(files)?;
+
+    Ok(serde_json::to_string(files).expect("serializing files"))
+}
+
+/// Return the files of the content `text` of a multi-file paste or `None` if it is not valid.
+#[must_use]
+pub fn decode(text: &str) -> Option<Vec<File>> {
+    serde_json::from_str(text)
+        .ok()
+        .filter(|files: &Vec<File>| validate(files).is_ok())
+}
+
+/// Text to put into the search index for the content `text` of a multi-file paste, i.e. the names
+/// and content of all its files.
+#[must_use]
+pub fn searchable(text: &str) -> String {
+    decode(text)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|file| format!("{}\n{}", file.name, file.text))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(name: &str, text: &str) -> File {
+        File {
+            name: name.to_string(),
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn roundtrip() -> Result<(), Error> {
+        let files = vec![
+            file("Cargo.toml", "[package]"),
+            file("main.rs", "fn main() {}"),
+        ];
+
+        let text = encode(&files)?;
+        assert_eq!(decode(&text), Some(files));
+        assert_eq!(
+            searchable(&text),
+            "Cargo.toml\n[package]\nmain.rs\nfn main() {}"
+        );
+
+        assert_eq!(decode("fn main() {}"), None);
+        assert_eq!(decode("[]"), None);
+        assert_eq!(decode(r#"[{"name":"../x","text":""}]"#), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn names() {
+        assert!(encode(&[]).is_err());
+        assert!(encode(&[file("../etc/passwd", "")]).is_err());
+        assert!(encode(&[file("a b.txt", "")]).is_err());
+        assert!(encode(&[file("..", "")]).is_err());
+        assert!(encode(&[file("a.rs", ""), file("a.rs", "")]).is_err());
+        assert!(encode(&[file(".gitignore", "target")]).is_ok());
+    }
+
+    #[test]
+    fn extension() {
+        assert_eq!(file("main.rs", "").extension(), Some("rs"));
+        assert_eq!(file("archive.tar.gz", "").extension(), Some("gz"));
+        assert_eq!(file("Makefile", "").extension(), None);
+        assert_eq!(file(".gitignore", "").extension(), None);
+    }
+}
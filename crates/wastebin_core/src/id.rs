@@ -1,10 +1,14 @@
 use rand::RngExt;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sha2::{Digest, Sha256};
 
 use std::fmt;
 use std::str::FromStr;
 
 use crate::db::write::Entry;
 
+mod words;
+
 const CHAR_TABLE: &[char; 64] = &[
     'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', 'j', 'k', 'l', 'm', 'n', 'o', 'p', 'q', 'r', 's',
     't', 'u', 'v', 'w', 'x', 'y', 'z', 'A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'J', 'K', 'L',
@@ -12,12 +16,62 @@ const CHAR_TABLE: &[char; 64] = &[
     '5', '6', '7', '8', '9', '-', '+',
 ];
 
+/// Symbols of [`Alphabet::Unambiguous`], i.e. lowercase letters and digits without `i`, `l`, `o`,
+/// `0` and `1`.
+const UNAMBIGUOUS_TABLE: &[char; 31] = &[
+    'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'j', 'k', 'm', 'n', 'p', 'q', 'r', 's', 't', 'u', 'v',
+    'w', 'x', 'y', 'z', '2', '3', '4', '5', '6', '7', '8', '9',
+];
+
+/// Maximum length of a [`Slug`] in bytes.
+pub const MAX_SLUG_LENGTH: usize = 64;
+
+/// Minimum length of identifiers generated by [`Generator::Random`].
+pub const MIN_GENERATED_LENGTH: usize = 4;
+
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
     #[error("illegal characters")]
     IllegalCharacters,
     #[error("wrong size")]
     WrongSize,
+    #[error("slugs must be 1 to {MAX_SLUG_LENGTH} letters, digits, `-` or `_`")]
+    InvalidSlug,
+    #[error("slug can be mistaken for a generated identifier")]
+    AmbiguousSlug,
+    #[error(
+        "identifiers must be between {MIN_GENERATED_LENGTH} and {MAX_SLUG_LENGTH} characters long"
+    )]
+    GeneratedLength,
+}
+
+/// Symbols of identifiers generated by [`Generator::Random`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Alphabet {
+    /// Letters, digits, `-` and `+` as used by six and eleven character identifiers. Other lengths
+    /// leave out `+`.
+    #[default]
+    Classic,
+    /// Lowercase letters and digits without look-alikes, easy to read aloud and retype.
+    Unambiguous,
+}
+
+/// Strategy to generate the identifiers of new entries. All generated identifiers parse back into
+/// the same [`Id`], so that they can be used in URLs as they are.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Generator {
+    /// Random characters of an [`Alphabet`], use [`Generator::random`] to check the length.
+    Random { alphabet: Alphabet, length: usize },
+    /// Adjective, noun and number such as `brave-otter-42`.
+    Words,
+}
+
+/// Custom identifier chosen by the creator of a paste. Slugs are stored inline, so that [`Id`]
+/// stays `Copy`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Slug {
+    len: u8,
+    bytes: [u8; MAX_SLUG_LENGTH],
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -26,6 +80,55 @@ pub enum Id {
     Id32(u32),
     /// Eleven-character identifiers.
     Id64(i64),
+    /// Custom identifiers.
+    Slug(Slug),
+}
+
+impl Slug {
+    /// String representation.
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        std::str::from_utf8(&self.bytes[..usize::from(self.len)]).expect("slugs are ASCII")
+    }
+}
+
+impl fmt::Debug for Slug {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Display for Slug {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Slug {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        if value.is_empty()
+            || value.len() > MAX_SLUG_LENGTH
+            || !value
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+        {
+            return Err(Error::InvalidSlug);
+        }
+
+        if parse_generated(value).is_ok() {
+            return Err(Error::AmbiguousSlug);
+        }
+
+        let mut bytes = [0; MAX_SLUG_LENGTH];
+        bytes[..value.len()].copy_from_slice(value.as_bytes());
+
+        Ok(Self {
+            len: u8::try_from(value.len()).expect("slug length fits into u8"),
+            bytes,
+        })
+    }
 }
 
 impl Id {
@@ -36,12 +139,26 @@ impl Id {
         Self::Id64(rand::rng().random::<i64>())
     }
 
-    /// Return i64 representation for database storage purposes.
+    /// Return i64 representation for database storage purposes. Slugs are represented by the first
+    /// eight bytes of their SHA-256 hash.
     #[must_use]
     pub fn to_i64(self) -> i64 {
         match self {
             Self::Id32(n) => n.into(),
             Self::Id64(n) => n,
+            Self::Slug(slug) => {
+                let hash = Sha256::digest(slug.as_str());
+                i64::from_le_bytes(hash[..8].try_into().expect("hash is longer than 8 bytes"))
+            }
+        }
+    }
+
+    /// Return the slug of custom identifiers.
+    #[must_use]
+    pub fn slug(&self) -> Option<&str> {
+        match self {
+            Self::Slug(slug) => Some(slug.as_str()),
+            Self::Id32(_) | Self::Id64(_) => None,
         }
     }
 
@@ -88,58 +205,186 @@ impl fmt::Display for Id {
 
                 write!(f, "{s}")
             }
+            Self::Slug(slug) => write!(f, "{slug}"),
+        }
+    }
+}
+
+/// Parse the string representation of a generated identifier.
+fn parse_generated(value: &str) -> Result<Id, Error> {
+    if value.len() == 6 {
+        let mut n: u32 = 0;
+
+        for (pos, char) in value.chars().enumerate() {
+            #[expect(clippy::cast_possible_truncation)]
+            let bits: u32 = CHAR_TABLE
+                .iter()
+                .enumerate()
+                .find_map(|(bits, c)| (char == *c).then_some(bits as u32))
+                .ok_or(Error::IllegalCharacters)?;
+
+            if pos < 5 {
+                n = (n << 6) | bits;
+            } else {
+                n = (n << 2) | bits;
+            }
+        }
+
+        Ok(Id::Id32(n))
+    } else if value.len() == 11 {
+        let mut n: i64 = 0;
+
+        for (pos, char) in value.chars().enumerate() {
+            #[expect(clippy::cast_possible_wrap)]
+            let bits: i64 = CHAR_TABLE
+                .iter()
+                .enumerate()
+                .find_map(|(bits, c)| (char == *c).then_some(bits as i64))
+                .ok_or(Error::IllegalCharacters)?;
+
+            if pos < 10 {
+                n = (n << 6) | bits;
+            } else {
+                n = (n << 4) | bits;
+            }
         }
+
+        Ok(Id::Id64(n))
+    } else {
+        Err(Error::WrongSize)
     }
 }
 
 impl FromStr for Id {
     type Err = Error;
 
+    /// Parse generated identifiers first and everything else as a [`Slug`].
     fn from_str(value: &str) -> Result<Self, Self::Err> {
-        if value.len() == 6 {
-            let mut n: u32 = 0;
-
-            for (pos, char) in value.chars().enumerate() {
-                #[expect(clippy::cast_possible_truncation)]
-                let bits: u32 = CHAR_TABLE
-                    .iter()
-                    .enumerate()
-                    .find_map(|(bits, c)| (char == *c).then_some(bits as u32))
-                    .ok_or(Error::IllegalCharacters)?;
-
-                if pos < 5 {
-                    n = (n << 6) | bits;
-                } else {
-                    n = (n << 2) | bits;
-                }
-            }
+        parse_generated(value).or_else(|err| value.parse().map(Self::Slug).map_err(|_| err))
+    }
+}
 
-            Ok(Self::Id32(n))
-        } else if value.len() == 11 {
-            let mut n: i64 = 0;
-
-            for (pos, char) in value.chars().enumerate() {
-                #[expect(clippy::cast_possible_wrap)]
-                let bits: i64 = CHAR_TABLE
-                    .iter()
-                    .enumerate()
-                    .find_map(|(bits, c)| (char == *c).then_some(bits as i64))
-                    .ok_or(Error::IllegalCharacters)?;
-
-                if pos < 10 {
-                    n = (n << 6) | bits;
-                } else {
-                    n = (n << 4) | bits;
-                }
+impl From<Slug> for Id {
+    fn from(slug: Slug) -> Self {
+        Self::Slug(slug)
+    }
+}
+
+impl Alphabet {
+    /// Symbols to pick from for identifiers that are not stored as numbers.
+    fn symbols(self) -> &'static [char] {
+        match self {
+            // `+` is not allowed in slugs.
+            Self::Classic => &CHAR_TABLE[..63],
+            Self::Unambiguous => UNAMBIGUOUS_TABLE,
+        }
+    }
+}
+
+impl Default for Generator {
+    fn default() -> Self {
+        Self::Random {
+            alphabet: Alphabet::Classic,
+            length: 11,
+        }
+    }
+}
+
+impl Generator {
+    /// Generator of `length` random characters of `alphabet`.
+    pub fn random(alphabet: Alphabet, length: usize) -> Result<Self, Error> {
+        if !(MIN_GENERATED_LENGTH..=MAX_SLUG_LENGTH).contains(&length) {
+            return Err(Error::GeneratedLength);
+        }
+
+        Ok(Self::Random { alphabet, length })
+    }
+
+    /// Generate a new random [`Id`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the length of [`Generator::Random`] is not valid, see [`Generator::random`].
+    #[must_use]
+    pub fn generate(self) -> Id {
+        let mut rng = rand::rng();
+
+        match self {
+            Self::Random {
+                alphabet: Alphabet::Classic,
+                length: 6,
+            } => Id::Id32(rng.random()),
+            Self::Random {
+                alphabet: Alphabet::Classic,
+                length: 11,
+            } => Id::Id64(rng.random()),
+            Self::Random { alphabet, length } => {
+                let value = (0..length)
+                    .map(|pos| {
+                        // The last character of six and eleven character identifiers only holds
+                        // two and four bits of their numeric representation.
+                        let bits = match (length, pos) {
+                            (6, 5) => 4,
+                            (11, 10) => 16,
+                            _ => CHAR_TABLE.len(),
+                        };
+
+                        let symbols = alphabet
+                            .symbols()
+                            .iter()
+                            .filter(|c| CHAR_TABLE[..bits].contains(c))
+                            .collect::<Vec<_>>();
+
+                        *symbols[rng.random_range(..symbols.len())]
+                    })
+                    .collect::<String>();
+
+                value.parse().expect("valid generated identifier")
             }
+            Self::Words => loop {
+                let adjective = words::ADJECTIVES[rng.random_range(..words::ADJECTIVES.len())];
+                let noun = words::NOUNS[rng.random_range(..words::NOUNS.len())];
+                let number = rng.random_range(..1000_u32);
 
-            Ok(Self::Id64(n))
-        } else {
-            Err(Error::WrongSize)
+                // Skip the rare words that could be mistaken for numeric identifiers.
+                if let Ok(slug) = format!("{adjective}-{noun}-{number}").parse::<Slug>() {
+                    break Id::Slug(slug);
+                }
+            },
         }
     }
 }
 
+/// Serialize as the string representation.
+impl Serialize for Id {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Deserialize from the string representation.
+impl<'de> Deserialize<'de> for Id {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        value.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Serialize as the string representation.
+impl Serialize for Slug {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+/// Deserialize from the string representation.
+impl<'de> Deserialize<'de> for Slug {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        value.parse().map_err(serde::de::Error::custom)
+    }
+}
+
 impl From<u32> for Id {
     fn from(n: u32) -> Self {
         Self::Id32(n)
@@ -197,4 +442,64 @@ mod tests {
         assert!(matches!(Id::from_str("abDE+-1"), Err(Error::WrongSize)));
         assert!(matches!(Id::from_str("abDE+"), Err(Error::WrongSize)));
     }
+
+    #[test]
+    fn slugs() {
+        let id = Id::from_str("deploy-notes").unwrap();
+        assert_eq!(id.slug(), Some("deploy-notes"));
+        assert_eq!(id.to_string(), "deploy-notes");
+        assert_eq!(id.to_i64(), Id::from_str("deploy-notes").unwrap().to_i64());
+        assert_ne!(id.to_i64(), Id::from_str("deploy_notes").unwrap().to_i64());
+
+        // Generated identifiers take precedence and cannot be requested as slugs.
+        assert_eq!(Id::from_str("deploy").unwrap().slug(), None);
+        assert!(matches!(
+            Slug::from_str("deploy"),
+            Err(Error::AmbiguousSlug)
+        ));
+
+        assert!(matches!(Slug::from_str(""), Err(Error::InvalidSlug)));
+        assert!(matches!(Slug::from_str("a.b"), Err(Error::InvalidSlug)));
+        assert!(matches!(
+            Slug::from_str(&"a".repeat(MAX_SLUG_LENGTH + 1)),
+            Err(Error::InvalidSlug)
+        ));
+        assert!(Slug::from_str(&"a".repeat(MAX_SLUG_LENGTH)).is_ok());
+    }
+
+    #[test]
+    fn generated_ids_parse() {
+        let generators = [4, 6, 8, 11, 64]
+            .into_iter()
+            .flat_map(|length| {
+                [Alphabet::Classic, Alphabet::Unambiguous]
+                    .map(|alphabet| Generator::random(alphabet, length).unwrap())
+            })
+            .chain([Generator::default(), Generator::Words]);
+
+        for generator in generators {
+            for _ in 0..100 {
+                let id = generator.generate();
+                let value = id.to_string();
+
+                assert_eq!(Id::from_str(&value).unwrap(), id, "{generator:?}: {value}");
+
+                if let Generator::Random { alphabet, length } = generator {
+                    assert_eq!(value.len(), length);
+                    assert!(value.chars().all(|c| alphabet.symbols().contains(&c)
+                        || (alphabet == Alphabet::Classic && c == '+')));
+                }
+            }
+        }
+
+        let id = Generator::Words.generate().to_string();
+        let parts = id.split('-').collect::<Vec<_>>();
+        assert_eq!(parts.len(), 3);
+        assert!(words::ADJECTIVES.contains(&parts[0]));
+        assert!(words::NOUNS.contains(&parts[1]));
+        assert!(parts[2].parse::<u32>().is_ok());
+
+        assert!(Generator::random(Alphabet::Unambiguous, 3).is_err());
+        assert!(Generator::random(Alphabet::Unambiguous, MAX_SLUG_LENGTH + 1).is_err());
+    }
 }
@@ -0,0 +1,34 @@
+//! Word lists of [`super::Generator::Words`]. Words are short, lowercase, easy to spell and do not
+//! contain digits or dashes.
+
+pub(super) const ADJECTIVES: &[&str] = &[
+    "able", "agile", "amber", "ample", "azure", "bold", "brave", "breezy", "brief", "bright",
+    "brisk", "busy", "calm", "candid", "cheery", "chief", "civil", "clean", "clear", "clever",
+    "cosy", "crisp", "curly", "daring", "dear", "deep", "eager", "early", "easy", "elder", "epic",
+    "even", "exact", "fair", "famous", "fancy", "fast", "fine", "firm", "fluffy", "fond", "frank",
+    "free", "fresh", "frosty", "funny", "fuzzy", "gentle", "giant", "glad", "golden", "grand",
+    "great", "green", "happy", "hardy", "hasty", "hearty", "honest", "humble", "jolly", "joyful",
+    "keen", "kind", "large", "lavish", "lively", "lucky", "magic", "major", "mellow", "merry",
+    "mighty", "mild", "modern", "modest", "neat", "nimble", "noble", "oaken", "plain", "plucky",
+    "polite", "proud", "quick", "quiet", "rapid", "rare", "ready", "regal", "rich", "robust",
+    "rosy", "royal", "rustic", "safe", "sandy", "sharp", "shiny", "silent", "silky", "simple",
+    "sleek", "smart", "smooth", "snowy", "solid", "sound", "spicy", "steady", "stout", "sunny",
+    "super", "sweet", "swift", "tender", "tidy", "tiny", "topaz", "tough", "upbeat", "urban",
+    "vivid", "warm", "wise", "witty", "young", "zesty",
+];
+
+pub(super) const NOUNS: &[&str] = &[
+    "acorn", "anchor", "apple", "arrow", "badger", "banjo", "beacon", "beaver", "bison", "bloom",
+    "breeze", "brook", "bubble", "cactus", "canyon", "castle", "cedar", "cello", "cherry", "cloud",
+    "clover", "comet", "coral", "cotton", "crane", "cricket", "dingo", "dolphin", "dove", "dune",
+    "eagle", "ember", "falcon", "fern", "finch", "fjord", "forest", "fox", "galaxy", "garden",
+    "gecko", "geyser", "glacier", "grove", "harbor", "hawk", "hazel", "heron", "hill", "island",
+    "ivy", "jaguar", "jasmine", "kayak", "kettle", "kiwi", "koala", "lagoon", "lantern", "lemon",
+    "lily", "lizard", "llama", "lotus", "maple", "marble", "meadow", "meteor", "mango", "moose",
+    "moth", "nebula", "nectar", "oasis", "ocean", "olive", "orbit", "orchid", "otter", "owl",
+    "panda", "parrot", "pebble", "pepper", "piano", "pine", "planet", "plum", "pond", "puffin",
+    "quartz", "rabbit", "raven", "reef", "river", "robin", "rocket", "saddle", "salmon", "shell",
+    "sparrow", "spruce", "squid", "star", "stone", "summit", "swan", "thistle", "tiger", "trail",
+    "tulip", "tundra", "turtle", "valley", "violet", "walnut", "walrus", "willow", "wombat",
+    "wren", "yak", "yarrow", "zebra", "zephyr", "acacia", "basil", "birch", "harp",
+];
@@ -1,7 +1,12 @@
 //! Core crate for code shared between the server and the control binaries.
 
+pub mod archive;
+pub mod audit;
+pub mod codec;
 pub mod crypto;
 pub mod db;
+pub mod dictionary;
 pub mod env;
 pub mod expiration;
+pub mod files;
 pub mod id;
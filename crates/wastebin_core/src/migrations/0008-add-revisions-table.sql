@@ -0,0 +1,12 @@
+CREATE TABLE revisions(
+    id INTEGER NOT NULL,
+    rev INTEGER NOT NULL,
+    data BLOB,
+    nonce BLOB,
+    PRIMARY KEY (id, rev)
+);
+
+CREATE TRIGGER delete_revisions AFTER DELETE ON entries
+BEGIN
+    DELETE FROM revisions WHERE id = OLD.id;
+END;
@@ -0,0 +1,6 @@
+CREATE VIRTUAL TABLE entries_fts USING fts5(title, text);
+
+CREATE TRIGGER delete_entries_fts AFTER DELETE ON entries
+BEGIN
+    DELETE FROM entries_fts WHERE rowid = OLD.id;
+END;
@@ -0,0 +1,6 @@
+ALTER TABLE entries ADD COLUMN created_at TEXT;
+ALTER TABLE entries ADD COLUMN accessed_at TEXT;
+ALTER TABLE entries ADD COLUMN size INTEGER;
+ALTER TABLE entries ADD COLUMN compressed_size INTEGER;
+
+UPDATE entries SET compressed_size = length(data);
@@ -0,0 +1,6 @@
+ALTER TABLE entries ADD COLUMN max_views INTEGER;
+ALTER TABLE entries ADD COLUMN views INTEGER NOT NULL DEFAULT 0;
+
+UPDATE entries SET max_views = 1 WHERE burn_after_reading;
+
+ALTER TABLE entries DROP COLUMN burn_after_reading;
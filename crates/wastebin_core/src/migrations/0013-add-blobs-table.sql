@@ -0,0 +1,21 @@
+CREATE TABLE blobs(
+    hash BLOB PRIMARY KEY,
+    data BLOB NOT NULL,
+    refs INTEGER NOT NULL
+);
+
+ALTER TABLE entries ADD COLUMN blob BLOB;
+
+CREATE TRIGGER release_blob_on_delete AFTER DELETE ON entries
+WHEN OLD.blob IS NOT NULL
+BEGIN
+    UPDATE blobs SET refs = refs - 1 WHERE hash = OLD.blob;
+    DELETE FROM blobs WHERE hash = OLD.blob AND refs <= 0;
+END;
+
+CREATE TRIGGER release_blob_on_update AFTER UPDATE OF blob ON entries
+WHEN OLD.blob IS NOT NULL
+BEGIN
+    UPDATE blobs SET refs = refs - 1 WHERE hash = OLD.blob;
+    DELETE FROM blobs WHERE hash = OLD.blob AND refs <= 0;
+END;
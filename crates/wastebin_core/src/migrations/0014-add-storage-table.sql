@@ -0,0 +1,55 @@
+CREATE TABLE storage (
+    id INTEGER PRIMARY KEY CHECK (id = 0),
+    used INTEGER NOT NULL
+);
+
+INSERT INTO storage (id, used) VALUES (0,
+    (SELECT COALESCE(SUM(length(data)), 0) FROM entries)
+    + (SELECT COALESCE(SUM(length(data)), 0) FROM blobs)
+    + (SELECT COALESCE(SUM(length(data)), 0) FROM revisions)
+);
+
+CREATE TRIGGER entries_storage_insert AFTER INSERT ON entries
+BEGIN
+    UPDATE storage SET used = used + COALESCE(length(NEW.data), 0) WHERE id = 0;
+END;
+
+CREATE TRIGGER entries_storage_update AFTER UPDATE OF data ON entries
+BEGIN
+    UPDATE storage SET used = used + COALESCE(length(NEW.data), 0) - COALESCE(length(OLD.data), 0) WHERE id = 0;
+END;
+
+CREATE TRIGGER entries_storage_delete AFTER DELETE ON entries
+BEGIN
+    UPDATE storage SET used = used - COALESCE(length(OLD.data), 0) WHERE id = 0;
+END;
+
+CREATE TRIGGER blobs_storage_insert AFTER INSERT ON blobs
+BEGIN
+    UPDATE storage SET used = used + COALESCE(length(NEW.data), 0) WHERE id = 0;
+END;
+
+CREATE TRIGGER blobs_storage_update AFTER UPDATE OF data ON blobs
+BEGIN
+    UPDATE storage SET used = used + COALESCE(length(NEW.data), 0) - COALESCE(length(OLD.data), 0) WHERE id = 0;
+END;
+
+CREATE TRIGGER blobs_storage_delete AFTER DELETE ON blobs
+BEGIN
+    UPDATE storage SET used = used - COALESCE(length(OLD.data), 0) WHERE id = 0;
+END;
+
+CREATE TRIGGER revisions_storage_insert AFTER INSERT ON revisions
+BEGIN
+    UPDATE storage SET used = used + COALESCE(length(NEW.data), 0) WHERE id = 0;
+END;
+
+CREATE TRIGGER revisions_storage_update AFTER UPDATE OF data ON revisions
+BEGIN
+    UPDATE storage SET used = used + COALESCE(length(NEW.data), 0) - COALESCE(length(OLD.data), 0) WHERE id = 0;
+END;
+
+CREATE TRIGGER revisions_storage_delete AFTER DELETE ON revisions
+BEGIN
+    UPDATE storage SET used = used - COALESCE(length(OLD.data), 0) WHERE id = 0;
+END;
@@ -0,0 +1,9 @@
+CREATE TABLE dictionaries(
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    data BLOB NOT NULL,
+    created_at TEXT NOT NULL
+);
+
+ALTER TABLE entries ADD COLUMN dictionary INTEGER;
+ALTER TABLE blobs ADD COLUMN dictionary INTEGER;
+ALTER TABLE revisions ADD COLUMN dictionary INTEGER;
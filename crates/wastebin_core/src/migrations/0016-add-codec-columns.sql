@@ -0,0 +1,12 @@
+ALTER TABLE entries ADD COLUMN codec TEXT;
+ALTER TABLE entries ADD COLUMN level INTEGER;
+ALTER TABLE blobs ADD COLUMN codec TEXT;
+ALTER TABLE blobs ADD COLUMN level INTEGER;
+ALTER TABLE revisions ADD COLUMN codec TEXT;
+ALTER TABLE revisions ADD COLUMN level INTEGER;
+
+-- Everything so far was compressed with zstd at the default level. Entries whose content lives in
+-- a blob take the encoding from there.
+UPDATE entries SET codec = 'zstd', level = 3 WHERE data IS NOT NULL;
+UPDATE blobs SET codec = 'zstd', level = 3;
+UPDATE revisions SET codec = 'zstd', level = 3;
@@ -0,0 +1,2 @@
+ALTER TABLE entries ADD COLUMN multi_file INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE revisions ADD COLUMN multi_file INTEGER NOT NULL DEFAULT 0;
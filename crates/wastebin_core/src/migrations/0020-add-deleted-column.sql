@@ -0,0 +1,2 @@
+ALTER TABLE entries ADD COLUMN deleted_at TEXT;
+ALTER TABLE entries ADD COLUMN purge_after TEXT;
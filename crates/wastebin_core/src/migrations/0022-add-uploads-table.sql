@@ -0,0 +1,22 @@
+CREATE TABLE uploads(
+    id INTEGER NOT NULL,
+    seq INTEGER NOT NULL,
+    data BLOB NOT NULL,
+    created_at TEXT NOT NULL,
+    PRIMARY KEY (id, seq)
+);
+
+CREATE TRIGGER uploads_storage_insert AFTER INSERT ON uploads
+BEGIN
+    UPDATE storage SET used = used + COALESCE(length(NEW.data), 0) WHERE id = 0;
+END;
+
+CREATE TRIGGER uploads_storage_update AFTER UPDATE OF data ON uploads
+BEGIN
+    UPDATE storage SET used = used + COALESCE(length(NEW.data), 0) - COALESCE(length(OLD.data), 0) WHERE id = 0;
+END;
+
+CREATE TRIGGER uploads_storage_delete AFTER DELETE ON uploads
+BEGIN
+    UPDATE storage SET used = used - COALESCE(length(OLD.data), 0) WHERE id = 0;
+END;
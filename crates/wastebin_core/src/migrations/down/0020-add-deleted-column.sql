@@ -0,0 +1,5 @@
+-- Earlier versions have no trash, so deleted entries are gone for good.
+DELETE FROM entries WHERE deleted_at IS NOT NULL;
+
+ALTER TABLE entries DROP COLUMN purge_after;
+ALTER TABLE entries DROP COLUMN deleted_at;
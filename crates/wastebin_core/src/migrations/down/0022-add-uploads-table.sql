@@ -0,0 +1,3 @@
+-- Dropping the table does not fire its triggers, so uploaded chunks are released first.
+DELETE FROM uploads;
+DROP TABLE uploads;
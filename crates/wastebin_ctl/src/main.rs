@@ -1,7 +1,10 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
 use std::path::PathBuf;
 use std::str::FromStr;
+use std::time::Duration;
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
 #[cfg(feature = "completion")]
 use clap::CommandFactory;
 use clap::{Args, Parser, Subcommand, ValueEnum};
@@ -11,10 +14,14 @@ use tabled::derive::display;
 use tabled::settings::{Alignment, Style};
 use tabled::{Table, Tabled};
 
-use wastebin_core::db::read::ListEntry;
-use wastebin_core::db::{Database, Open};
+use wastebin_core::archive::{Header, Record};
+use wastebin_core::audit::{self, Event};
+use wastebin_core::db::read::{ListEntry, SearchEntry};
+use wastebin_core::db::sqlite::{self, Step};
+use wastebin_core::db::{self, Access, Backend, Config, Conflict, Database, Open, RawEntry};
 use wastebin_core::env::vars;
 use wastebin_core::id::Id;
+use wastebin_core::{codec, dictionary};
 
 #[derive(Parser)]
 #[command(version, about)]
@@ -30,9 +37,8 @@ enum Commands {
     Completion { shell: Shell },
     /// List and filter database entries
     List {
-        /// Path to the database file
-        #[arg(long, env = vars::DATABASE_PATH)]
-        database: PathBuf,
+        #[command(flatten)]
+        database: DatabaseArgs,
 
         /// List entry with the given identifier
         #[arg(short, long)]
@@ -53,21 +59,147 @@ enum Commands {
         #[arg(short, long)]
         sort: Option<SortOrder>,
     },
-    /// Delete specific entries
+    /// Delete specific entries, moving them to the trash if a trash period is set
     Delete {
-        /// Path to the database file
-        #[arg(long, env = vars::DATABASE_PATH)]
-        database: PathBuf,
+        #[command(flatten)]
+        database: DatabaseArgs,
 
         /// Delete entry with the given identifiers
         identifier: Vec<String>,
     },
-    /// Purge all expired entries and show their identifiers
+    /// Restore a deleted entry from the trash
+    Restore {
+        #[command(flatten)]
+        database: DatabaseArgs,
+
+        /// Identifier of the entry to restore
+        identifier: String,
+    },
+    /// Purge expired entries and deleted ones past the trash period and show their identifiers
     Purge {
-        /// Path to the database file
-        #[arg(long, env = vars::DATABASE_PATH)]
-        database: PathBuf,
+        #[command(flatten)]
+        database: DatabaseArgs,
     },
+    /// Show when entries were created, burned, deleted, restored, expired, purged or evicted
+    Audit {
+        #[command(flatten)]
+        database: DatabaseArgs,
+
+        /// Show only events of the entry with the given identifier
+        #[arg(short, long)]
+        id: Option<String>,
+
+        /// Show only events at or after the given UTC time, e.g. `2026-10-01` or
+        /// `2026-10-01 12:00:00`
+        #[arg(long)]
+        since: Option<String>,
+    },
+    /// Search the content and title of unencrypted entries
+    Search {
+        #[command(flatten)]
+        database: DatabaseArgs,
+
+        /// SQLite FTS5 query, e.g. `foo bar` or `"foo bar"`
+        query: String,
+    },
+    /// Write a consistent copy of the database, even while the server is running
+    Backup {
+        #[command(flatten)]
+        database: DatabaseArgs,
+
+        /// Path of the backup file, must not exist yet
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+    /// Export all entries to a portable JSON Lines archive
+    Export {
+        #[command(flatten)]
+        database: DatabaseArgs,
+
+        /// Path of the archive, standard output if not given
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Import entries from an archive written by `export`, keeping their identifiers
+    Import {
+        #[command(flatten)]
+        database: DatabaseArgs,
+
+        /// What to do if an identifier is already taken
+        #[arg(long, default_value = "fail")]
+        on_conflict: OnConflict,
+
+        /// Path of the archive
+        input: PathBuf,
+    },
+    /// Train a compression dictionary on unencrypted entries and use it for new entries
+    TrainDictionary {
+        #[command(flatten)]
+        database: DatabaseArgs,
+
+        /// Maximum size of the dictionary in bytes
+        #[arg(long, default_value_t = dictionary::DEFAULT_MAX_SIZE)]
+        max_size: usize,
+    },
+    /// Rewrite all unencrypted content with the given compression settings
+    Recompress {
+        #[command(flatten)]
+        database: DatabaseArgs,
+
+        /// zstd compression level
+        #[arg(long, env = vars::COMPRESSION_LEVEL, default_value_t = codec::DEFAULT_LEVEL)]
+        level: i32,
+
+        /// Do not compress with the most recently trained dictionary
+        #[arg(long)]
+        no_dictionary: bool,
+    },
+    /// Inspect or migrate the schema of an SQLite database
+    Db {
+        #[command(subcommand)]
+        command: DbCommands,
+    },
+}
+
+#[derive(Subcommand)]
+enum DbCommands {
+    /// Show the schema version of the database and the migrations known to this build
+    Status {
+        #[command(flatten)]
+        database: SchemaArgs,
+    },
+    /// Migrate the schema to the latest or the given version, creating the database if needed
+    Migrate {
+        #[command(flatten)]
+        database: SchemaArgs,
+
+        /// Schema version to migrate to, reverting newer migrations if necessary
+        #[arg(long)]
+        to: Option<usize>,
+
+        /// Only show the migrations that would be applied or reverted
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Args)]
+struct SchemaArgs {
+    /// Path to the SQLite database file
+    #[arg(long, env = vars::DATABASE_PATH)]
+    database: PathBuf,
+}
+
+#[derive(Args)]
+struct DatabaseArgs {
+    /// Path to the SQLite database file
+    #[arg(long, env = vars::DATABASE_PATH)]
+    database: PathBuf,
+
+    /// Seconds entries deleted by this command stay restorable, 0 to delete them right away.
+    /// Entries deleted earlier keep the deadline they were deleted with
+    #[arg(long, env = vars::TRASH_PERIOD, default_value_t = 0)]
+    trash_period: u64,
 }
 
 #[derive(Args)]
@@ -88,6 +220,26 @@ enum SortOrder {
     TitleDesc,
     ExpirationAsc,
     ExpirationDesc,
+    CreatedAsc,
+    CreatedDesc,
+    SizeAsc,
+    SizeDesc,
+    CompressedSizeAsc,
+    CompressedSizeDesc,
+    AccessedAsc,
+    AccessedDesc,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum OnConflict {
+    /// Stop importing
+    Fail,
+    /// Keep the existing entry
+    Skip,
+    /// Replace the existing entry
+    Replace,
+    /// Import the entry under a new identifier
+    NewId,
 }
 
 #[derive(Clone, Copy, ValueEnum)]
@@ -110,6 +262,79 @@ struct Entry {
     #[tabled(display("display::option", ""))]
     expiration: Option<String>,
     expired: Expired,
+    #[tabled(display("display::option", ""))]
+    created: Option<String>,
+    #[tabled(display("display::option", ""))]
+    size: Option<u64>,
+    #[tabled(rename = "compressed", display("display::option", ""))]
+    compressed_size: Option<u64>,
+    #[tabled(display("display::option", ""))]
+    accessed: Option<String>,
+    #[tabled(display("display::option", ""))]
+    deleted: Option<String>,
+}
+
+/// Table cell showing whether something is the case.
+struct Check(bool);
+
+#[derive(Tabled)]
+struct MigrationEntry {
+    version: usize,
+    name: &'static str,
+    applied: Check,
+    reversible: Check,
+}
+
+#[derive(Tabled)]
+struct AuditEntry {
+    time: String,
+    id: Id,
+    event: Event,
+    #[tabled(display("display::option", ""))]
+    uid: Option<i64>,
+    actor: String,
+}
+
+#[derive(Tabled)]
+struct SearchResult {
+    id: Id,
+    #[tabled(display("display::option", ""))]
+    title: Option<String>,
+    snippet: String,
+}
+
+impl From<OnConflict> for Conflict {
+    fn from(on_conflict: OnConflict) -> Self {
+        match on_conflict {
+            OnConflict::Fail => Conflict::Fail,
+            OnConflict::Skip => Conflict::Skip,
+            OnConflict::Replace => Conflict::Replace,
+            OnConflict::NewId => Conflict::NewId,
+        }
+    }
+}
+
+impl DatabaseArgs {
+    /// Open the database with `access` and spawn its handler. Audit log events are recorded as
+    /// caused by `actor`.
+    fn open(self, actor: &'static str, access: Access) -> Result<Database> {
+        let config = Config {
+            trash_period: (self.trash_period > 0).then(|| Duration::from_secs(self.trash_period)),
+            actor,
+            access,
+            ..Default::default()
+        };
+
+        let (db, db_handler) =
+            match Database::open(Backend::Sqlite, Open::Path(self.database), config) {
+                Err(err @ db::Error::Schema { .. }) => {
+                    bail!("{err}, run `wastebin-ctl db migrate` first")
+                }
+                result => result?,
+            };
+        tokio::task::spawn(db_handler);
+        Ok(db)
+    }
 }
 
 impl From<bool> for Encrypted {
@@ -133,6 +358,12 @@ impl std::fmt::Display for Encrypted {
     }
 }
 
+impl std::fmt::Display for Check {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.0 { write!(f, "☑️") } else { Ok(()) }
+    }
+}
+
 impl std::fmt::Display for Expired {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -150,6 +381,45 @@ impl From<ListEntry> for Entry {
             encrypted: entry.is_encrypted.into(),
             expiration: entry.expiration,
             expired: entry.is_expired.into(),
+            created: entry.created,
+            size: entry.size,
+            compressed_size: entry.compressed_size,
+            accessed: entry.accessed,
+            deleted: entry.deleted,
+        }
+    }
+}
+
+impl From<audit::Record> for AuditEntry {
+    fn from(record: audit::Record) -> Self {
+        Self {
+            time: record.created,
+            id: record.id,
+            event: record.event,
+            uid: record.uid,
+            actor: record.actor,
+        }
+    }
+}
+
+/// Return `true` if `time` is a date like `YYYY-MM-DD` optionally followed by ` HH:MM:SS`, the
+/// format timestamps are stored and compared in.
+fn is_datetime(time: &str) -> bool {
+    const PATTERN: &[u8] = b"dddd-dd-dd dd:dd:dd";
+
+    (time.len() == 10 || time.len() == PATTERN.len())
+        && time.bytes().zip(PATTERN).all(|(c, p)| match p {
+            b'd' => c.is_ascii_digit(),
+            _ => c == *p,
+        })
+}
+
+impl From<SearchEntry> for SearchResult {
+    fn from(entry: SearchEntry) -> Self {
+        Self {
+            id: entry.id,
+            title: entry.title,
+            snippet: entry.snippet.replace('\n', " "),
         }
     }
 }
@@ -185,8 +455,7 @@ async fn main() -> Result<()> {
                 .transpose()
                 .with_context(|| "Invalid identifier")?;
 
-            let (db, db_handler) = Database::new(Open::Path(database))?;
-            tokio::task::spawn(db_handler);
+            let db = database.open("wastebin-ctl list", Access::ReadOnly)?;
 
             let mut db_items: Vec<_> = db
                 .list()
@@ -230,6 +499,28 @@ async fn main() -> Result<()> {
                     SortOrder::ExpirationDesc => {
                         db_items.sort_unstable_by(|a, b| a.expiration.cmp(&b.expiration).reverse())
                     }
+                    SortOrder::CreatedAsc => {
+                        db_items.sort_unstable_by(|a, b| a.created.cmp(&b.created))
+                    }
+                    SortOrder::CreatedDesc => {
+                        db_items.sort_unstable_by(|a, b| a.created.cmp(&b.created).reverse())
+                    }
+                    SortOrder::SizeAsc => db_items.sort_unstable_by_key(|entry| entry.size),
+                    SortOrder::SizeDesc => {
+                        db_items.sort_unstable_by(|a, b| a.size.cmp(&b.size).reverse())
+                    }
+                    SortOrder::CompressedSizeAsc => {
+                        db_items.sort_unstable_by_key(|entry| entry.compressed_size);
+                    }
+                    SortOrder::CompressedSizeDesc => db_items.sort_unstable_by(|a, b| {
+                        a.compressed_size.cmp(&b.compressed_size).reverse()
+                    }),
+                    SortOrder::AccessedAsc => {
+                        db_items.sort_unstable_by(|a, b| a.accessed.cmp(&b.accessed))
+                    }
+                    SortOrder::AccessedDesc => {
+                        db_items.sort_unstable_by(|a, b| a.accessed.cmp(&b.accessed).reverse())
+                    }
                 }
             }
 
@@ -248,8 +539,7 @@ async fn main() -> Result<()> {
                 .collect::<Result<Vec<_>, _>>()
                 .with_context(|| "Invalid identifier")?;
 
-            let (db, db_handler) = Database::new(Open::Path(database))?;
-            tokio::task::spawn(db_handler);
+            let db = database.open("wastebin-ctl delete", Access::ReadWrite)?;
 
             let affected = db.delete_many(ids).await?;
             println!(
@@ -257,9 +547,21 @@ async fn main() -> Result<()> {
                 if affected > 1 { "entries" } else { "entry" }
             );
         }
+        Commands::Restore {
+            database,
+            identifier,
+        } => {
+            let id = Id::from_str(&identifier).with_context(|| "Invalid identifier")?;
+
+            let db = database.open("wastebin-ctl restore", Access::ReadWrite)?;
+
+            db.restore(id)
+                .await
+                .with_context(|| format!("Could not restore {id}, is it in the trash?"))?;
+            println!("Restored {id}");
+        }
         Commands::Purge { database } => {
-            let (db, db_handler) = Database::new(Open::Path(database))?;
-            tokio::task::spawn(db_handler);
+            let db = database.open("wastebin-ctl purge", Access::ReadWrite)?;
 
             let ids = db.purge().await?;
 
@@ -267,7 +569,7 @@ async fn main() -> Result<()> {
                 println!("no entries purged");
             } else {
                 println!(
-                    "purged {} expired {}",
+                    "purged {} expired or deleted {}",
                     ids.len(),
                     if ids.len() > 1 { "entries" } else { "entry" }
                 );
@@ -277,6 +579,231 @@ async fn main() -> Result<()> {
                 }
             }
         }
+        Commands::Audit {
+            database,
+            id,
+            since,
+        } => {
+            let id = id
+                .map(|id| Id::from_str(&id))
+                .transpose()
+                .with_context(|| "Invalid identifier")?;
+
+            if let Some(since) = &since
+                && !is_datetime(since)
+            {
+                bail!("Invalid time `{since}`, expected `YYYY-MM-DD` or `YYYY-MM-DD HH:MM:SS`");
+            }
+
+            let db = database.open("wastebin-ctl audit", Access::ReadOnly)?;
+
+            let records: Vec<_> = db
+                .audit(id, since)
+                .await?
+                .into_iter()
+                .map(AuditEntry::from)
+                .collect();
+
+            let mut table = Table::new(&records);
+            table.with(Style::psql()).with(Alignment::left());
+
+            println!("{table}");
+        }
+        Commands::Search { database, query } => {
+            let db = database.open("wastebin-ctl search", Access::ReadOnly)?;
+
+            let results: Vec<_> = db
+                .search(&query)
+                .await?
+                .into_iter()
+                .map(SearchResult::from)
+                .collect();
+
+            let mut table = Table::new(&results);
+            table.with(Style::psql()).with(Alignment::left());
+
+            println!("{table}");
+        }
+        Commands::Backup { database, output } => {
+            if output.exists() {
+                bail!("{} already exists", output.display());
+            }
+
+            let db = database.open("wastebin-ctl backup", Access::ReadOnly)?;
+            db.backup(output.clone()).await?;
+
+            println!("backed up database to {}", output.display());
+        }
+        Commands::Export { database, output } => {
+            let db = database.open("wastebin-ctl export", Access::ReadOnly)?;
+
+            let mut writer: Box<dyn Write> = match &output {
+                Some(path) => Box::new(BufWriter::new(
+                    File::create_new(path)
+                        .with_context(|| format!("Failed to create {}", path.display()))?,
+                )),
+                None => Box::new(BufWriter::new(std::io::stdout().lock())),
+            };
+
+            serde_json::to_writer(&mut writer, &Header::default())?;
+            writeln!(writer)?;
+
+            let entries = db.export().await?;
+            let count = entries.len();
+
+            for entry in entries {
+                let id = entry.id;
+                let record = Record::try_from(entry)
+                    .with_context(|| format!("Failed to export entry {id}"))?;
+                serde_json::to_writer(&mut writer, &record)?;
+                writeln!(writer)?;
+            }
+
+            writer.flush()?;
+
+            if let Some(path) = output {
+                println!("exported {count} entries to {}", path.display());
+            }
+        }
+        Commands::Import {
+            database,
+            on_conflict,
+            input,
+        } => {
+            let file = File::open(&input)
+                .with_context(|| format!("Failed to open {}", input.display()))?;
+            let mut lines = BufReader::new(file).lines();
+
+            let header: Header = match lines.next() {
+                Some(line) => serde_json::from_str(&line?).with_context(|| "Invalid header")?,
+                None => bail!("{} is empty", input.display()),
+            };
+
+            header.validate()?;
+
+            let db = database.open("wastebin-ctl import", Access::ReadWrite)?;
+            let mut owners = db.owners().await?;
+            let (mut imported, mut skipped) = (0, 0);
+
+            for (number, line) in (2..).zip(lines) {
+                let record: Record = serde_json::from_str(&line?)
+                    .with_context(|| format!("Invalid record in line {number}"))?;
+                let id = record.id;
+
+                let entry = RawEntry::try_from(record)
+                    .with_context(|| format!("Invalid record in line {number}"))?;
+
+                match db.import(entry, on_conflict.into(), &mut owners).await {
+                    Ok(Some(new_id)) if new_id != id => {
+                        println!("imported {id} as {new_id}");
+                        imported += 1;
+                    }
+                    Ok(Some(_)) => imported += 1,
+                    Ok(None) => skipped += 1,
+                    Err(db::Error::Conflict) => bail!(
+                        "{id} already exists, choose how to resolve conflicts with --on-conflict"
+                    ),
+                    Err(err) => return Err(err.into()),
+                }
+            }
+
+            println!("imported {imported} entries, skipped {skipped}");
+        }
+        Commands::TrainDictionary { database, max_size } => {
+            let db = database.open("wastebin-ctl train-dictionary", Access::ReadWrite)?;
+
+            let (id, samples) = db
+                .train_dictionary(max_size)
+                .await
+                .with_context(|| "Failed to train dictionary, there may be too few entries")?;
+
+            println!("trained dictionary {id} on {samples} entries");
+        }
+        Commands::Recompress {
+            database,
+            level,
+            no_dictionary,
+        } => {
+            let levels = codec::levels();
+
+            if !levels.contains(&level) {
+                bail!(
+                    "level must be between {} and {}",
+                    levels.start(),
+                    levels.end()
+                );
+            }
+
+            let db = database.open("wastebin-ctl recompress", Access::ReadWrite)?;
+
+            let db::Recompressed { rewritten, skipped } = db
+                .recompress(level, !no_dictionary)
+                .await
+                .with_context(|| "Failed to recompress entries")?;
+
+            println!("recompressed {rewritten} entries, skipped {skipped} encrypted");
+        }
+        Commands::Db {
+            command: DbCommands::Status { database },
+        } => {
+            let current = sqlite::schema_version(&database.database)?;
+            let migrations = sqlite::migrations();
+
+            let rows: Vec<_> = migrations
+                .iter()
+                .map(|migration| MigrationEntry {
+                    version: migration.version,
+                    name: migration.name,
+                    applied: Check(migration.version <= current),
+                    reversible: Check(migration.reversible),
+                })
+                .collect();
+
+            let mut table = Table::new(&rows);
+            table.with(Style::psql()).with(Alignment::left());
+
+            println!("{table}");
+            println!(
+                "schema version {current}, latest version {}",
+                migrations.len()
+            );
+
+            if current > migrations.len() {
+                println!("the database was migrated by a newer version of wastebin");
+            }
+        }
+        Commands::Db {
+            command:
+                DbCommands::Migrate {
+                    database,
+                    to,
+                    dry_run,
+                },
+        } => {
+            let target = to.unwrap_or_else(|| sqlite::migrations().len());
+
+            let steps = if dry_run {
+                let current = sqlite::schema_version(&database.database)?;
+                sqlite::plan(current, target)?
+            } else {
+                sqlite::migrate(&database.database, target)?
+            };
+
+            if steps.is_empty() {
+                println!("schema is already at version {target}");
+            }
+
+            for step in steps {
+                let (action, migration) = match (step, dry_run) {
+                    (Step::Apply(migration), false) => ("applied", migration),
+                    (Step::Apply(migration), true) => ("would apply", migration),
+                    (Step::Revert(migration), false) => ("reverted", migration),
+                    (Step::Revert(migration), true) => ("would revert", migration),
+                };
+
+                println!("{action} {} {}", migration.version, migration.name);
+            }
+        }
     }
 
     Ok(())
@@ -25,6 +25,8 @@ pub(crate) enum Mode {
     Source,
     /// Markdown rendered to HTML.
     Rendered,
+    /// Syntax-highlighted file of a multi-file paste at the given position.
+    File(usize),
 }
 
 /// Internal cache slot partitioning cached HTML by paste identity and render mode.
@@ -73,6 +75,14 @@ impl Cache {
             .cache_get(&Slot::new(key, mode))
             .cloned()
     }
+
+    /// Remove all cached representations of `id` regardless of extension and mode.
+    pub fn remove(&self, id: Id) {
+        self.inner
+            .lock()
+            .expect("getting lock")
+            .retain(|slot, _| slot.id != id);
+    }
 }
 
 impl Key {
@@ -80,6 +90,13 @@ impl Key {
     pub fn id(&self) -> String {
         self.id.to_string()
     }
+
+    /// Fall back to the stored `extension` unless the URL names one, so that links with any
+    /// extension keep resolving after the owner changed it.
+    pub fn or_extension(mut self, extension: Option<String>) -> Self {
+        self.ext = self.ext.or(extension);
+        self
+    }
 }
 
 impl Display for Key {
@@ -121,7 +138,30 @@ mod tests {
         assert_eq!(key.id, 1_243_750_162_u32.into());
         assert_eq!(key.ext.unwrap(), "rs");
 
-        assert!(Key::from_str("foo").is_err());
-        assert!(Key::from_str("bar.rs").is_err());
+        let key = Key::from_str("deploy-notes.rs").unwrap();
+        assert_eq!(key.id(), "deploy-notes");
+        assert_eq!(key.id.slug(), Some("deploy-notes"));
+        assert_eq!(key.ext.unwrap(), "rs");
+
+        assert!(Key::from_str("foo bar").is_err());
+        assert!(Key::from_str("b+r.rs").is_err());
+    }
+
+    #[test]
+    fn remove_all_slots_of_id() {
+        let cache = Cache::new(NonZeroUsize::new(8).unwrap()).unwrap();
+        let plain = Key::from_str("bJZCna").unwrap();
+        let rust = Key::from_str("bJZCna.rs").unwrap();
+        let other = Key::from_str("sIiFec").unwrap();
+
+        cache.put(&plain, Mode::Source, Html::new(String::from("a")));
+        cache.put(&rust, Mode::Rendered, Html::new(String::from("b")));
+        cache.put(&other, Mode::Source, Html::new(String::from("c")));
+
+        cache.remove(plain.id);
+
+        assert!(cache.get(&plain, Mode::Source).is_none());
+        assert!(cache.get(&rust, Mode::Rendered).is_none());
+        assert!(cache.get(&other, Mode::Source).is_some());
     }
 }
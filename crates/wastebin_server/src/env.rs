@@ -1,39 +1,85 @@
 use std::env::VarError;
 use std::fmt::Display;
 use std::net::{Ipv4Addr, SocketAddr};
-use std::num::{NonZeroUsize, ParseIntError};
+use std::num::{NonZeroU64, NonZeroUsize, ParseIntError};
 use std::path::PathBuf;
 use std::time::Duration;
 
 use axum_extra::extract::cookie::Key;
 
+use crate::page::Slugs;
+
 use wastebin_core::env::vars::{
-    self, ADDRESS_PORT, BASE_URL, CACHE_SIZE, DATABASE_PATH, HTTP_TIMEOUT, MAX_BODY_SIZE,
-    PASTE_EXPIRATIONS, SIGNING_KEY,
+    self, ADDRESS_PORT, BACKUP_INTERVAL, BACKUP_PATH, BACKUP_RETENTION, BASE_URL, CACHE_SIZE,
+    COMPRESSION_LEVEL, DATABASE_PATH, HTTP_TIMEOUT, ID_GENERATOR, ID_LENGTH, MAX_BODY_SIZE,
+    MAX_STORAGE, MAX_STREAM_SIZE, PASTE_EXPIRATIONS, PURGE_INTERVAL, PURGE_VACUUM,
+    READER_POOL_SIZE, SIGNING_KEY, SLUG_CHARS, SLUG_MAX_LENGTH, SLUG_MIN_LENGTH, SLUGS, STORAGE,
+    TRASH_PERIOD,
 };
-use wastebin_core::{db, expiration};
+use wastebin_core::id::{Alphabet, Generator, MAX_SLUG_LENGTH, MIN_GENERATED_LENGTH};
+use wastebin_core::{codec, db, expiration};
 use wastebin_highlight::{Theme, theme::ParseThemeNameError};
 
 pub const DEFAULT_HTTP_TIMEOUT: Duration = Duration::from_secs(5);
 
+pub const DEFAULT_PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+pub const DEFAULT_BACKUP_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
 #[derive(thiserror::Error, Debug)]
 pub(crate) enum Error {
     #[error("failed to construct cache")]
     CacheConstruction(#[from] cached::BuildError),
     #[error("failed to parse {CACHE_SIZE}, expected number of elements: {0}")]
     CacheSize(ParseIntError),
+    #[error("failed to parse {READER_POOL_SIZE}, expected number of connections: {0}")]
+    ReaderPoolSize(ParseIntError),
     #[error("failed to parse {DATABASE_PATH}, contains non-Unicode data")]
     DatabasePath,
+    #[error("failed to parse {STORAGE}, expected `sqlite` or `memory`")]
+    Storage,
     #[error("failed to parse {MAX_BODY_SIZE}, expected number of bytes: {0}")]
     MaxBodySize(ParseIntError),
+    #[error("failed to parse {MAX_STREAM_SIZE}, expected number of bytes: {0}")]
+    MaxStreamSize(ParseIntError),
+    #[error("failed to parse {MAX_STORAGE}, expected non-zero number of bytes: {0}")]
+    MaxStorage(ParseIntError),
+    #[error("failed to parse {COMPRESSION_LEVEL}, expected a level between {} and {}", codec::levels().start(), codec::levels().end())]
+    CompressionLevel,
+    #[error("failed to parse {ID_GENERATOR}, expected `classic`, `unambiguous` or `words`")]
+    IdGenerator,
+    #[error(
+        "failed to parse {ID_LENGTH}, expected a length between {MIN_GENERATED_LENGTH} and {MAX_SLUG_LENGTH}"
+    )]
+    IdLength,
     #[error("failed to parse {ADDRESS_PORT}, expected `host:port`")]
     AddressPort,
+    #[error("failed to parse {BACKUP_PATH}, contains non-Unicode data")]
+    BackupPath,
+    #[error("failed to parse {BACKUP_INTERVAL}, expected non-zero number of seconds: {0}")]
+    BackupInterval(ParseIntError),
+    #[error("failed to parse {BACKUP_RETENTION}, expected non-zero number of backups: {0}")]
+    BackupRetention(ParseIntError),
     #[error("failed to parse {BASE_URL}: {0}")]
     BaseUrl(String),
     #[error("failed to generate key from {SIGNING_KEY}: {0}")]
     SigningKey(String),
     #[error("failed to parse {HTTP_TIMEOUT}: {0}")]
     HttpTimeout(ParseIntError),
+    #[error("failed to parse {PURGE_INTERVAL}: {0}")]
+    PurgeInterval(ParseIntError),
+    #[error("failed to parse {PURGE_VACUUM}, expected `true` or `false`")]
+    PurgeVacuum,
+    #[error("failed to parse {TRASH_PERIOD}, expected number of seconds: {0}")]
+    TrashPeriod(ParseIntError),
+    #[error("failed to parse {SLUGS}, expected `true` or `false`")]
+    Slugs,
+    #[error("failed to parse {SLUG_CHARS}, expected ASCII letters, digits, `-` or `_`")]
+    SlugChars,
+    #[error(
+        "failed to parse {SLUG_MIN_LENGTH} and {SLUG_MAX_LENGTH}, expected lengths between 1 and {MAX_SLUG_LENGTH}"
+    )]
+    SlugLength,
     #[error("failed to parse {PASTE_EXPIRATIONS}: {0}")]
     ParsePasteExpiration(#[from] expiration::Error),
     #[error("failed to parse theme name")]
@@ -77,6 +123,12 @@ pub fn cache_size() -> Result<NonZeroUsize, Error> {
         .map_err(Error::CacheSize)
 }
 
+pub fn reader_pool_size() -> Result<usize, Error> {
+    std::env::var(vars::READER_POOL_SIZE)
+        .map_or_else(|_| Ok(4), |s| s.parse::<usize>())
+        .map_err(Error::ReaderPoolSize)
+}
+
 pub fn database_method() -> Result<db::Open, Error> {
     match std::env::var(vars::DATABASE_PATH) {
         Ok(path) => Ok(db::Open::Path(PathBuf::from(path))),
@@ -85,6 +137,13 @@ pub fn database_method() -> Result<db::Open, Error> {
     }
 }
 
+pub fn storage_backend() -> Result<db::Backend, Error> {
+    std::env::var(vars::STORAGE).map_or_else(
+        |_| Ok(db::Backend::default()),
+        |s| s.parse().map_err(|_| Error::Storage),
+    )
+}
+
 pub fn signing_key() -> Result<Key, Error> {
     std::env::var(vars::SIGNING_KEY).map_or_else(
         |_| Ok(Key::generate()),
@@ -116,6 +175,50 @@ pub fn max_body_size() -> Result<usize, Error> {
         .map_err(Error::MaxBodySize)
 }
 
+/// Read the maximum size of unencrypted `text/*` bodies, which are streamed into the database
+/// instead of being held in memory. Defaults to `max_body_size`.
+pub fn max_stream_size(max_body_size: usize) -> Result<usize, Error> {
+    std::env::var(vars::MAX_STREAM_SIZE)
+        .map_or_else(|_| Ok(max_body_size), |s| s.parse::<usize>())
+        .map_err(Error::MaxStreamSize)
+}
+
+pub fn max_storage() -> Result<Option<NonZeroU64>, Error> {
+    std::env::var(vars::MAX_STORAGE)
+        .ok()
+        .map(|s| s.parse::<NonZeroU64>())
+        .transpose()
+        .map_err(Error::MaxStorage)
+}
+
+/// Read the generator of paste identifiers.
+pub fn id_generator() -> Result<Generator, Error> {
+    let length = std::env::var(vars::ID_LENGTH)
+        .map_or_else(|_| Ok(11), |s| s.parse::<usize>())
+        .map_err(|_| Error::IdLength)?;
+
+    let alphabet = match std::env::var(vars::ID_GENERATOR).as_deref() {
+        Err(VarError::NotPresent) | Ok("classic") => Alphabet::Classic,
+        Ok("unambiguous") => Alphabet::Unambiguous,
+        Ok("words") => return Ok(Generator::Words),
+        _ => return Err(Error::IdGenerator),
+    };
+
+    Generator::random(alphabet, length).map_err(|_| Error::IdLength)
+}
+
+pub fn compression_level() -> Result<Option<i32>, Error> {
+    std::env::var(vars::COMPRESSION_LEVEL)
+        .ok()
+        .map(|s| {
+            s.parse::<i32>()
+                .ok()
+                .filter(|level| codec::levels().contains(level))
+                .ok_or(Error::CompressionLevel)
+        })
+        .transpose()
+}
+
 /// Read base URL either from the environment variable or fallback to the hostname.
 pub fn base_url() -> Result<url::Url, Error> {
     if let Some(base_url) = std::env::var(vars::BASE_URL).map_or_else(
@@ -160,3 +263,120 @@ pub fn expiration_set() -> Result<expiration::ExpirationSet, Error> {
 
     Ok(set)
 }
+
+/// Interval between purges of expired pastes, `None` if disabled by setting it to 0.
+pub fn purge_interval() -> Result<Option<Duration>, Error> {
+    let interval = std::env::var(vars::PURGE_INTERVAL)
+        .map_or_else(
+            |_| Ok(DEFAULT_PURGE_INTERVAL),
+            |s| s.parse::<u64>().map(Duration::from_secs),
+        )
+        .map_err(Error::PurgeInterval)?;
+
+    Ok((!interval.is_zero()).then_some(interval))
+}
+
+pub fn purge_vacuum() -> Result<bool, Error> {
+    std::env::var(vars::PURGE_VACUUM)
+        .map_or_else(|_| Ok(false), |s| s.parse::<bool>())
+        .map_err(|_| Error::PurgeVacuum)
+}
+
+/// How long deleted pastes stay restorable, `None` if they are deleted right away.
+pub fn trash_period() -> Result<Option<Duration>, Error> {
+    let period = std::env::var(vars::TRASH_PERIOD)
+        .map_or_else(|_| Ok(0), |s| s.parse::<u64>())
+        .map_err(Error::TrashPeriod)?;
+
+    Ok((period > 0).then(|| Duration::from_secs(period)))
+}
+
+/// Read the rules for custom identifiers, `None` if they are disabled.
+pub fn slugs() -> Result<Option<Slugs>, Error> {
+    let enabled = std::env::var(vars::SLUGS)
+        .map_or_else(|_| Ok(true), |s| s.parse::<bool>())
+        .map_err(|_| Error::Slugs)?;
+
+    if !enabled {
+        return Ok(None);
+    }
+
+    let defaults = Slugs::default();
+
+    let length = |var, default: usize| {
+        std::env::var(var).map_or_else(|_| Ok(default), |s| s.parse::<usize>())
+    };
+
+    let min_length =
+        length(vars::SLUG_MIN_LENGTH, defaults.min_length).map_err(|_| Error::SlugLength)?;
+    let max_length =
+        length(vars::SLUG_MAX_LENGTH, defaults.max_length).map_err(|_| Error::SlugLength)?;
+
+    if min_length == 0 || min_length > max_length || max_length > MAX_SLUG_LENGTH {
+        return Err(Error::SlugLength);
+    }
+
+    let chars = match std::env::var(vars::SLUG_CHARS) {
+        Ok(chars) => {
+            if chars.is_empty()
+                || !chars
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+            {
+                return Err(Error::SlugChars);
+            }
+
+            Some(chars)
+        }
+        Err(VarError::NotUnicode(_)) => return Err(Error::SlugChars),
+        Err(VarError::NotPresent) => None,
+    };
+
+    Ok(Some(Slugs {
+        min_length,
+        max_length,
+        chars,
+    }))
+}
+
+/// Scheduled backup settings.
+pub(crate) struct Backup {
+    /// Directory the backups are written to
+    pub path: PathBuf,
+    /// Time between backups
+    pub interval: Duration,
+    /// Number of backups to keep
+    pub retention: NonZeroUsize,
+}
+
+/// Read scheduled backup settings, `None` if no backup directory is set.
+pub fn backup() -> Result<Option<Backup>, Error> {
+    let path = match std::env::var(vars::BACKUP_PATH) {
+        Ok(path) => PathBuf::from(path),
+        Err(VarError::NotUnicode(_)) => return Err(Error::BackupPath),
+        Err(VarError::NotPresent) => return Ok(None),
+    };
+
+    let interval = std::env::var(vars::BACKUP_INTERVAL)
+        .map_or_else(
+            |_| Ok(DEFAULT_BACKUP_INTERVAL),
+            |s| {
+                s.parse::<NonZeroU64>()
+                    .map(|secs| Duration::from_secs(secs.get()))
+            },
+        )
+        .map_err(Error::BackupInterval)?;
+
+    let retention = std::env::var(vars::BACKUP_RETENTION)
+        .map_or_else(
+            |_| Ok(NonZeroUsize::new(7).expect("7 is non-zero")),
+            |s| s.parse::<NonZeroUsize>(),
+        )
+        .map_err(Error::BackupRetention)?;
+
+    Ok(Some(Backup {
+        path,
+        interval,
+        retention,
+    }))
+}
@@ -2,9 +2,11 @@ use std::num::TryFromIntError;
 
 use axum::Json;
 use axum::http::StatusCode;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-use wastebin_core::{crypto, db, id};
+use wastebin_core::expiration::Expiration;
+use wastebin_core::id::Id;
+use wastebin_core::{crypto, db, files, id};
 
 #[derive(thiserror::Error, Debug)]
 pub(crate) enum Error {
@@ -26,11 +28,58 @@ pub(crate) enum Error {
     Id(#[from] id::Error),
     #[error("payload too large")]
     MalformedForm,
+    #[error("invalid files: {0}")]
+    Files(#[from] files::Error),
+    #[error("either text or files can be given")]
+    TextAndFiles,
+    #[error("could not create zip archive: {0}")]
+    Zip(#[from] zip::result::ZipError),
+    #[error("custom identifiers are disabled")]
+    SlugsDisabled,
+    #[error("slug must be between {0} and {1} characters long")]
+    SlugLength(usize, usize),
+    #[error("slug may only contain the characters `{0}`")]
+    SlugChars(String),
+    #[error("invalid expiration time: {0}")]
+    ExpiresAt(#[from] time::error::Parse),
+    #[error("expiration time lies in the past")]
+    ExpiresAtPast,
+    #[error("expiration time must be at most {0} from now")]
+    ExpiresAtTooLate(Expiration),
+    #[error("either expires or expires_at can be given")]
+    ExpiresAndExpiresAt,
+    #[error("either expires or expires_at must be given")]
+    NoExpiration,
+    #[error("expiration of {0} seconds is not configured")]
+    ExpirationNotConfigured(u64),
+    #[error("parent paste `{0}` does not exist")]
+    ParentNotFound(Id),
+    #[error("invalid extension `{0}`")]
+    Extension(String),
+    #[error("content exceeds {0} bytes")]
+    BodyTooLarge(usize),
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Deserialize)]
 pub(crate) struct JsonError {
     pub message: String,
+    /// Machine-readable error code for errors clients are expected to handle.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+}
+
+impl Error {
+    /// Machine-readable code of errors that clients are expected to handle.
+    fn code(&self) -> Option<&'static str> {
+        match self {
+            Error::Database(db::Error::Conflict) => Some("slug_taken"),
+            Error::SlugsDisabled => Some("slugs_disabled"),
+            Error::SlugLength(..)
+            | Error::SlugChars(_)
+            | Error::Id(id::Error::InvalidSlug | id::Error::AmbiguousSlug) => Some("invalid_slug"),
+            _ => None,
+        }
+    }
 }
 
 /// Response carrying a status code and the error message as JSON.
@@ -41,17 +90,37 @@ impl From<Error> for StatusCode {
         match err {
             Error::Database(db::Error::NotFound) => StatusCode::NOT_FOUND,
             Error::Database(
-                db::Error::Delete | db::Error::Crypto(crypto::Error::ChaCha20Poly1305Decrypt),
+                db::Error::Delete
+                | db::Error::Edit
+                | db::Error::Restore
+                | db::Error::Crypto(crypto::Error::ChaCha20Poly1305Decrypt),
             ) => StatusCode::FORBIDDEN,
-            Error::Database(db::Error::NoPassword) | Error::Id(_) | Error::UrlParsing(_) => {
-                StatusCode::BAD_REQUEST
-            }
+            Error::Database(db::Error::NoPassword | db::Error::Utf8(_))
+            | Error::Id(_)
+            | Error::UrlParsing(_)
+            | Error::Files(_)
+            | Error::TextAndFiles
+            | Error::SlugsDisabled
+            | Error::SlugLength(..)
+            | Error::SlugChars(_)
+            | Error::ExpiresAt(_)
+            | Error::ExpiresAtPast
+            | Error::ExpiresAtTooLate(_)
+            | Error::ExpiresAndExpiresAt
+            | Error::NoExpiration
+            | Error::ExpirationNotConfigured(_)
+            | Error::ParentNotFound(_)
+            | Error::Extension(_) => StatusCode::BAD_REQUEST,
+            Error::Database(db::Error::Conflict) => StatusCode::CONFLICT,
             Error::MalformedForm => StatusCode::UNPROCESSABLE_ENTITY,
+            Error::BodyTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+            Error::Database(db::Error::QuotaExceeded) => StatusCode::INSUFFICIENT_STORAGE,
             Error::Join(_)
             | Error::QrCode(_)
             | Error::Database(_)
             | Error::IntConversion(_)
             | Error::SyntaxHighlighting(_)
+            | Error::Zip(_)
             | Error::Axum(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
@@ -61,6 +130,7 @@ impl From<Error> for JsonErrorResponse {
     fn from(err: Error) -> Self {
         let payload = Json::from(JsonError {
             message: err.to_string(),
+            code: err.code().map(String::from),
         });
 
         (err.into(), payload)
@@ -47,4 +47,44 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn restore_from_trash() -> Result<(), Box<dyn std::error::Error>> {
+        let config = wastebin_core::db::Config {
+            trash_period: Some(std::time::Duration::from_secs(3600)),
+            ..Default::default()
+        };
+        let client = Client::with_config(StoreCookies(true), config).await;
+
+        let data = Entry {
+            text: String::from("FooBarBaz"),
+            ..Default::default()
+        };
+
+        let res = client.post_form().form(&data).send().await?;
+        let location = res.headers().get("location").unwrap().to_str()?.to_owned();
+        let id = location.replace('/', "");
+
+        let res = client.post(&format!("/delete/{id}")).send().await?;
+        assert_eq!(res.status(), StatusCode::SEE_OTHER);
+
+        let res = client.get(&format!("/raw/{id}")).send().await?;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+        let res = client.get(&location).send().await?;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert!(res.text().await?.contains(r#"name="restore""#));
+
+        let res = client
+            .post(&location)
+            .form(&[("restore", "1")])
+            .send()
+            .await?;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let res = client.get(&format!("/raw/{id}")).send().await?;
+        assert_eq!(res.text().await?, "FooBarBaz");
+
+        Ok(())
+    }
 }
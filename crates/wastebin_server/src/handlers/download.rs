@@ -1,5 +1,7 @@
 use std::fmt::Write;
+use std::io::{Cursor, Write as _};
 
+use axum::body::Body;
 use axum::extract::{Path, State};
 use axum::http::header;
 use axum::response::{IntoResponse, Response};
@@ -10,10 +12,15 @@ use crate::cache::Key;
 use crate::handlers::extract::{Password, Theme};
 use crate::handlers::html::{ErrorResponse, PasswordInput, make_error};
 use crate::i18n::Lang;
-use wastebin_core::db::read::{Data, Entry};
+use tokio_util::io::ReaderStream;
+use wastebin_core::db::read::{Metadata, Stream};
 use wastebin_core::db::{self, Database};
+use wastebin_core::files::File;
+use zip::ZipWriter;
+use zip::write::SimpleFileOptions;
 
-/// GET handler for raw content of a paste.
+/// GET handler for downloading a paste as a file. Multi-file pastes are downloaded as a zip
+/// archive.
 pub async fn get(
     Path(id): Path<String>,
     State(db): State<Database>,
@@ -26,10 +33,11 @@ pub async fn get(
         let key: Key = id.parse()?;
         let password = password.map(|Password(password)| password);
 
-        match db.get(key.id, password).await {
-            Ok(Entry::Regular(data) | Entry::Burned(data)) => {
-                Ok(get_download(&key, data).into_response())
-            }
+        match db.stream(key.id, password).await {
+            Ok(mut stream) => match stream.files().await? {
+                Some(files) => Ok(get_zip(&key, stream.metadata, &files)?.into_response()),
+                None => Ok(get_download(&key, stream).into_response()),
+            },
             Err(db::Error::NoPassword) => Ok(PasswordInput {
                 page: page.clone(),
                 theme: theme.clone(),
@@ -58,8 +66,12 @@ fn make_content_disposition(filename: &str) -> HeaderValue {
     HeaderValue::try_from(value).unwrap_or_else(|_| HeaderValue::from_static("attachment"))
 }
 
-fn get_download(key: &Key, data: Data) -> impl IntoResponse {
-    let filename = data.metadata.title.unwrap_or_else(|| key.to_string());
+fn get_download(key: &Key, stream: Stream) -> impl IntoResponse {
+    let filename = stream.metadata.title.unwrap_or_else(|| {
+        key.clone()
+            .or_extension(stream.metadata.extension)
+            .to_string()
+    });
 
     let content_type = "text; charset=utf-8";
     let content_disposition = make_content_disposition(&filename);
@@ -69,16 +81,47 @@ fn get_download(key: &Key, data: Data) -> impl IntoResponse {
             (header::CONTENT_TYPE, HeaderValue::from_static(content_type)),
             (header::CONTENT_DISPOSITION, content_disposition),
         ],
-        data.text,
+        Body::from_stream(ReaderStream::new(stream.reader)),
     )
 }
 
+fn get_zip(
+    key: &Key,
+    metadata: Metadata,
+    files: &[File],
+) -> Result<impl IntoResponse, crate::Error> {
+    let filename = metadata.title.unwrap_or_else(|| key.id());
+    let content_disposition = make_content_disposition(&format!("{filename}.zip"));
+
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+
+    for file in files {
+        zip.start_file(file.name.as_str(), SimpleFileOptions::default())?;
+        zip.write_all(file.text.as_bytes())
+            .map_err(zip::result::ZipError::Io)?;
+    }
+
+    Ok((
+        [
+            (
+                header::CONTENT_TYPE,
+                HeaderValue::from_static("application/zip"),
+            ),
+            (header::CONTENT_DISPOSITION, content_disposition),
+        ],
+        zip.finish()?.into_inner(),
+    ))
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::handlers::insert::form::Entry;
+    use crate::handlers::insert::api::RedirectResponse;
+    use crate::handlers::insert::{api, form::Entry};
     use crate::test_helpers::{Client, StoreCookies};
     use http::header;
     use reqwest::StatusCode;
+    use std::io::{Cursor, Read};
+    use wastebin_core::files::File;
 
     #[tokio::test]
     async fn download() -> Result<(), Box<dyn std::error::Error>> {
@@ -160,4 +203,45 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn download_files() -> Result<(), Box<dyn std::error::Error>> {
+        let client = Client::new(StoreCookies(false)).await;
+        let entry = api::Entry {
+            title: Some(String::from("crate")),
+            files: vec![
+                File {
+                    name: String::from("Cargo.toml"),
+                    text: String::from("[package]"),
+                },
+                File {
+                    name: String::from("main.rs"),
+                    text: String::from("fn main() {}"),
+                },
+            ],
+            ..Default::default()
+        };
+
+        let res = client.post_json().json(&entry).send().await?;
+        let payload = res.json::<RedirectResponse>().await?;
+        let res = client.get(&format!("/dl{}", payload.path)).send().await?;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(
+            res.headers().get(header::CONTENT_TYPE).unwrap(),
+            "application/zip"
+        );
+        assert_eq!(
+            res.headers().get(header::CONTENT_DISPOSITION).unwrap(),
+            "attachment; filename*=UTF-8''crate.zip"
+        );
+
+        let mut archive = zip::ZipArchive::new(Cursor::new(res.bytes().await?))?;
+        assert_eq!(archive.len(), 2);
+
+        let mut text = String::new();
+        archive.by_name("main.rs")?.read_to_string(&mut text)?;
+        assert_eq!(text, "fn main() {}");
+
+        Ok(())
+    }
 }
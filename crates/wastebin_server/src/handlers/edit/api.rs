@@ -0,0 +1,166 @@
+use axum::Json;
+use axum::extract::{Path, State};
+use axum_extra::extract::cookie::Key;
+use serde::{Deserialize, Serialize};
+
+use crate::cache::{self, Cache};
+use crate::errors::{Error, JsonErrorResponse};
+use crate::handlers::extract::{Uids, verify_owner_token};
+use crate::handlers::insert::text_or_files;
+use wastebin_core::db::{Database, write};
+use wastebin_core::files::File;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(crate) struct Entry {
+    #[serde(default)]
+    pub text: String,
+    /// Files of a multi-file paste, given instead of `text`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<File>,
+    pub password: Option<String>,
+    /// Optional owner token from the insert response. Authorizes the edit in addition to the
+    /// uids of the `uid` cookie.
+    #[serde(default)]
+    pub owner: Option<String>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub(crate) struct EditResponse {
+    pub path: String,
+    /// Revision number of the new content.
+    pub revision: u32,
+}
+
+impl From<Entry> for write::Entry {
+    fn from(entry: Entry) -> Self {
+        Self {
+            text: entry.text,
+            password: entry.password,
+            ..Default::default()
+        }
+    }
+}
+
+pub async fn put(
+    Path(id): Path<String>,
+    State(db): State<Database>,
+    State(cache): State<Cache>,
+    State(key): State<Key>,
+    uids: Option<Uids>,
+    Json(mut entry): Json<Entry>,
+) -> Result<Json<EditResponse>, JsonErrorResponse> {
+    let paste: cache::Key = id.parse()?;
+    let files = std::mem::take(&mut entry.files);
+
+    let mut uids = uids.map(|Uids(uids)| uids).unwrap_or_default();
+
+    if let Some(uid) = entry
+        .owner
+        .as_deref()
+        .and_then(|token| verify_owner_token(&key, token))
+    {
+        uids.push(uid);
+    }
+
+    let mut entry: write::Entry = entry.into();
+    text_or_files(&mut entry, &files)?;
+
+    let revision = db
+        .edit(paste.id, &uids, entry)
+        .await
+        .map_err(Error::Database)?;
+
+    cache.remove(paste.id);
+
+    Ok(Json::from(EditResponse {
+        path: format!("/{paste}"),
+        revision,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::handlers::insert::api::{Entry, RedirectResponse};
+    use crate::test_helpers::{Client, StoreCookies};
+    use reqwest::StatusCode;
+
+    #[tokio::test]
+    async fn edit_with_owner_token() -> Result<(), Box<dyn std::error::Error>> {
+        let client = Client::new(StoreCookies(false)).await;
+
+        let entry = Entry {
+            text: "FooBarBaz".to_string(),
+            ..Default::default()
+        };
+
+        let payload = client
+            .post_json()
+            .json(&entry)
+            .send()
+            .await?
+            .json::<RedirectResponse>()
+            .await?;
+
+        let res = client
+            .put(&payload.path)
+            .json(&super::Entry {
+                text: "BazBarFoo".to_string(),
+                owner: Some(payload.owner.clone()),
+                ..Default::default()
+            })
+            .send()
+            .await?;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let edited = res.json::<super::EditResponse>().await?;
+        assert_eq!(edited.path, payload.path);
+        assert_eq!(edited.revision, 2);
+
+        let res = client.get(&format!("/raw{}", payload.path)).send().await?;
+        assert_eq!(res.text().await?, "BazBarFoo");
+
+        let res = client
+            .get(&format!("/raw{}", payload.path))
+            .query(&[("rev", "1")])
+            .send()
+            .await?;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.text().await?, "FooBarBaz");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn edit_without_ownership() -> Result<(), Box<dyn std::error::Error>> {
+        let client = Client::new(StoreCookies(false)).await;
+
+        let entry = Entry {
+            text: "FooBarBaz".to_string(),
+            ..Default::default()
+        };
+
+        let payload = client
+            .post_json()
+            .json(&entry)
+            .send()
+            .await?
+            .json::<RedirectResponse>()
+            .await?;
+
+        let res = client
+            .put(&payload.path)
+            .json(&super::Entry {
+                text: "BazBarFoo".to_string(),
+                owner: Some("garbage".to_string()),
+                ..Default::default()
+            })
+            .send()
+            .await?;
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+
+        let res = client.get(&format!("/raw{}", payload.path)).send().await?;
+        assert_eq!(res.text().await?, "FooBarBaz");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,151 @@
+use axum::extract::{Path, State};
+use axum::response::Redirect;
+use axum_extra::extract::Form;
+use serde::{Deserialize, Serialize};
+
+use crate::cache::Key;
+use crate::handlers::extract::{Theme, Uids};
+use crate::handlers::html::{ErrorResponse, make_error};
+use crate::handlers::insert::form::collect_files;
+use crate::i18n::Lang;
+use crate::{Cache, Database, Page};
+use wastebin_core::db::write;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(crate) struct Entry {
+    #[serde(default)]
+    pub text: String,
+    #[serde(default)]
+    pub password: String,
+    /// Names of the files of a multi-file paste.
+    #[serde(default, rename = "file-name", skip_serializing_if = "Vec::is_empty")]
+    pub file_names: Vec<String>,
+    /// Content of the files of a multi-file paste.
+    #[serde(default, rename = "file-text", skip_serializing_if = "Vec::is_empty")]
+    pub file_texts: Vec<String>,
+}
+
+impl From<Entry> for write::Entry {
+    fn from(entry: Entry) -> Self {
+        let password = (!entry.password.is_empty()).then_some(entry.password);
+
+        Self {
+            text: entry.text,
+            password,
+            ..Default::default()
+        }
+    }
+}
+
+#[expect(clippy::too_many_arguments)]
+pub async fn post<E: std::fmt::Debug>(
+    Path(id): Path<String>,
+    State(db): State<Database>,
+    State(cache): State<Cache>,
+    State(page): State<Page>,
+    uids: Option<Uids>,
+    theme: Option<Theme>,
+    lang: Lang,
+    entry: Result<Form<Entry>, E>,
+) -> Result<Redirect, ErrorResponse> {
+    let Ok(Form(mut entry)) = entry else {
+        return Err(make_error(crate::Error::MalformedForm, page, theme, lang));
+    };
+
+    async {
+        let key: Key = id.parse()?;
+
+        let names = std::mem::take(&mut entry.file_names);
+        let texts = std::mem::take(&mut entry.file_texts);
+        let mut entry: write::Entry = entry.into();
+
+        if !texts.is_empty() {
+            entry.set_files(&collect_files(names, texts, None))?;
+        }
+
+        let uids = uids.map(|Uids(uids)| uids).unwrap_or_default();
+        db.edit(key.id, &uids, entry).await?;
+        cache.remove(key.id);
+        Ok(Redirect::to(&format!("/{key}")))
+    }
+    .await
+    .map_err(|err| make_error(err, page.clone(), theme, lang))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::handlers::insert::form::Entry;
+    use crate::test_helpers::{Client, StoreCookies};
+    use reqwest::{StatusCode, header};
+
+    #[tokio::test]
+    async fn edit_via_form() -> Result<(), Box<dyn std::error::Error>> {
+        let client = Client::new(StoreCookies(true)).await;
+
+        let data = Entry {
+            text: String::from("FooBarBaz"),
+            ..Default::default()
+        };
+
+        let res = client.post_form().form(&data).send().await?;
+        let location = res.headers().get("location").unwrap().to_str()?.to_owned();
+
+        // Render once to populate the cache, it must be invalidated by the edit.
+        let res = client.get(&location).send().await?;
+        assert!(res.text().await?.contains("FooBarBaz"));
+
+        let res = client
+            .post(&format!("/update{location}"))
+            .form(&super::Entry {
+                text: String::from("BazBarFoo"),
+                ..Default::default()
+            })
+            .send()
+            .await?;
+        assert_eq!(res.status(), StatusCode::SEE_OTHER);
+        assert_eq!(res.headers().get("location").unwrap(), &location);
+
+        let res = client
+            .get(&location)
+            .header(header::ACCEPT, "text/html; charset=utf-8")
+            .send()
+            .await?;
+        let body = res.text().await?;
+        assert!(body.contains("BazBarFoo"));
+        assert!(body.contains("?rev=1"));
+
+        let res = client.get(&location).query(&[("rev", "1")]).send().await?;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert!(res.text().await?.contains("FooBarBaz"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn edit_via_form_without_ownership() -> Result<(), Box<dyn std::error::Error>> {
+        let client = Client::new(StoreCookies(false)).await;
+
+        let data = Entry {
+            text: String::from("FooBarBaz"),
+            ..Default::default()
+        };
+
+        let res = client.post_form().form(&data).send().await?;
+        let location = res.headers().get("location").unwrap().to_str()?.to_owned();
+
+        let res = client
+            .post(&format!("/update{location}"))
+            .form(&super::Entry {
+                text: String::from("BazBarFoo"),
+                ..Default::default()
+            })
+            .send()
+            .await?;
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+
+        let res = client.get(&format!("/raw{location}")).send().await?;
+        assert_eq!(res.text().await?, "FooBarBaz");
+
+        Ok(())
+    }
+}
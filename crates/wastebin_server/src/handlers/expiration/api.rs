@@ -0,0 +1,135 @@
+use axum::Json;
+use axum::extract::{Path, State};
+use axum_extra::extract::cookie::Key;
+use serde::{Deserialize, Serialize};
+
+use crate::cache;
+use crate::errors::{Error, JsonErrorResponse};
+use crate::handlers::expiration::new_expiration;
+use crate::handlers::extract::{Uids, verify_owner_token};
+use crate::{Database, Page};
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(crate) struct Expiration {
+    /// Seconds from now, one of the configured expirations with zero meaning never.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires: Option<u64>,
+    /// Point in time as RFC 3339 timestamp, given instead of `expires`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<String>,
+    /// Optional owner token from the insert response. Authorizes the change in addition to the
+    /// uids of the `uid` cookie.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+}
+
+pub async fn put(
+    Path(id): Path<String>,
+    State(db): State<Database>,
+    State(page): State<Page>,
+    State(key): State<Key>,
+    uids: Option<Uids>,
+    Json(expiration): Json<Expiration>,
+) -> Result<(), JsonErrorResponse> {
+    let paste: cache::Key = id.parse()?;
+    let expires = new_expiration(&page, expiration.expires, expiration.expires_at.as_deref())?;
+
+    let mut uids = uids.map(|Uids(uids)| uids).unwrap_or_default();
+
+    if let Some(uid) = expiration
+        .owner
+        .as_deref()
+        .and_then(|token| verify_owner_token(&key, token))
+    {
+        uids.push(uid);
+    }
+
+    db.set_expiration(paste.id, &uids, expires)
+        .await
+        .map_err(Error::Database)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::handlers::insert::api::{Entry, RedirectResponse};
+    use crate::handlers::metadata::MetadataResponse;
+    use crate::test_helpers::{Client, StoreCookies};
+    use reqwest::StatusCode;
+
+    #[tokio::test]
+    async fn change_expiration() -> Result<(), Box<dyn std::error::Error>> {
+        let client = Client::new(StoreCookies(false)).await;
+
+        let entry = Entry {
+            text: "FooBarBaz".to_string(),
+            expires: std::num::NonZeroU32::new(60),
+            ..Default::default()
+        };
+
+        let payload = client
+            .post_json()
+            .json(&entry)
+            .send()
+            .await?
+            .json::<RedirectResponse>()
+            .await?;
+
+        let path = format!("/expiration{}", payload.path);
+
+        let res = client
+            .put(&path)
+            .json(&super::Expiration {
+                expires: Some(0),
+                owner: Some(String::from("garbage")),
+                ..Default::default()
+            })
+            .send()
+            .await?;
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+
+        let res = client
+            .put(&path)
+            .json(&super::Expiration {
+                expires: Some(3600),
+                owner: Some(payload.owner.clone()),
+                ..Default::default()
+            })
+            .send()
+            .await?;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+        let res = client
+            .put(&path)
+            .json(&super::Expiration {
+                expires_at: Some(String::from("2999-12-31T18:00:00Z")),
+                owner: Some(payload.owner.clone()),
+                ..Default::default()
+            })
+            .send()
+            .await?;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let res = client.get(&format!("/meta{}", payload.path)).send().await?;
+        let metadata = res.json::<MetadataResponse>().await?;
+        assert!(metadata.expires_in.is_some_and(|secs| secs > 60));
+
+        let res = client
+            .put(&path)
+            .json(&super::Expiration {
+                expires: Some(0),
+                owner: Some(payload.owner.clone()),
+                ..Default::default()
+            })
+            .send()
+            .await?;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let res = client.get(&format!("/meta{}", payload.path)).send().await?;
+        let metadata = res.json::<MetadataResponse>().await?;
+        assert_eq!(metadata.expires_in, None);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,79 @@
+use axum::extract::{Path, State};
+use axum::response::Redirect;
+use axum_extra::extract::Form;
+use serde::{Deserialize, Serialize};
+
+use crate::cache::Key;
+use crate::handlers::expiration::new_expiration;
+use crate::handlers::extract::{Theme, Uids};
+use crate::handlers::html::{ErrorResponse, make_error};
+use crate::i18n::Lang;
+use crate::{Database, Page};
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(crate) struct Expiration {
+    /// Seconds from now, one of the configured expirations with zero meaning never.
+    pub expires: u64,
+}
+
+pub async fn post<E: std::fmt::Debug>(
+    Path(id): Path<String>,
+    State(db): State<Database>,
+    State(page): State<Page>,
+    uids: Option<Uids>,
+    theme: Option<Theme>,
+    lang: Lang,
+    expiration: Result<Form<Expiration>, E>,
+) -> Result<Redirect, ErrorResponse> {
+    let Ok(Form(expiration)) = expiration else {
+        return Err(make_error(crate::Error::MalformedForm, page, theme, lang));
+    };
+
+    async {
+        let key: Key = id.parse()?;
+        let expires = new_expiration(&page, Some(expiration.expires), None)?;
+        let uids = uids.map(|Uids(uids)| uids).unwrap_or_default();
+        db.set_expiration(key.id, &uids, expires).await?;
+        Ok(Redirect::to(&format!("/{key}")))
+    }
+    .await
+    .map_err(|err| make_error(err, page.clone(), theme, lang))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::handlers::insert::form::Entry;
+    use crate::test_helpers::{Client, StoreCookies};
+    use reqwest::StatusCode;
+
+    #[tokio::test]
+    async fn change_expiration_via_form() -> Result<(), Box<dyn std::error::Error>> {
+        let client = Client::new(StoreCookies(true)).await;
+
+        let data = Entry {
+            text: String::from("FooBarBaz"),
+            expires: Some(String::from("60")),
+            ..Default::default()
+        };
+
+        let res = client.post_form().form(&data).send().await?;
+        let location = res.headers().get("location").unwrap().to_str()?.to_owned();
+
+        let res = client
+            .post(&format!("/expiration{location}"))
+            .form(&super::Expiration { expires: 0 })
+            .send()
+            .await?;
+        assert_eq!(res.status(), StatusCode::SEE_OTHER);
+        assert_eq!(res.headers().get("location").unwrap(), &location);
+
+        let res = client
+            .get(&location)
+            .header(reqwest::header::ACCEPT, "text/html; charset=utf-8")
+            .send()
+            .await?;
+        assert!(!res.text().await?.contains("expires in"));
+
+        Ok(())
+    }
+}
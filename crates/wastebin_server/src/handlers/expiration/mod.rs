@@ -0,0 +1,37 @@
+pub mod api;
+pub mod form;
+
+use std::time::{Duration, SystemTime};
+
+use crate::errors::Error;
+use crate::handlers::insert::requested_expiration;
+use crate::page::Page;
+
+/// Point in time a paste should expire at, `None` meaning never. It is given either as `expires`
+/// seconds from now, which must be one of the expirations of `page` with zero meaning never, or as
+/// RFC 3339 `expires_at` timestamp.
+pub(crate) fn new_expiration(
+    page: &Page,
+    expires: Option<u64>,
+    expires_at: Option<&str>,
+) -> Result<Option<SystemTime>, Error> {
+    match (
+        expires,
+        expires_at.filter(|expires_at| !expires_at.is_empty()),
+    ) {
+        (Some(_), Some(_)) => Err(Error::ExpiresAndExpiresAt),
+        (None, Some(expires_at)) => requested_expiration(page, Some(expires_at)),
+        (Some(secs), None) => {
+            if !page
+                .expirations
+                .iter()
+                .any(|expiration| expiration.duration.as_secs() == secs)
+            {
+                return Err(Error::ExpirationNotConfigured(secs));
+            }
+
+            Ok((secs > 0).then(|| SystemTime::now() + Duration::from_secs(secs)))
+        }
+        (None, None) => Err(Error::NoExpiration),
+    }
+}
@@ -38,6 +38,12 @@ pub(crate) struct Preference {
     pub pref: Theme,
 }
 
+/// Revision selection for use in shared [`axum::extract::Query`]'s.
+#[derive(Debug, Deserialize)]
+pub(crate) struct Revision {
+    pub rev: Option<u32>,
+}
+
 /// Password extractor.
 pub(crate) struct Password(pub crypto::Password);
 
@@ -7,17 +7,19 @@ use crate::handlers::extract::Theme;
 use crate::handlers::html::qr::{code_from, dark_modules};
 use crate::handlers::html::{ErrorResponse, make_error};
 use crate::i18n::Lang;
-use crate::{Error, Page};
+use crate::{Database, Error, Page};
 
 /// GET handler for the burn page.
 pub async fn get(
     Path(id): Path<String>,
     State(page): State<Page>,
+    State(db): State<Database>,
     theme: Option<Theme>,
     lang: Lang,
 ) -> Result<Burn, ErrorResponse> {
     async {
         let key: Key = id.parse()?;
+        let max_views = db.get_metadata(key.id).await?.max_views.unwrap_or(1);
 
         let code = tokio::task::spawn_blocking({
             let page = page.clone();
@@ -32,13 +34,14 @@ pub async fn get(
             code,
             theme: theme.clone(),
             lang,
+            max_views,
         })
     }
     .await
     .map_err(|err| make_error(err, page, theme, lang))
 }
 
-/// Burn page shown if "burn-after-reading" was selected during insertion.
+/// Burn page shown if a view limit was set during insertion.
 #[derive(Template, WebTemplate)]
 #[template(path = "burn.html", escape = "none")]
 pub(crate) struct Burn {
@@ -47,6 +50,7 @@ pub(crate) struct Burn {
     code: qrcodegen::QrCode,
     theme: Option<Theme>,
     lang: Lang,
+    max_views: u32,
 }
 
 impl Burn {
@@ -220,4 +224,45 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn max_views() -> Result<(), Box<dyn std::error::Error>> {
+        let client = Client::new(StoreCookies(false)).await;
+        let data = Entry {
+            text: String::from("shared-secret"),
+            max_views: Some(String::from("2")),
+            ..Default::default()
+        };
+
+        let res = client.post_form().form(&data).send().await?;
+        let location = res.headers().get("location").unwrap().to_str()?.to_owned();
+
+        let res = client.get(&location).send().await?;
+        assert!(res.text().await?.contains("once its views are used up"));
+
+        let location = location.replace("burn/", "");
+
+        for remaining in ["after 2 more views", "the moment it is revealed"] {
+            let res = client
+                .get(&location)
+                .header(header::ACCEPT, "text/html; charset=utf-8")
+                .send()
+                .await?;
+            assert!(res.text().await?.contains(remaining));
+
+            let res = client
+                .post(&location)
+                .form(&[("confirm_burn", "1")])
+                .header(header::ACCEPT, "text/html; charset=utf-8")
+                .send()
+                .await?;
+            assert_eq!(res.status(), StatusCode::OK);
+            assert!(res.text().await?.contains("shared-secret"));
+        }
+
+        let res = client.get(&location).send().await?;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+        Ok(())
+    }
 }
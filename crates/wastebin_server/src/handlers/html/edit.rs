@@ -0,0 +1,142 @@
+use askama::Template;
+use askama_web::WebTemplate;
+use axum::extract::{Form, Path, State};
+use axum::response::{IntoResponse, Response};
+
+use crate::cache::Key;
+use crate::handlers::extract::{Theme, Uids};
+use crate::handlers::html::paste::PasswordForm;
+use crate::handlers::html::{ErrorResponse, PasswordInput, make_error};
+use crate::i18n::Lang;
+use crate::{Database, Page};
+use wastebin_core::crypto::Password;
+use wastebin_core::db;
+use wastebin_core::files::File;
+
+/// Editor pre-filled with the current content of a paste.
+#[derive(Template, WebTemplate)]
+#[template(path = "edit.html")]
+pub(crate) struct Edit {
+    page: Page,
+    key: Key,
+    theme: Option<Theme>,
+    lang: Lang,
+    text: String,
+    /// Files of a multi-file paste, edited instead of `text`.
+    files: Vec<File>,
+    title: Option<String>,
+    /// Password of an encrypted paste, passed along to encrypt the new revision.
+    password: Option<String>,
+}
+
+/// GET handler for the edit page. POST requests carry the password of encrypted pastes.
+pub async fn get<E>(
+    State(page): State<Page>,
+    State(db): State<Database>,
+    Path(id): Path<String>,
+    uids: Option<Uids>,
+    theme: Option<Theme>,
+    lang: Lang,
+    form: Result<Form<PasswordForm>, E>,
+) -> Result<Response, ErrorResponse> {
+    async {
+        let password = form
+            .ok()
+            .map(|Form(form)| form.password)
+            .filter(|password| !password.is_empty());
+        let key: Key = id.parse()?;
+        let metadata = db.get_metadata(key.id).await?;
+
+        let is_owner = match (uids, metadata.uid) {
+            (Some(Uids(uids)), Some(owner_uid)) => uids.contains(&owner_uid),
+            _ => false,
+        };
+
+        if !is_owner || metadata.max_views.is_some() {
+            return Err(db::Error::Edit.into());
+        }
+
+        let data = match db
+            .get_revision(
+                key.id,
+                metadata.revision,
+                password
+                    .as_ref()
+                    .map(|password| Password::from(password.as_bytes().to_vec())),
+            )
+            .await
+        {
+            Ok(data) => data,
+            Err(db::Error::NoPassword) => {
+                return Ok(PasswordInput {
+                    page: page.clone(),
+                    theme: theme.clone(),
+                    lang,
+                    id: format!("edit/{key}"),
+                }
+                .into_response());
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+        Ok(Edit {
+            page: page.clone(),
+            key,
+            theme: theme.clone(),
+            lang,
+            files: data.files().unwrap_or_default(),
+            text: data.text,
+            title: data.metadata.title,
+            password,
+        }
+        .into_response())
+    }
+    .await
+    .map_err(|err| make_error(err, page, theme, lang))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::handlers::insert::form::Entry;
+    use crate::test_helpers::{Client, StoreCookies};
+    use reqwest::StatusCode;
+
+    #[tokio::test]
+    async fn owner_sees_editor() -> Result<(), Box<dyn std::error::Error>> {
+        let client = Client::new(StoreCookies(true)).await;
+        let data = Entry {
+            text: String::from("FooBarBaz"),
+            ..Default::default()
+        };
+
+        let res = client.post_form().form(&data).send().await?;
+        let location = res.headers().get("location").unwrap().to_str()?.to_owned();
+
+        let res = client.get(&format!("/edit{location}")).send().await?;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let body = res.text().await?;
+        assert!(body.contains(&format!("action=\"/update{location}\"")));
+        assert!(body.contains("FooBarBaz"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn stranger_is_forbidden() -> Result<(), Box<dyn std::error::Error>> {
+        let client = Client::new(StoreCookies(false)).await;
+        let data = Entry {
+            text: String::from("FooBarBaz"),
+            ..Default::default()
+        };
+
+        let res = client.post_form().form(&data).send().await?;
+        let location = res.headers().get("location").unwrap().to_str()?.to_owned();
+
+        let res = client.get(&format!("/edit{location}")).send().await?;
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+        assert!(!res.text().await?.contains("FooBarBaz"));
+
+        Ok(())
+    }
+}
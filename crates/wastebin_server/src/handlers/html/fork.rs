@@ -0,0 +1,151 @@
+use axum::extract::{Form, Path, Query, State};
+use axum::response::{IntoResponse, Response};
+
+use crate::cache::Key;
+use crate::handlers::extract::{Revision, Theme};
+use crate::handlers::html::index::{Draft, Index};
+use crate::handlers::html::paste::PasswordForm;
+use crate::handlers::html::{ErrorResponse, PasswordInput, make_error};
+use crate::i18n::Lang;
+use crate::{Database, Highlighter, Page};
+use wastebin_core::crypto::Password;
+use wastebin_core::db;
+
+/// GET handler showing the index form pre-filled with a paste's content. POST requests carry the
+/// password of encrypted pastes.
+#[expect(clippy::too_many_arguments)]
+pub async fn get<E>(
+    State(page): State<Page>,
+    State(db): State<Database>,
+    State(highlighter): State<Highlighter>,
+    Path(id): Path<String>,
+    Query(revision): Query<Revision>,
+    theme: Option<Theme>,
+    lang: Lang,
+    form: Result<Form<PasswordForm>, E>,
+) -> Result<Response, ErrorResponse> {
+    async {
+        let password = form
+            .ok()
+            .map(|Form(form)| Password::from(form.password.as_bytes().to_vec()));
+        let key: Key = id.parse()?;
+
+        let rev = match revision.rev {
+            Some(rev) => rev,
+            None => db.get_metadata(key.id).await?.revision,
+        };
+
+        // Unlike a regular view this does not count as one, view-limited pastes are not found.
+        let data = match db.get_revision(key.id, rev, password).await {
+            Ok(data) => data,
+            Err(db::Error::NoPassword) => {
+                return Ok(PasswordInput {
+                    page: page.clone(),
+                    theme: theme.clone(),
+                    lang,
+                    id: format!("fork/{key}?rev={rev}"),
+                }
+                .into_response());
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+        let (text, filename, files) = match data.files() {
+            Some(mut files) => {
+                let first = files.remove(0);
+                (first.text, Some(first.name), files)
+            }
+            None => (data.text, None, Vec::new()),
+        };
+
+        let draft = Draft {
+            text,
+            filename,
+            files,
+            title: data.metadata.title,
+            extension: key.ext.or(data.metadata.extension),
+            parent: Some(key.id.to_string()),
+        };
+
+        Ok(
+            Index::with_draft(page.clone(), theme.clone(), lang, highlighter, draft)
+                .into_response(),
+        )
+    }
+    .await
+    .map_err(|err| make_error(err, page, theme, lang))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::handlers::insert::form::Entry;
+    use crate::test_helpers::{Client, StoreCookies};
+    use reqwest::{StatusCode, header};
+
+    #[tokio::test]
+    async fn fork() -> Result<(), Box<dyn std::error::Error>> {
+        let client = Client::new(StoreCookies(false)).await;
+        let data = Entry {
+            text: String::from("FooBarBaz"),
+            title: String::from("Forkable"),
+            extension: Some(String::from("rs")),
+            ..Default::default()
+        };
+
+        let res = client.post_form().form(&data).send().await?;
+        let location = res.headers().get("location").unwrap().to_str()?.to_owned();
+        let res = client.get(&format!("/fork{location}")).send().await?;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let id = location
+            .trim_start_matches('/')
+            .trim_end_matches(".rs")
+            .to_owned();
+        let body = res.text().await?;
+        assert!(body.contains(">FooBarBaz</textarea>"));
+        assert!(body.contains("value=\"Forkable\""));
+        assert!(body.contains("<option value=\"rs\" selected>"));
+        assert!(body.contains(&format!("name=\"parent\" value=\"{id}\"")));
+
+        let data = Entry {
+            text: String::from("FooBarBaz, forked"),
+            parent: Some(id.clone()),
+            ..Default::default()
+        };
+
+        let res = client.post_form().form(&data).send().await?;
+        let location = res.headers().get("location").unwrap().to_str()?.to_owned();
+
+        let res = client
+            .get(&location)
+            .header(header::ACCEPT, "text/html; charset=utf-8")
+            .send()
+            .await?;
+        assert!(res.text().await?.contains(&format!("href=\"/{id}\"")));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn fork_burn_after_reading() -> Result<(), Box<dyn std::error::Error>> {
+        let client = Client::new(StoreCookies(false)).await;
+        let data = Entry {
+            text: String::from("secret"),
+            burn_after_reading: Some(String::from("on")),
+            ..Default::default()
+        };
+
+        let res = client.post_form().form(&data).send().await?;
+        let location = res
+            .headers()
+            .get("location")
+            .unwrap()
+            .to_str()?
+            .replace("burn/", "");
+
+        let res = client.get(&format!("/fork{location}")).send().await?;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+        Ok(())
+    }
+}
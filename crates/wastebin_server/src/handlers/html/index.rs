@@ -4,6 +4,7 @@ use axum::extract::State;
 
 use crate::i18n::Lang;
 use crate::{Highlighter, Page, handlers::extract::Theme};
+use wastebin_core::files::File;
 
 /// GET handler for the index page.
 pub async fn get(
@@ -17,9 +18,23 @@ pub async fn get(
         theme,
         lang,
         highlighter,
+        draft: None,
     }
 }
 
+/// Content the index form is pre-filled with.
+pub(crate) struct Draft {
+    pub text: String,
+    /// Name of the first file of a multi-file paste.
+    pub filename: Option<String>,
+    /// Remaining files of a multi-file paste.
+    pub files: Vec<File>,
+    pub title: Option<String>,
+    pub extension: Option<String>,
+    /// Identifier of the paste the draft was forked from.
+    pub parent: Option<String>,
+}
+
 /// Index page displaying a form for paste insertion and a selection box for languages.
 #[derive(Template, WebTemplate)]
 #[template(path = "index.html")]
@@ -28,4 +43,38 @@ pub(crate) struct Index {
     theme: Option<Theme>,
     lang: Lang,
     highlighter: Highlighter,
+    draft: Option<Draft>,
+}
+
+impl Index {
+    /// Create index page with the form pre-filled from `draft`.
+    pub(crate) fn with_draft(
+        page: Page,
+        theme: Option<Theme>,
+        lang: Lang,
+        highlighter: Highlighter,
+        draft: Draft,
+    ) -> Self {
+        Self {
+            page,
+            theme,
+            lang,
+            highlighter,
+            draft: Some(draft),
+        }
+    }
+
+    /// Return `true` if the draft's extension is `extension`.
+    fn has_files(&self) -> bool {
+        self.draft
+            .as_ref()
+            .is_some_and(|draft| !draft.files.is_empty())
+    }
+
+    fn is_selected(&self, extension: &str) -> bool {
+        self.draft
+            .as_ref()
+            .and_then(|draft| draft.extension.as_deref())
+            .is_some_and(|ext| ext == extension)
+    }
 }
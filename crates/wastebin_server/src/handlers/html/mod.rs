@@ -1,4 +1,6 @@
 pub mod burn;
+pub mod edit;
+pub mod fork;
 pub mod index;
 pub mod paste;
 pub mod qr;
@@ -32,7 +34,7 @@ pub(crate) struct PasswordInput {
     pub id: String,
 }
 
-/// Interstitial page shown before a burn-after-reading paste is revealed.
+/// Interstitial page shown before a view-limited paste is revealed.
 #[derive(Template, WebTemplate)]
 #[template(path = "burn-confirmation.html")]
 pub(crate) struct BurnConfirmation {
@@ -41,6 +43,19 @@ pub(crate) struct BurnConfirmation {
     pub lang: Lang,
     pub id: String,
     pub title: Option<String>,
+    /// Views left including the one to be confirmed.
+    pub remaining_views: u32,
+}
+
+/// Page offering the owner of a deleted paste to restore it from the trash.
+#[derive(Template, WebTemplate)]
+#[template(path = "restore-confirmation.html")]
+pub(crate) struct RestoreConfirmation {
+    pub page: Page,
+    pub theme: Option<Theme>,
+    pub lang: Lang,
+    pub id: String,
+    pub title: Option<String>,
 }
 
 /// Error response carrying a status code and the page itself.
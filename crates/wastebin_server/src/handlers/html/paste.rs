@@ -8,14 +8,17 @@ use serde::Deserialize;
 
 use crate::cache::{Key, Mode};
 use crate::handlers::cookie;
-use crate::handlers::extract::{Theme, Uids, serialize_uids, verify_owner_token};
-use crate::handlers::html::{BurnConfirmation, ErrorResponse, PasswordInput, make_error};
+use crate::handlers::extract::{Revision, Theme, Uids, serialize_uids, verify_owner_token};
+use crate::handlers::html::{
+    BurnConfirmation, ErrorResponse, PasswordInput, RestoreConfirmation, make_error,
+};
 use crate::i18n::Lang;
 use crate::{Cache, Database, Highlighter, Page};
 use wastebin_core::crypto::Password;
 use wastebin_core::db;
 use wastebin_core::db::read::{Data, Entry, Metadata};
 use wastebin_core::expiration::Expiration;
+use wastebin_core::id::Id;
 
 /// Magic-link handoff: when a paste was created via the JSON API, the response contains a signed
 /// `owner` token. Opening `/<id>?owner=<token>` lets the browser claim ownership of the paste, the
@@ -37,6 +40,8 @@ pub(crate) struct PasteForm {
     pub(crate) password: Option<String>,
     #[serde(default)]
     pub(crate) confirm_burn: Option<String>,
+    #[serde(default)]
+    pub(crate) restore: Option<String>,
 }
 
 /// Paste view showing the formatted paste.
@@ -53,9 +58,88 @@ pub(crate) struct Paste {
     /// Expiration in case it was set.
     expiration: Option<Expiration>,
     html: String,
+    /// Highlighted files of a multi-file paste, shown instead of `html`.
+    files: Vec<FileView>,
     title: Option<String>,
     /// Whether the paste's extension identifies it as Markdown, enabling the rendered-view toggle.
     is_markdown: bool,
+    /// Latest revision number.
+    revision: u32,
+    /// Revision number of the shown content.
+    selected_revision: u32,
+    /// Paste this one was forked from.
+    parent: Option<Id>,
+    stats: Stats,
+}
+
+/// A highlighted file of a multi-file paste.
+pub(crate) struct FileView {
+    name: String,
+    html: String,
+}
+
+/// Creation, access and size information of a paste.
+pub(crate) struct Stats {
+    created: Option<String>,
+    accessed: Option<String>,
+    size: Option<u64>,
+    compressed_size: Option<u64>,
+}
+
+impl From<&Metadata> for Stats {
+    fn from(metadata: &Metadata) -> Self {
+        Self {
+            created: metadata.created.clone(),
+            accessed: metadata.accessed.clone(),
+            size: metadata.size,
+            compressed_size: metadata.compressed_size,
+        }
+    }
+}
+
+impl Stats {
+    /// Size and creation datetime, if known.
+    pub(crate) fn summary(&self, lang: Lang) -> Option<String> {
+        let size = self.size.map(|size| format_size(size, lang));
+        let created = self
+            .created
+            .as_ref()
+            .map(|created| format!("{} {created} UTC", lang.t("paste.created")));
+
+        match (size, created) {
+            (Some(size), Some(created)) => Some(format!("{size} · {created}")),
+            (size, created) => size.or(created),
+        }
+    }
+
+    /// Stored size and last access datetime, if known.
+    pub(crate) fn details(&self, lang: Lang) -> String {
+        let compressed = self
+            .compressed_size
+            .map(|size| format!("{} {}", lang.t("paste.compressed"), format_size(size, lang)));
+        let accessed = self
+            .accessed
+            .as_ref()
+            .map(|accessed| format!("{} {accessed} UTC", lang.t("paste.accessed")));
+
+        compressed
+            .into_iter()
+            .chain(accessed)
+            .collect::<Vec<_>>()
+            .join(" · ")
+    }
+}
+
+/// Format `bytes` in kilo- or megabytes.
+#[expect(clippy::cast_precision_loss)]
+fn format_size(bytes: u64, lang: Lang) -> String {
+    let kb = bytes as f64 / 1024.0;
+
+    if kb < 1024.0 {
+        format!("{kb:.1} {}", lang.t("stats.unit.kb"))
+    } else {
+        format!("{:.1} {}", kb / 1024.0, lang.t("stats.unit.mb"))
+    }
 }
 
 /// Return `true` if `ext` identifies a Markdown paste.
@@ -63,6 +147,35 @@ pub(crate) fn is_markdown_ext(ext: Option<&str>) -> bool {
     ext.is_some_and(|ext| ext.eq_ignore_ascii_case("md") || ext.eq_ignore_ascii_case("markdown"))
 }
 
+/// Return highlighted `text` from the cache slot `mode` of `key` if `is_latest` or highlight it and
+/// cache the result if `can_cache`.
+#[expect(clippy::too_many_arguments)]
+async fn highlight(
+    cache: &Cache,
+    highlighter: &Highlighter,
+    key: &Key,
+    mode: Mode,
+    is_latest: bool,
+    can_cache: bool,
+    text: String,
+    ext: Option<String>,
+) -> Result<String, crate::Error> {
+    if let Some(html) = cache.get(key, mode.clone()).filter(|_| is_latest) {
+        tracing::trace!(?key, "found cached item");
+        return Ok(html.into_inner());
+    }
+
+    let highlighter = highlighter.clone();
+    let html = tokio::task::spawn_blocking(move || highlighter.highlight(text, ext)).await??;
+
+    if can_cache {
+        tracing::trace!(?key, "cache item");
+        cache.put(key, mode, html.clone());
+    }
+
+    Ok(html.into_inner())
+}
+
 #[expect(clippy::too_many_arguments)]
 pub async fn get<E>(
     State(cache): State<Cache>,
@@ -72,6 +185,7 @@ pub async fn get<E>(
     State(cookie_key): State<CookieKey>,
     Path(id): Path<String>,
     Query(handoff): Query<OwnerHandoff>,
+    Query(revision): Query<Revision>,
     jar: SignedCookieJar,
     uids: Option<Uids>,
     theme: Option<Theme>,
@@ -101,29 +215,76 @@ pub async fn get<E>(
             .filter(|password| !password.is_empty())
             .map(|password| Password::from(password.as_bytes().to_vec()));
         let confirmed = form.as_ref().and_then(|form| form.confirm_burn.as_deref()) == Some("1");
+        let restore = form.as_ref().and_then(|form| form.restore.as_deref()) == Some("1");
         let no_password = password.is_none();
         let key: Key = id.parse()?;
 
         let metadata = match db.get_metadata(key.id).await {
             Ok(metadata) => metadata,
+            // Owners of a paste in the trash are offered to restore it.
+            Err(db::Error::NotFound) => {
+                let owner_uids = uids.as_ref().map_or(&[][..], |Uids(uids)| uids.as_slice());
+
+                let Some(trashed) = db.get_trashed(key.id).await.ok().filter(|trashed| {
+                    trashed
+                        .uid
+                        .is_some_and(|owner_uid| owner_uids.contains(&owner_uid))
+                }) else {
+                    return Err(db::Error::NotFound.into());
+                };
+
+                if !restore {
+                    return Ok(RestoreConfirmation {
+                        page: page.clone(),
+                        theme: theme.clone(),
+                        lang,
+                        id,
+                        title: trashed.title,
+                    }
+                    .into_response());
+                }
+
+                db.restore_for(key.id, owner_uids).await?;
+                db.get_metadata(key.id).await?
+            }
             Err(err) => return Err(err.into()),
         };
 
-        if metadata.must_be_deleted && !confirmed {
+        let key = key.or_extension(metadata.extension.clone());
+
+        if let Some(remaining_views) = metadata.remaining_views()
+            && !confirmed
+        {
             return Ok(BurnConfirmation {
                 page: page.clone(),
                 theme: theme.clone(),
                 lang,
                 id,
                 title: metadata.title.clone(),
+                remaining_views,
             }
             .into_response());
         }
 
-        let (data, is_available) = match db.get(key.id, password).await {
-            Ok(Entry::Regular(data)) => (data, true),
-            Ok(Entry::Burned(data)) => (data, false),
+        let result = match revision.rev {
+            Some(rev) => db
+                .get_revision(key.id, rev, password)
+                .await
+                .map(|data| (data, true)),
+            None => db.get(key.id, password).await.map(|entry| match entry {
+                Entry::Regular(data) => (data, true),
+                Entry::Burned(data) => (data, false),
+            }),
+        };
+
+        let (data, is_available) = match result {
+            Ok(result) => result,
             Err(db::Error::NoPassword) => {
+                let id = match revision.rev {
+                    Some(rev) => format!("{id}?rev={rev}"),
+                    None => id,
+                };
+
                 return Ok(PasswordInput {
                     page: page.clone(),
                     theme: theme.clone(),
@@ -135,37 +296,70 @@ pub async fn get<E>(
             Err(err) => return Err(err.into()),
         };
 
+        let files = data.files();
         let Data { text, metadata } = data;
+        let stats = Stats::from(&metadata);
         let Metadata {
             uid: owner_uid,
             title,
             expiration,
+            revision: latest_revision,
+            parent,
             ..
         } = metadata;
+        let selected_revision = revision.rev.unwrap_or(latest_revision);
+        let is_latest = selected_revision == latest_revision;
 
         let can_delete = match (uids, owner_uid) {
             (Some(Uids(uids)), Some(owner_uid)) => uids.contains(&owner_uid),
             _ => false,
         };
 
-        let html = if let Some(html) = cache.get(&key, Mode::Source) {
-            tracing::trace!(?key, "found cached item");
-            html.into_inner()
-        } else {
-            let ext = key.ext.clone();
-            let highlighter = highlighter.clone();
-            let html =
-                tokio::task::spawn_blocking(move || highlighter.highlight(text, ext)).await??;
-
-            if is_available && no_password {
-                tracing::trace!(?key, "cache item");
-                cache.put(&key, Mode::Source, html.clone());
-            }
+        let can_cache = is_available && no_password && is_latest;
 
-            html.into_inner()
+        let (html, files) = match files {
+            Some(files) => {
+                let mut views = Vec::with_capacity(files.len());
+
+                for (index, file) in files.into_iter().enumerate() {
+                    let ext = file.extension().map(str::to_string);
+                    let html = highlight(
+                        &cache,
+                        &highlighter,
+                        &key,
+                        Mode::File(index),
+                        is_latest,
+                        can_cache,
+                        file.text,
+                        ext,
+                    )
+                    .await?;
+                    views.push(FileView {
+                        name: file.name,
+                        html,
+                    });
+                }
+
+                (String::new(), views)
+            }
+            None => {
+                let ext = key.ext.clone();
+                let html = highlight(
+                    &cache,
+                    &highlighter,
+                    &key,
+                    Mode::Source,
+                    is_latest,
+                    can_cache,
+                    text,
+                    ext,
+                )
+                .await?;
+                (html, Vec::new())
+            }
         };
 
-        let is_markdown = is_markdown_ext(key.ext.as_deref());
+        let is_markdown = files.is_empty() && is_markdown_ext(key.ext.as_deref());
         let paste = Paste {
             page: page.clone(),
             key,
@@ -175,8 +369,13 @@ pub async fn get<E>(
             is_available,
             expiration,
             html,
+            files,
             title,
             is_markdown,
+            revision: latest_revision,
+            selected_revision,
+            parent,
+            stats,
         };
 
         Ok(paste.into_response())
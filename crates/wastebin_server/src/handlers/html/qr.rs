@@ -6,13 +6,14 @@ use url::Url;
 
 use crate::cache::Key;
 use crate::handlers::extract::{Theme, Uids};
-use crate::handlers::html::paste::is_markdown_ext;
+use crate::handlers::html::paste::{Stats, is_markdown_ext};
 use crate::handlers::html::{ErrorResponse, make_error};
 use crate::i18n::Lang;
 use crate::{Error, Page};
 use wastebin_core::db::Database;
 use wastebin_core::db::read::Metadata;
 use wastebin_core::expiration::Expiration;
+use wastebin_core::id::Id;
 
 /// GET handler for a QR page.
 pub async fn get(
@@ -34,12 +35,16 @@ pub async fn get(
                 .map_err(Error::from)??
         };
 
+        let metadata = db.get_metadata(key.id).await?;
+        let key = key.or_extension(metadata.extension.clone());
+        let stats = Stats::from(&metadata);
         let Metadata {
             uid: owner_uid,
             title,
             expiration,
+            parent,
             ..
-        } = db.get_metadata(key.id).await?;
+        } = metadata;
 
         let can_delete = match (uids, owner_uid) {
             (Some(Uids(uids)), Some(owner_uid)) => uids.contains(&owner_uid),
@@ -58,6 +63,8 @@ pub async fn get(
             code,
             title,
             expiration,
+            parent,
+            stats,
             is_markdown,
         })
     }
@@ -79,6 +86,9 @@ pub(crate) struct Qr {
     code: qrcodegen::QrCode,
     title: Option<String>,
     expiration: Option<Expiration>,
+    /// Paste this one was forked from.
+    parent: Option<Id>,
+    stats: Stats,
 }
 
 impl Qr {
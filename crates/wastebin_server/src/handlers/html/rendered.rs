@@ -5,7 +5,7 @@ use axum::response::{IntoResponse, Response};
 
 use crate::cache::{Key, Mode};
 use crate::handlers::extract::{Theme, Uids};
-use crate::handlers::html::paste::PasswordForm;
+use crate::handlers::html::paste::{PasswordForm, Stats};
 use crate::handlers::html::{ErrorResponse, PasswordInput, make_error};
 use crate::i18n::Lang;
 use crate::{Cache, Database, Highlighter, Page};
@@ -13,6 +13,7 @@ use wastebin_core::crypto::Password;
 use wastebin_core::db;
 use wastebin_core::db::read::{Data, Entry, Metadata};
 use wastebin_core::expiration::Expiration;
+use wastebin_core::id::Id;
 use wastebin_highlight::markdown;
 
 /// Page showing a Markdown paste rendered as HTML.
@@ -30,6 +31,9 @@ pub(crate) struct Rendered {
     expiration: Option<Expiration>,
     html: String,
     title: Option<String>,
+    /// Paste this one was forked from.
+    parent: Option<Id>,
+    stats: Stats,
 }
 
 #[expect(clippy::too_many_arguments)]
@@ -67,10 +71,13 @@ pub async fn get<E>(
         };
 
         let Data { text, metadata } = data;
+        let key = key.or_extension(metadata.extension.clone());
+        let stats = Stats::from(&metadata);
         let Metadata {
             uid: owner_uid,
             title,
             expiration,
+            parent,
             ..
         } = metadata;
 
@@ -107,6 +114,8 @@ pub async fn get<E>(
             expiration,
             html,
             title,
+            parent,
+            stats,
         };
 
         Ok(rendered.into_response())
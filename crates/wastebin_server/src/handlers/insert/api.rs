@@ -5,23 +5,42 @@ use axum::extract::State;
 use axum_extra::extract::cookie::Key;
 use serde::{Deserialize, Serialize};
 
+use crate::Page;
 use crate::errors::{Error, JsonErrorResponse};
-use crate::handlers::extract::{sign_owner_token, verify_owner_token};
+use crate::handlers::extract::sign_owner_token;
+use crate::handlers::insert::{Requested, prepare, text_or_files};
 use wastebin_core::db::{Database, write};
+use wastebin_core::files::File;
 
 #[derive(Debug, Default, Serialize, Deserialize)]
 pub(crate) struct Entry {
+    #[serde(default)]
     pub text: String,
+    /// Files of a multi-file paste, given instead of `text`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<File>,
     pub extension: Option<String>,
     pub expires: Option<NonZeroU32>,
+    /// Point in time of expiration as RFC 3339 timestamp, given instead of `expires`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<String>,
+    /// Delete after being viewed this many times.
+    pub max_views: Option<NonZeroU32>,
+    /// Same as `max_views` set to 1, kept for compatibility.
     pub burn_after_reading: Option<bool>,
     pub password: Option<String>,
     pub title: Option<String>,
+    /// Identifier of the paste this one was forked from.
+    #[serde(default)]
+    pub parent: Option<String>,
     /// Optional server-signed owner token from a previous insert. When present
     /// and valid, the new paste reuses that token's uid instead of minting a
     /// new one, so all of a client's pastes share a single deletion identity.
     #[serde(default)]
     pub owner: Option<String>,
+    /// Custom identifier instead of a random one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slug: Option<String>,
 }
 
 #[derive(Deserialize, Serialize)]
@@ -38,10 +57,19 @@ impl From<Entry> for write::Entry {
             text: entry.text,
             extension: entry.extension,
             expires: entry.expires,
-            burn_after_reading: entry.burn_after_reading,
+            expires_at: None,
+            max_views: entry.max_views.or_else(|| {
+                entry
+                    .burn_after_reading
+                    .is_some_and(|burn| burn)
+                    .then_some(NonZeroU32::MIN)
+            }),
             uid: None,
             password: entry.password,
             title: entry.title,
+            parent: None,
+            slug: None,
+            multi_file: false,
         }
     }
 }
@@ -49,23 +77,25 @@ impl From<Entry> for write::Entry {
 pub async fn post(
     State(db): State<Database>,
     State(key): State<Key>,
-    Json(entry): Json<Entry>,
+    State(page): State<Page>,
+    Json(mut entry): Json<Entry>,
 ) -> Result<Json<RedirectResponse>, JsonErrorResponse> {
-    // Reuse the uid encoded in a valid `owner` token so a client can group its
-    // pastes under one identity; otherwise mint a fresh uid. A raw uid is never
-    // trusted — only a server-signed token is accepted, and an invalid one falls
-    // back to minting rather than failing the request.
-    let uid = match entry
-        .owner
-        .as_deref()
-        .and_then(|token| verify_owner_token(&key, token))
-    {
-        Some(uid) => uid,
-        None => db.next_uid().await.map_err(Error::Database)?,
+    let files = std::mem::take(&mut entry.files);
+
+    let slug = entry.slug.take();
+    let expires_at = entry.expires_at.take();
+    let parent = entry.parent.take();
+    let owner = entry.owner.take();
+    let requested = Requested {
+        slug: slug.as_deref(),
+        expires_at: expires_at.as_deref(),
+        parent: parent.as_deref(),
+        owner: owner.as_deref(),
     };
 
     let mut entry: write::Entry = entry.into();
-    entry.uid = Some(uid);
+    text_or_files(&mut entry, &files)?;
+    let uid = prepare(&db, &key, &page, &mut entry, requested).await?;
 
     let (id, entry) = db.insert(entry).await.map_err(Error::Database)?;
     let path = format!("/{}", id.to_url_path(&entry));
@@ -76,7 +106,9 @@ pub async fn post(
 
 #[cfg(test)]
 mod tests {
+    use crate::errors::JsonError;
     use crate::handlers::extract::PASSWORD_HEADER_NAME;
+    use crate::handlers::metadata::MetadataResponse;
     use crate::test_helpers::{Client, StoreCookies};
     use reqwest::StatusCode;
     use wastebin_core::db::write::Entry;
@@ -102,6 +134,54 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn insert_burn_after_reading() -> Result<(), Box<dyn std::error::Error>> {
+        let client = Client::new(StoreCookies(false)).await;
+
+        let entry = super::Entry {
+            text: "FooBarBaz".to_string(),
+            burn_after_reading: Some(true),
+            ..Default::default()
+        };
+
+        let res = client.post_json().json(&entry).send().await?;
+        let payload = res.json::<super::RedirectResponse>().await?;
+
+        let res = client.get(&format!("/meta{}", payload.path)).send().await?;
+        let metadata = res.json::<MetadataResponse>().await?;
+        assert_eq!(metadata.max_views, Some(1));
+
+        let res = client.get(&format!("/raw{}", payload.path)).send().await?;
+        assert_eq!(res.text().await?, "FooBarBaz");
+
+        let res = client.get(&format!("/raw{}", payload.path)).send().await?;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn insert_quota_exceeded() -> Result<(), Box<dyn std::error::Error>> {
+        let config = wastebin_core::db::Config {
+            max_storage: std::num::NonZeroU64::new(16),
+            ..Default::default()
+        };
+        let client = Client::with_config(StoreCookies(false), config).await;
+
+        let entry = Entry {
+            text: "FooBarBaz".repeat(10),
+            ..Default::default()
+        };
+
+        let res = client.post_json().json(&entry).send().await?;
+        assert_eq!(res.status(), StatusCode::INSUFFICIENT_STORAGE);
+
+        let payload = res.json::<crate::errors::JsonError>().await?;
+        assert_eq!(payload.message, "database error: storage quota exceeded");
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn insert_fail() -> Result<(), Box<dyn std::error::Error>> {
         let client = Client::new(StoreCookies(false)).await;
@@ -273,6 +353,71 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn insert_fork() -> Result<(), Box<dyn std::error::Error>> {
+        let client = Client::new(StoreCookies(false)).await;
+
+        let parent = client
+            .post_json()
+            .json(&super::Entry {
+                text: "FooBarBaz".to_string(),
+                ..Default::default()
+            })
+            .send()
+            .await?
+            .json::<super::RedirectResponse>()
+            .await?;
+
+        let parent_id = parent.path.trim_start_matches('/').to_string();
+
+        let res = client
+            .post_json()
+            .json(&super::Entry {
+                text: "FooBarBaz, forked".to_string(),
+                parent: Some(parent_id.clone()),
+                ..Default::default()
+            })
+            .send()
+            .await?;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let fork = res.json::<super::RedirectResponse>().await?;
+        let res = client
+            .get(&fork.path)
+            .header(reqwest::header::ACCEPT, "text/html; charset=utf-8")
+            .send()
+            .await?;
+        assert!(
+            res.text()
+                .await?
+                .contains(&format!("href=\"/{parent_id}\""))
+        );
+
+        let res = client
+            .post_json()
+            .json(&super::Entry {
+                text: "FooBarBaz".to_string(),
+                parent: Some("#invalid".to_string()),
+                ..Default::default()
+            })
+            .send()
+            .await?;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+        let res = client
+            .post_json()
+            .json(&super::Entry {
+                text: "FooBarBaz".to_string(),
+                parent: Some("000000".to_string()),
+                ..Default::default()
+            })
+            .send()
+            .await?;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn insert_encrypted() -> Result<(), Box<dyn std::error::Error>> {
         let client = Client::new(StoreCookies(false)).await;
@@ -300,4 +445,96 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn insert_expires_at() -> Result<(), Box<dyn std::error::Error>> {
+        let client = Client::new(StoreCookies(false)).await;
+
+        let expires_at = (time::OffsetDateTime::now_utc() + time::Duration::hours(2))
+            .format(&time::format_description::well_known::Rfc3339)?;
+
+        let entry = super::Entry {
+            text: "FooBarBaz".to_string(),
+            expires_at: Some(expires_at.clone()),
+            ..Default::default()
+        };
+
+        let res = client.post_json().json(&entry).send().await?;
+        assert_eq!(res.status(), StatusCode::OK);
+        let payload = res.json::<super::RedirectResponse>().await?;
+
+        let res = client.get(&format!("/meta{}", payload.path)).send().await?;
+        let metadata = res.json::<MetadataResponse>().await?;
+        assert!(
+            metadata
+                .expires_in
+                .is_some_and(|secs| (7190..=7200).contains(&secs))
+        );
+
+        for entry in [
+            super::Entry {
+                expires_at: Some(String::from("2000-01-01T00:00:00Z")),
+                ..Default::default()
+            },
+            super::Entry {
+                expires_at: Some(String::from("tomorrow")),
+                ..Default::default()
+            },
+            super::Entry {
+                expires: std::num::NonZeroU32::new(60),
+                expires_at: Some(expires_at.clone()),
+                ..Default::default()
+            },
+        ] {
+            let res = client.post_json().json(&entry).send().await?;
+            assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn insert_slug() -> Result<(), Box<dyn std::error::Error>> {
+        let client = Client::new(StoreCookies(false)).await;
+
+        let entry = super::Entry {
+            text: "FooBarBaz".to_string(),
+            extension: Some("md".to_string()),
+            slug: Some("deploy-notes".to_string()),
+            ..Default::default()
+        };
+
+        let res = client.post_json().json(&entry).send().await?;
+        assert_eq!(res.status(), StatusCode::OK);
+        let payload = res.json::<super::RedirectResponse>().await?;
+        assert_eq!(payload.path, "/deploy-notes.md");
+
+        let res = client.get("/raw/deploy-notes").send().await?;
+        assert_eq!(res.text().await?, "FooBarBaz");
+
+        let res = client.post_json().json(&entry).send().await?;
+        assert_eq!(res.status(), StatusCode::CONFLICT);
+        let payload = res.json::<JsonError>().await?;
+        assert_eq!(payload.code.as_deref(), Some("slug_taken"));
+
+        for slug in ["deploy", "deploy notes", ""] {
+            let entry = super::Entry {
+                text: "FooBarBaz".to_string(),
+                slug: Some(slug.to_string()),
+                ..Default::default()
+            };
+
+            let res = client.post_json().json(&entry).send().await?;
+
+            if slug.is_empty() {
+                assert_eq!(res.status(), StatusCode::OK);
+            } else {
+                assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+                let payload = res.json::<JsonError>().await?;
+                assert_eq!(payload.code.as_deref(), Some("invalid_slug"));
+            }
+        }
+
+        Ok(())
+    }
 }
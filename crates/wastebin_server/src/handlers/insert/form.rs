@@ -1,7 +1,8 @@
 use std::num::NonZeroU32;
 
-use axum::extract::{Form, State};
+use axum::extract::State;
 use axum::response::{IntoResponse, Redirect};
+use axum_extra::extract::Form;
 use axum_extra::extract::cookie::SignedCookieJar;
 use serde::{Deserialize, Serialize};
 
@@ -9,23 +10,99 @@ use crate::Page;
 use crate::handlers::cookie;
 use crate::handlers::extract::{Theme, Uids, serialize_uids};
 use crate::handlers::html::make_error;
+use crate::handlers::insert::{requested_expiration, requested_parent, requested_slug};
 use crate::i18n::Lang;
 use wastebin_core::db::{Database, write};
+use wastebin_core::files::File;
 
 #[derive(Debug, Default, Serialize, Deserialize)]
 pub(crate) struct Entry {
     pub text: String,
     pub extension: Option<String>,
     pub expires: Option<String>,
+    /// Point in time of expiration as RFC 3339 timestamp, set from the date/time picker.
+    #[serde(
+        default,
+        rename = "expires-at",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub expires_at: Option<String>,
     pub password: String,
     pub title: String,
+    #[serde(rename = "max-views")]
+    pub max_views: Option<String>,
+    /// Same as `max-views` set to 1, kept for compatibility.
     #[serde(rename = "burn-after-reading")]
     pub burn_after_reading: Option<String>,
+    /// Identifier of the paste this one was forked from.
+    pub parent: Option<String>,
+    /// Custom identifier instead of a random one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slug: Option<String>,
+    /// Name of the file in `text` if more files are added.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filename: Option<String>,
+    /// Names of additional files.
+    #[serde(default, rename = "file-name", skip_serializing_if = "Vec::is_empty")]
+    pub file_names: Vec<String>,
+    /// Content of additional files.
+    #[serde(default, rename = "file-text", skip_serializing_if = "Vec::is_empty")]
+    pub file_texts: Vec<String>,
+}
+
+/// Pair up file `names` and `texts` of the editor panes, skipping empty panes. Unnamed files are
+/// named after their position and get `extension` if given.
+pub(crate) fn collect_files(
+    names: impl IntoIterator<Item = String>,
+    texts: impl IntoIterator<Item = String>,
+    extension: Option<&str>,
+) -> Vec<File> {
+    names
+        .into_iter()
+        .chain(std::iter::repeat(String::new()))
+        .zip(texts)
+        .enumerate()
+        .filter(|(_, (name, text))| !name.is_empty() || !text.is_empty())
+        .map(|(index, (name, text))| {
+            let name = if name.is_empty() {
+                match extension {
+                    Some(extension) => format!("file{}.{extension}", index + 1),
+                    None => format!("file{}", index + 1),
+                }
+            } else {
+                name
+            };
+
+            File { name, text }
+        })
+        .collect()
+}
+
+impl Entry {
+    /// Take the text and the additional files as files of a multi-file paste, or `None` if no
+    /// additional files were added.
+    fn take_files(&mut self) -> Option<Vec<File>> {
+        if self.file_texts.is_empty() {
+            return None;
+        }
+
+        let names = std::iter::once(self.filename.take().unwrap_or_default())
+            .chain(std::mem::take(&mut self.file_names));
+        let texts = std::iter::once(std::mem::take(&mut self.text))
+            .chain(std::mem::take(&mut self.file_texts));
+
+        Some(collect_files(names, texts, self.extension.as_deref()))
+    }
 }
 
 impl From<Entry> for write::Entry {
     fn from(entry: Entry) -> Self {
-        let burn_after_reading = entry.burn_after_reading.map(|s| s == "on");
+        let max_views = entry
+            .max_views
+            .and_then(|max_views| max_views.parse::<NonZeroU32>().ok())
+            .or_else(|| {
+                (entry.burn_after_reading.as_deref() == Some("on")).then_some(NonZeroU32::MIN)
+            });
         let password = (!entry.password.is_empty()).then_some(entry.password);
         let title = (!entry.title.is_empty()).then_some(entry.title);
         let expires = entry
@@ -36,10 +113,14 @@ impl From<Entry> for write::Entry {
             text: entry.text,
             extension: entry.extension,
             expires,
-            burn_after_reading,
+            expires_at: None,
+            max_views,
             uid: None,
             password,
             title,
+            parent: None,
+            slug: None,
+            multi_file: false,
         }
     }
 }
@@ -53,11 +134,17 @@ pub async fn post<E: std::fmt::Debug>(
     lang: Lang,
     entry: Result<Form<Entry>, E>,
 ) -> Result<(SignedCookieJar, Redirect), impl IntoResponse> {
-    let Ok(Form(entry)) = entry else {
+    let Ok(Form(mut entry)) = entry else {
         return Err(make_error(crate::Error::MalformedForm, page, theme, lang));
     };
 
     async {
+        let files = entry.take_files();
+        let slug = requested_slug(&page, entry.slug.as_deref())?;
+        let expires_at = requested_expiration(&page, entry.expires_at.as_deref())?;
+
+        let parent = requested_parent(&db, entry.parent.as_deref()).await?;
+
         // Pick the existing primary uid (first in the cookie list) or mint a new one.
         // Re-set the cookie with the full list unchanged so claimed uids survive.
         let mut uids = uids.map(|Uids(uids)| uids).unwrap_or_default();
@@ -72,12 +159,19 @@ pub async fn post<E: std::fmt::Debug>(
 
         let mut entry: write::Entry = entry.into();
         entry.uid = Some(primary);
+        entry.parent = parent;
+        entry.slug = slug;
+        entry.expires_at = expires_at;
+
+        if let Some(files) = files {
+            entry.set_files(&files)?;
+        }
 
         let (id, entry) = db.insert(entry).await?;
 
         let url = {
             let url_path = id.to_url_path(&entry);
-            if entry.burn_after_reading.unwrap_or(false) {
+            if entry.max_views.is_some() {
                 format!("/burn/{url_path}")
             } else {
                 format!("/{url_path}")
@@ -174,4 +268,47 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn insert_files() -> Result<(), Box<dyn std::error::Error>> {
+        let client = Client::new(StoreCookies(false)).await;
+
+        let res = client
+            .post_form()
+            .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+            .body(
+                "text=fn+main%28%29+%7B%7D&title=&password=&filename=main.rs&extension=rs\
+                 &file-name=&file-text=%5Bpackage%5D\
+                 &file-name=&file-text=",
+            )
+            .send()
+            .await?;
+        assert_eq!(res.status(), StatusCode::SEE_OTHER);
+
+        let location = res.headers().get("location").unwrap().to_str()?;
+        let res = client.get(&format!("/raw{location}")).send().await?;
+        assert_eq!(res.status(), StatusCode::MULTIPLE_CHOICES);
+        let urls = res.text().await?;
+        let names = urls
+            .lines()
+            .filter_map(|url| url.rsplit('/').next())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["main.rs", "file2.rs"]);
+
+        let res = client
+            .get(&format!("/raw{location}/file2.rs"))
+            .send()
+            .await?;
+        assert_eq!(res.text().await?, "[package]");
+
+        let res = client
+            .post_form()
+            .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+            .body("text=foo&title=&password=&filename=..&file-text=bar")
+            .send()
+            .await?;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+        Ok(())
+    }
 }
@@ -1,2 +1,229 @@
 pub mod api;
 pub mod form;
+pub mod text;
+
+use std::time::SystemTime;
+
+use axum::extract::{Request, State};
+use axum::handler::Handler;
+use axum::http::header::CONTENT_TYPE;
+use axum::response::Response;
+use axum_extra::extract::cookie::Key;
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+
+use crate::AppState;
+use crate::errors::Error;
+use crate::handlers::extract::verify_owner_token;
+use crate::page::Page;
+use wastebin_core::db::{self, Database, write};
+use wastebin_core::files::File;
+use wastebin_core::id::{Id, Slug};
+
+/// Options of a new paste that are checked before insertion.
+#[derive(Default)]
+pub(crate) struct Requested<'a> {
+    pub slug: Option<&'a str>,
+    pub expires_at: Option<&'a str>,
+    pub parent: Option<&'a str>,
+    pub owner: Option<&'a str>,
+}
+
+/// POST handler for new pastes. Bodies with a `text/*` content type are the plain content of the
+/// paste, see [`text::post`], anything else is handled by [`api::post`].
+pub async fn post(State(state): State<AppState>, request: Request) -> Response {
+    let text = request
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<mime::Mime>().ok())
+        .is_some_and(|mime| mime.type_() == mime::TEXT);
+
+    if text {
+        text::post.call(request, state).await
+    } else {
+        api::post.call(request, state).await
+    }
+}
+
+/// Check the `requested` options against the rules of `page` and set them on `entry`. The uid of
+/// the entry is taken from a valid owner token or newly generated and returned.
+pub(crate) async fn prepare(
+    db: &Database,
+    key: &Key,
+    page: &Page,
+    entry: &mut write::Entry,
+    requested: Requested<'_>,
+) -> Result<i64, Error> {
+    entry.slug = requested_slug(page, requested.slug)?;
+
+    if entry.expires.is_some() && requested.expires_at.is_some() {
+        return Err(Error::ExpiresAndExpiresAt);
+    }
+
+    entry.expires_at = requested_expiration(page, requested.expires_at)?;
+    entry.parent = requested_parent(db, requested.parent).await?;
+
+    // Reuse the uid encoded in a valid `owner` token so a client can group its
+    // pastes under one identity; otherwise mint a fresh uid. A raw uid is never
+    // trusted — only a server-signed token is accepted, and an invalid one falls
+    // back to minting rather than failing the request.
+    let uid = match requested
+        .owner
+        .and_then(|token| verify_owner_token(key, token))
+    {
+        Some(uid) => uid,
+        None => db.next_uid().await?,
+    };
+
+    entry.uid = Some(uid);
+
+    Ok(uid)
+}
+
+/// Make `entry` a multi-file paste of `files` unless none were given. A paste's content is either
+/// its text or its files, so both must not be given at once.
+pub(crate) fn text_or_files(entry: &mut write::Entry, files: &[File]) -> Result<(), Error> {
+    match (entry.text.is_empty(), files.is_empty()) {
+        (_, true) => Ok(()),
+        (true, false) => Ok(entry.set_files(files)?),
+        (false, false) => Err(Error::TextAndFiles),
+    }
+}
+
+/// Validate the custom identifier `slug` against the rules of `page`. An empty slug means that no
+/// custom identifier was requested.
+pub(crate) fn requested_slug(page: &Page, slug: Option<&str>) -> Result<Option<Slug>, Error> {
+    let Some(slug) = slug.filter(|slug| !slug.is_empty()) else {
+        return Ok(None);
+    };
+
+    let rules = page.slugs.as_ref().ok_or(Error::SlugsDisabled)?;
+
+    if !(rules.min_length..=rules.max_length).contains(&slug.len()) {
+        return Err(Error::SlugLength(rules.min_length, rules.max_length));
+    }
+
+    if let Some(chars) = &rules.chars
+        && !slug.chars().all(|c| chars.contains(c))
+    {
+        return Err(Error::SlugChars(chars.clone()));
+    }
+
+    Ok(Some(slug.parse()?))
+}
+
+/// Parse the RFC 3339 timestamp `expires_at` and check that it lies between now and the longest
+/// expiration of `page`. An empty timestamp means that no point in time was requested.
+pub(crate) fn requested_expiration(
+    page: &Page,
+    expires_at: Option<&str>,
+) -> Result<Option<SystemTime>, Error> {
+    let Some(expires_at) = expires_at.filter(|expires_at| !expires_at.is_empty()) else {
+        return Ok(None);
+    };
+
+    let expires_at = SystemTime::from(OffsetDateTime::parse(expires_at, &Rfc3339)?);
+    let now = SystemTime::now();
+
+    if expires_at <= now {
+        return Err(Error::ExpiresAtPast);
+    }
+
+    if let Some(max) = page.max_expiration()
+        && now
+            .checked_add(max.duration)
+            .is_some_and(|latest| expires_at > latest)
+    {
+        return Err(Error::ExpiresAtTooLate(max.clone()));
+    }
+
+    Ok(Some(expires_at))
+}
+
+/// Look up the paste `parent` a new paste is forked from. An empty identifier means that the paste
+/// is not a fork.
+pub(crate) async fn requested_parent(
+    db: &Database,
+    parent: Option<&str>,
+) -> Result<Option<Id>, Error> {
+    let Some(parent) = parent.filter(|parent| !parent.is_empty()) else {
+        return Ok(None);
+    };
+
+    let parent = parent.parse::<Id>()?;
+
+    match db.get_metadata(parent).await {
+        Ok(_) => Ok(Some(parent)),
+        Err(db::Error::NotFound) => Err(Error::ParentNotFound(parent)),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use time::OffsetDateTime;
+    use time::format_description::well_known::Rfc3339;
+
+    use super::*;
+
+    fn page(expirations: &str) -> Page {
+        Page::new(
+            String::from("test"),
+            url::Url::parse("https://localhost:8888").unwrap(),
+            wastebin_highlight::Theme::Ayu,
+            expirations.parse().unwrap(),
+            1024,
+            1024,
+            None,
+        )
+    }
+
+    fn in_secs(secs: i64) -> String {
+        (OffsetDateTime::now_utc() + time::Duration::seconds(secs))
+            .format(&Rfc3339)
+            .unwrap()
+    }
+
+    #[test]
+    fn expiration_within_maximum() {
+        let page = page("10m,1h=d");
+
+        let expires_at = requested_expiration(&page, Some(&in_secs(1800))).unwrap();
+        let remaining = expires_at
+            .unwrap()
+            .duration_since(SystemTime::now())
+            .unwrap();
+        assert!(remaining <= Duration::from_secs(1800));
+        assert!(remaining >= Duration::from_secs(1790));
+
+        assert!(requested_expiration(&page, None).unwrap().is_none());
+        assert!(requested_expiration(&page, Some("")).unwrap().is_none());
+
+        assert!(matches!(
+            requested_expiration(&page, Some(&in_secs(7200))),
+            Err(Error::ExpiresAtTooLate(_))
+        ));
+        assert!(matches!(
+            requested_expiration(&page, Some(&in_secs(-60))),
+            Err(Error::ExpiresAtPast)
+        ));
+        assert!(matches!(
+            requested_expiration(&page, Some("next friday")),
+            Err(Error::ExpiresAt(_))
+        ));
+    }
+
+    #[test]
+    fn expiration_without_maximum() {
+        let page = page("0=d,1h");
+
+        assert!(
+            requested_expiration(&page, Some("2999-12-31T18:00:00+01:00"))
+                .unwrap()
+                .is_some()
+        );
+    }
+}
@@ -0,0 +1,217 @@
+use std::num::NonZeroU32;
+
+use axum::Json;
+use axum::body::Body;
+use axum::extract::{Query, State};
+use axum::http::HeaderMap;
+use axum_extra::extract::cookie::Key;
+use futures::TryStreamExt;
+use http_body_util::{BodyDataStream, LengthLimitError, Limited};
+use serde::{Deserialize, Serialize};
+use tokio_util::io::StreamReader;
+
+use crate::Page;
+use crate::errors::{Error, JsonErrorResponse};
+use crate::handlers::extract::{PASSWORD_HEADER_NAME, sign_owner_token};
+use crate::handlers::insert::api::RedirectResponse;
+use crate::handlers::insert::{Requested, prepare};
+use wastebin_core::db::{self, Database, write};
+
+/// Options of a plain text paste given as query parameters, see [`super::api::Entry`].
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(crate) struct Params {
+    pub extension: Option<String>,
+    pub title: Option<String>,
+    pub expires: Option<NonZeroU32>,
+    pub expires_at: Option<String>,
+    pub max_views: Option<NonZeroU32>,
+    pub parent: Option<String>,
+    pub owner: Option<String>,
+    pub slug: Option<String>,
+}
+
+/// POST handler for the request body as content of a new paste. Unless the paste is encrypted
+/// with the password given in the `wastebin-password` header, the body is streamed into the
+/// database without being held in memory at once and may be up to `max_stream_size` bytes large.
+/// Content to encrypt is read at once and bounded by `max_body_size` like any other insert.
+pub async fn post(
+    State(db): State<Database>,
+    State(key): State<Key>,
+    State(page): State<Page>,
+    Query(params): Query<Params>,
+    headers: HeaderMap,
+    body: Body,
+) -> Result<Json<RedirectResponse>, JsonErrorResponse> {
+    let mut entry = write::Entry {
+        extension: params.extension,
+        title: params.title,
+        expires: params.expires,
+        max_views: params.max_views,
+        password: headers
+            .get(PASSWORD_HEADER_NAME)
+            .and_then(|value| value.to_str().ok())
+            .map(String::from),
+        ..Default::default()
+    };
+
+    let requested = Requested {
+        slug: params.slug.as_deref(),
+        expires_at: params.expires_at.as_deref(),
+        parent: params.parent.as_deref(),
+        owner: params.owner.as_deref(),
+    };
+
+    let uid = prepare(&db, &key, &page, &mut entry, requested).await?;
+
+    let limit = if entry.password.is_some() {
+        page.max_body_size
+    } else {
+        page.max_stream_size
+    };
+    let body = BodyDataStream::new(Limited::new(body, limit)).map_err(std::io::Error::other);
+
+    let (id, entry) = match db.insert_stream(entry, StreamReader::new(body)).await {
+        Ok(inserted) => inserted,
+        Err(db::Error::Read(err))
+            if err
+                .get_ref()
+                .is_some_and(|err| err.is::<LengthLimitError>()) =>
+        {
+            return Err(Error::BodyTooLarge(limit).into());
+        }
+        Err(err) => return Err(Error::Database(err).into()),
+    };
+
+    let path = format!("/{}", id.to_url_path(&entry));
+    let owner = sign_owner_token(&key, uid);
+
+    Ok(Json::from(RedirectResponse { path, owner }))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::handlers::extract::PASSWORD_HEADER_NAME;
+    use crate::handlers::insert::api::RedirectResponse;
+    use crate::test_helpers::{Client, StoreCookies};
+    use reqwest::StatusCode;
+    use reqwest::header::{CONTENT_DISPOSITION, CONTENT_TYPE};
+
+    #[tokio::test]
+    async fn insert_text() -> Result<(), Box<dyn std::error::Error>> {
+        let client = Client::new(StoreCookies(false)).await;
+        let text = "log line with €\n".repeat(20_000);
+
+        let res = client
+            .post("/")
+            .header(CONTENT_TYPE, "text/plain")
+            .query(&super::Params {
+                extension: Some(String::from("log")),
+                title: Some(String::from("build.log")),
+                ..Default::default()
+            })
+            .body(text.clone())
+            .send()
+            .await?;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let payload = res.json::<RedirectResponse>().await?;
+        assert!(payload.path.ends_with(".log"));
+        assert!(!payload.owner.is_empty());
+
+        let id = payload
+            .path
+            .trim_start_matches('/')
+            .trim_end_matches(".log");
+
+        let res = client.get(&format!("/raw/{id}")).send().await?;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(
+            res.headers().get(CONTENT_TYPE).unwrap(),
+            "text/plain; charset=utf-8"
+        );
+        assert_eq!(res.text().await?, text);
+
+        let res = client.get(&format!("/dl/{id}")).send().await?;
+        assert_eq!(
+            res.headers().get(CONTENT_DISPOSITION).unwrap(),
+            "attachment; filename*=UTF-8''build.log"
+        );
+        assert_eq!(res.text().await?, text);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn insert_text_encrypted() -> Result<(), Box<dyn std::error::Error>> {
+        let client = Client::new(StoreCookies(false)).await;
+
+        let res = client
+            .post("/")
+            .header(CONTENT_TYPE, "text/plain")
+            .header(PASSWORD_HEADER_NAME, "secret")
+            .body("FooBarBaz")
+            .send()
+            .await?;
+        assert_eq!(res.status(), StatusCode::OK);
+        let payload = res.json::<RedirectResponse>().await?;
+
+        let res = client
+            .get(&format!("/raw{}", payload.path))
+            .header(PASSWORD_HEADER_NAME, "secret")
+            .send()
+            .await?;
+        assert_eq!(res.text().await?, "FooBarBaz");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn insert_text_fail() -> Result<(), Box<dyn std::error::Error>> {
+        let client = Client::new(StoreCookies(false)).await;
+
+        let res = client
+            .post("/")
+            .header(CONTENT_TYPE, "text/plain")
+            .body(vec![b'a'; 5 * 1024 * 1024])
+            .send()
+            .await?;
+        assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        // Content to encrypt is held in memory and therefore bounded by the body size limit.
+        let res = client
+            .post("/")
+            .header(CONTENT_TYPE, "text/plain")
+            .header(PASSWORD_HEADER_NAME, "secret")
+            .body(vec![b'a'; 2 * 1024 * 1024])
+            .send()
+            .await?;
+        assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        let res = client
+            .post("/")
+            .header(CONTENT_TYPE, "text/plain")
+            .body(vec![b'a'; 2 * 1024 * 1024])
+            .send()
+            .await?;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let res = client
+            .post("/")
+            .header(CONTENT_TYPE, "text/plain")
+            .body(vec![b'a', 0xff])
+            .send()
+            .await?;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+        let res = client
+            .post("/")
+            .header(CONTENT_TYPE, "text/plain")
+            .query(&[("slug", "deploy notes")])
+            .body("FooBarBaz")
+            .send()
+            .await?;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,94 @@
+use axum::extract::{Path, State};
+use axum::response::Redirect;
+use axum_extra::extract::Form;
+use serde::{Deserialize, Serialize};
+
+use crate::cache::Cache;
+use crate::handlers::extract::{Theme, Uids};
+use crate::handlers::html::{ErrorResponse, make_error};
+use crate::i18n::Lang;
+use crate::{Database, Page};
+
+/// Title and extension as submitted from the paste page. Empty fields remove the value.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(crate) struct Update {
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub extension: String,
+}
+
+#[expect(clippy::too_many_arguments)]
+pub async fn post<E: std::fmt::Debug>(
+    Path(id): Path<String>,
+    State(db): State<Database>,
+    State(cache): State<Cache>,
+    State(page): State<Page>,
+    uids: Option<Uids>,
+    theme: Option<Theme>,
+    lang: Lang,
+    update: Result<Form<Update>, E>,
+) -> Result<Redirect, ErrorResponse> {
+    let Ok(Form(update)) = update else {
+        return Err(make_error(crate::Error::MalformedForm, page, theme, lang));
+    };
+
+    async {
+        let uids = uids.map(|Uids(uids)| uids).unwrap_or_default();
+        let path = super::update(
+            &db,
+            &cache,
+            &id,
+            &uids,
+            Some(update.title.trim().to_string()),
+            Some(update.extension.trim().to_string()),
+        )
+        .await?;
+        Ok(Redirect::to(&path))
+    }
+    .await
+    .map_err(|err| make_error(err, page.clone(), theme, lang))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::handlers::insert::form::Entry;
+    use crate::test_helpers::{Client, StoreCookies};
+    use reqwest::StatusCode;
+
+    #[tokio::test]
+    async fn edit_metadata_via_form() -> Result<(), Box<dyn std::error::Error>> {
+        let client = Client::new(StoreCookies(true)).await;
+
+        let data = Entry {
+            text: String::from("FooBarBaz"),
+            extension: Some(String::from("rs")),
+            ..Default::default()
+        };
+
+        let res = client.post_form().form(&data).send().await?;
+        let location = res.headers().get("location").unwrap().to_str()?.to_owned();
+        let id = location.trim_start_matches('/').split('.').next().unwrap();
+
+        let res = client
+            .post(&format!("/meta/{id}"))
+            .form(&super::Update {
+                title: String::from("notes"),
+                extension: String::from("md"),
+            })
+            .send()
+            .await?;
+        assert_eq!(res.status(), StatusCode::SEE_OTHER);
+        assert_eq!(res.headers().get("location").unwrap(), &format!("/{id}.md"));
+
+        let res = client
+            .get(&format!("/{id}"))
+            .header(reqwest::header::ACCEPT, "text/html; charset=utf-8")
+            .send()
+            .await?;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert!(res.text().await?.contains("notes"));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,272 @@
+pub mod form;
+
+use axum::Json;
+use axum::extract::{Path, State};
+use axum_extra::extract::cookie::Key as CookieKey;
+use serde::{Deserialize, Serialize};
+
+use crate::Database;
+use crate::cache::{Cache, Key};
+use crate::errors::{Error, JsonErrorResponse};
+use crate::handlers::extract::{Uids, verify_owner_token};
+use wastebin_core::db::read::Metadata;
+
+/// Paste metadata as returned by the API.
+#[derive(Deserialize, Serialize)]
+pub(crate) struct MetadataResponse {
+    pub title: Option<String>,
+    /// File extension given on insert or set by the owner
+    pub extension: Option<String>,
+    /// Creation datetime in UTC, unknown for old pastes
+    pub created: Option<String>,
+    /// Datetime of the previous access in UTC
+    pub accessed: Option<String>,
+    /// Seconds until the paste expires
+    pub expires_in: Option<u64>,
+    /// Number of views after which the paste is deleted
+    pub max_views: Option<u32>,
+    pub views: u32,
+    /// Content size in bytes
+    pub size: Option<u64>,
+    /// Stored size in bytes
+    pub compressed_size: Option<u64>,
+    pub revision: u32,
+    pub parent: Option<String>,
+}
+
+impl From<Metadata> for MetadataResponse {
+    fn from(metadata: Metadata) -> Self {
+        Self {
+            title: metadata.title,
+            extension: metadata.extension,
+            created: metadata.created,
+            accessed: metadata.accessed,
+            expires_in: metadata
+                .expiration
+                .map(|expiration| expiration.duration.as_secs()),
+            max_views: metadata.max_views,
+            views: metadata.views,
+            size: metadata.size,
+            compressed_size: metadata.compressed_size,
+            revision: metadata.revision,
+            parent: metadata.parent.map(|parent| parent.to_string()),
+        }
+    }
+}
+
+/// GET handler for the metadata of a paste. Does neither burn nor decrypt the paste.
+pub async fn get(
+    Path(id): Path<String>,
+    State(db): State<Database>,
+) -> Result<Json<MetadataResponse>, JsonErrorResponse> {
+    let key: Key = id.parse()?;
+    let metadata = db.get_metadata(key.id).await.map_err(Error::Database)?;
+    Ok(Json::from(MetadataResponse::from(metadata)))
+}
+
+/// Title and extension to change. Absent fields are kept and empty ones removed.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(crate) struct Update {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extension: Option<String>,
+    /// Optional owner token from the insert response. Authorizes the change in addition to the
+    /// uids of the `uid` cookie.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub(crate) struct UpdateResponse {
+    /// Path of the paste including its new extension.
+    pub path: String,
+}
+
+/// Return `true` if `extension` can be stored as the extension of a paste, i.e. it is empty or
+/// consists of ASCII letters, digits, `-`, `_` and `+` only.
+pub(crate) fn is_valid_extension(extension: &str) -> bool {
+    extension
+        .bytes()
+        .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'+'))
+}
+
+/// Change the title and extension of paste `id` if any of `uids` owns it and return its new path.
+pub(crate) async fn update(
+    db: &Database,
+    cache: &Cache,
+    id: &str,
+    uids: &[i64],
+    title: Option<String>,
+    extension: Option<String>,
+) -> Result<String, Error> {
+    let key: Key = id.parse()?;
+
+    if let Some(extension) = extension.as_deref()
+        && !is_valid_extension(extension)
+    {
+        return Err(Error::Extension(extension.to_string()));
+    }
+
+    db.edit_metadata(key.id, uids, title, extension).await?;
+    cache.remove(key.id);
+
+    let extension = db.get_metadata(key.id).await?.extension;
+    let key = Key {
+        id: key.id,
+        ext: extension,
+    };
+
+    Ok(format!("/{key}"))
+}
+
+/// PUT handler changing the title and extension of a paste.
+pub async fn put(
+    Path(id): Path<String>,
+    State(db): State<Database>,
+    State(cache): State<Cache>,
+    State(key): State<CookieKey>,
+    uids: Option<Uids>,
+    Json(update): Json<Update>,
+) -> Result<Json<UpdateResponse>, JsonErrorResponse> {
+    let mut uids = uids.map(|Uids(uids)| uids).unwrap_or_default();
+
+    if let Some(uid) = update
+        .owner
+        .as_deref()
+        .and_then(|token| verify_owner_token(&key, token))
+    {
+        uids.push(uid);
+    }
+
+    let path = self::update(&db, &cache, &id, &uids, update.title, update.extension).await?;
+
+    Ok(Json::from(UpdateResponse { path }))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::handlers::insert::api;
+    use crate::handlers::insert::form::Entry;
+    use crate::test_helpers::{Client, StoreCookies};
+    use reqwest::StatusCode;
+
+    #[tokio::test]
+    async fn metadata() -> Result<(), Box<dyn std::error::Error>> {
+        let client = Client::new(StoreCookies(false)).await;
+        let data = Entry {
+            text: String::from("FooBarBaz"),
+            title: String::from("Meta"),
+            ..Default::default()
+        };
+
+        let res = client.post_form().form(&data).send().await?;
+        let location = res.headers().get("location").unwrap().to_str()?.to_owned();
+
+        let res = client.get(&format!("/meta{location}")).send().await?;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let payload = res.json::<super::MetadataResponse>().await?;
+        assert_eq!(payload.title.as_deref(), Some("Meta"));
+        assert_eq!(payload.size, Some(9));
+        assert_eq!(payload.revision, 1);
+        assert!(payload.created.is_some());
+        assert!(payload.accessed.is_none());
+
+        client.get(&format!("/raw{location}")).send().await?;
+
+        let res = client.get(&format!("/meta{location}")).send().await?;
+        let payload = res.json::<super::MetadataResponse>().await?;
+        assert!(payload.accessed.is_some());
+
+        let res = client.get("/meta/000000").send().await?;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn edit_metadata() -> Result<(), Box<dyn std::error::Error>> {
+        let client = Client::new(StoreCookies(false)).await;
+
+        let entry = api::Entry {
+            text: String::from("fn main() {}"),
+            extension: Some(String::from("rs")),
+            ..Default::default()
+        };
+
+        let payload = client
+            .post_json()
+            .json(&entry)
+            .send()
+            .await?
+            .json::<api::RedirectResponse>()
+            .await?;
+
+        let old_path = payload.path.clone();
+        let id = old_path.trim_start_matches('/').split('.').next().unwrap();
+
+        let res = client
+            .put(&format!("/meta/{id}"))
+            .json(&super::Update {
+                title: Some(String::from("forbidden")),
+                ..Default::default()
+            })
+            .send()
+            .await?;
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+
+        let res = client
+            .put(&format!("/meta/{id}"))
+            .json(&super::Update {
+                extension: Some(String::from("../etc")),
+                owner: Some(payload.owner.clone()),
+                ..Default::default()
+            })
+            .send()
+            .await?;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+        let res = client
+            .put(&format!("/meta/{id}"))
+            .json(&super::Update {
+                title: Some(String::from("Hello")),
+                extension: Some(String::from("py")),
+                owner: Some(payload.owner.clone()),
+            })
+            .send()
+            .await?;
+        assert_eq!(res.status(), StatusCode::OK);
+        let new_path = res.json::<super::UpdateResponse>().await?.path;
+        assert_eq!(new_path, format!("/{id}.py"));
+
+        for path in [&old_path, &new_path, &format!("/{id}")] {
+            let res = client.get(path).send().await?;
+            assert_eq!(res.status(), StatusCode::OK);
+        }
+
+        let res = client.get(&format!("/meta/{id}")).send().await?;
+        let metadata = res.json::<super::MetadataResponse>().await?;
+        assert_eq!(metadata.title.as_deref(), Some("Hello"));
+        assert_eq!(metadata.extension.as_deref(), Some("py"));
+
+        let res = client
+            .put(&format!("/meta/{id}"))
+            .json(&super::Update {
+                extension: Some(String::new()),
+                owner: Some(payload.owner.clone()),
+                ..Default::default()
+            })
+            .send()
+            .await?;
+        let path = res.json::<super::UpdateResponse>().await?.path;
+        assert_eq!(path, format!("/{id}"));
+
+        let res = client.get(&format!("/meta/{id}")).send().await?;
+        let metadata = res.json::<super::MetadataResponse>().await?;
+        assert_eq!(metadata.title.as_deref(), Some("Hello"));
+        assert_eq!(metadata.extension, None);
+
+        Ok(())
+    }
+}
@@ -1,8 +1,11 @@
 pub mod delete;
 pub mod download;
+pub mod edit;
+pub mod expiration;
 pub mod extract;
 pub mod html;
 pub mod insert;
+pub mod metadata;
 pub mod raw;
 pub mod robots;
 pub mod theme;
@@ -1,17 +1,160 @@
-use axum::extract::{Path, State};
+use axum::body::Body;
+use axum::extract::{Path, Query, State};
+use axum::http::{HeaderMap, HeaderValue, StatusCode, header};
 use axum::response::{IntoResponse, Response};
+use tokio_util::io::ReaderStream;
 
 use crate::cache::Key;
-use crate::handlers::extract::{Password, Theme};
+use crate::handlers::extract::{Password, Revision, Theme};
 use crate::handlers::html::{ErrorResponse, PasswordInput, make_error};
 use crate::i18n::Lang;
 use crate::{Database, Page};
+use wastebin_core::crypto;
 use wastebin_core::db;
-use wastebin_core::db::read::Entry;
+use wastebin_core::db::read::{Data, Entry, Stream};
 
-/// GET handler for raw content of a paste.
+/// Fetch revision `rev` of `key` or the latest content if not given.
+async fn fetch(
+    db: &Database,
+    key: &Key,
+    rev: Option<u32>,
+    password: Option<crypto::Password>,
+) -> Result<Data, db::Error> {
+    match rev {
+        Some(rev) => db.get_revision(key.id, rev, password).await,
+        None => db
+            .get(key.id, password)
+            .await
+            .map(|(Entry::Regular(data) | Entry::Burned(data))| data),
+    }
+}
+
+/// Stream revision `rev` of `key` or the latest content if not given. Only the latest content is
+/// actually read in chunks, see [`Database::stream`].
+async fn fetch_stream(
+    db: &Database,
+    key: &Key,
+    rev: Option<u32>,
+    password: Option<crypto::Password>,
+) -> Result<Stream, db::Error> {
+    match rev {
+        Some(rev) => db.get_revision(key.id, rev, password).await.map(Into::into),
+        None => db.stream(key.id, password).await,
+    }
+}
+
+/// Check if `headers` accept a zstd encoded response, ignoring wildcards.
+fn accepts_zstd(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(header::ACCEPT_ENCODING)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|coding| {
+            let mut params = coding.split(';').map(str::trim);
+
+            params
+                .next()
+                .is_some_and(|name| name.eq_ignore_ascii_case("zstd"))
+                && params.all(|param| {
+                    param
+                        .strip_prefix("q=")
+                        .and_then(|q| q.parse::<f32>().ok())
+                        .is_none_or(|q| q > 0.0)
+                })
+        })
+}
+
+/// GET handler for raw content of a paste. Multi-file pastes have no single content, so clients
+/// are sent `300 Multiple Choices` with the URLs of the files, one per line. The latest content of
+/// unencrypted pastes is sent as stored with `Content-Encoding: zstd` if the client accepts it.
+#[expect(clippy::too_many_arguments)]
 pub async fn get(
     Path(id): Path<String>,
+    Query(revision): Query<Revision>,
+    State(db): State<Database>,
+    State(page): State<Page>,
+    theme: Option<Theme>,
+    lang: Lang,
+    headers: HeaderMap,
+    password: Option<Password>,
+) -> Result<Response, ErrorResponse> {
+    async {
+        let password = password.map(|Password(password)| password);
+        let key: Key = id.parse()?;
+
+        if revision.rev.is_none()
+            && accepts_zstd(&headers)
+            && let Some(stream) = db.stream_zstd(key.id).await?
+        {
+            return Ok((
+                [
+                    (
+                        header::CONTENT_TYPE,
+                        HeaderValue::from_static(mime::TEXT_PLAIN_UTF_8.as_ref()),
+                    ),
+                    (header::CONTENT_ENCODING, HeaderValue::from_static("zstd")),
+                    (header::VARY, HeaderValue::from_static("accept-encoding")),
+                ],
+                Body::from_stream(ReaderStream::new(stream.reader)),
+            )
+                .into_response());
+        }
+
+        match fetch_stream(&db, &key, revision.rev, password).await {
+            Ok(mut stream) => match stream.files().await? {
+                Some(files) => {
+                    let rev = revision
+                        .rev
+                        .map(|rev| format!("?rev={rev}"))
+                        .unwrap_or_default();
+                    let urls = files
+                        .into_iter()
+                        .map(|file| {
+                            let url = page
+                                .base_url
+                                .join(&format!("raw/{}/{}{rev}", key.id, file.name))?;
+                            Ok(format!("{url}\n"))
+                        })
+                        .collect::<Result<String, url::ParseError>>()?;
+
+                    Ok((
+                        StatusCode::MULTIPLE_CHOICES,
+                        [(
+                            header::CONTENT_TYPE,
+                            HeaderValue::from_static(mime::TEXT_PLAIN_UTF_8.as_ref()),
+                        )],
+                        urls,
+                    )
+                        .into_response())
+                }
+                None => Ok((
+                    [(
+                        header::CONTENT_TYPE,
+                        HeaderValue::from_static(mime::TEXT_PLAIN_UTF_8.as_ref()),
+                    )],
+                    Body::from_stream(ReaderStream::new(stream.reader)),
+                )
+                    .into_response()),
+            },
+            Err(db::Error::NoPassword) => Ok(PasswordInput {
+                page: page.clone(),
+                theme: theme.clone(),
+                lang,
+                id: key.id.to_string(),
+            }
+            .into_response()),
+            Err(err) => Err(err.into()),
+        }
+    }
+    .await
+    .map_err(|err| make_error(err, page, theme, lang))
+}
+
+/// GET handler for raw content of a single file of a multi-file paste.
+pub async fn get_file(
+    Path((id, name)): Path<(String, String)>,
+    Query(revision): Query<Revision>,
     State(db): State<Database>,
     State(page): State<Page>,
     theme: Option<Theme>,
@@ -22,8 +165,12 @@ pub async fn get(
         let password = password.map(|Password(password)| password);
         let key: Key = id.parse()?;
 
-        match db.get(key.id, password).await {
-            Ok(Entry::Regular(data) | Entry::Burned(data)) => Ok(data.text.into_response()),
+        match fetch(&db, &key, revision.rev, password).await {
+            Ok(data) => data
+                .files()
+                .and_then(|files| files.into_iter().find(|file| file.name == name))
+                .map(|file| file.text.into_response())
+                .ok_or_else(|| db::Error::NotFound.into()),
             Err(db::Error::NoPassword) => Ok(PasswordInput {
                 page: page.clone(),
                 theme: theme.clone(),
@@ -37,3 +184,104 @@ pub async fn get(
     .await
     .map_err(|err| make_error(err, page, theme, lang))
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::handlers::insert::api::{Entry, RedirectResponse};
+    use crate::test_helpers::{Client, StoreCookies};
+    use reqwest::StatusCode;
+    use reqwest::header::{ACCEPT_ENCODING, CONTENT_ENCODING};
+    use wastebin_core::files::File;
+
+    #[tokio::test]
+    async fn raw_files() -> Result<(), Box<dyn std::error::Error>> {
+        let client = Client::new(StoreCookies(false)).await;
+
+        let entry = Entry {
+            files: vec![
+                File {
+                    name: String::from("Cargo.toml"),
+                    text: String::from("[package]"),
+                },
+                File {
+                    name: String::from("main.rs"),
+                    text: String::from("fn main() {}"),
+                },
+            ],
+            ..Default::default()
+        };
+
+        let res = client.post_json().json(&entry).send().await?;
+        assert_eq!(res.status(), StatusCode::OK);
+        let payload = res.json::<RedirectResponse>().await?;
+
+        let res = client.get(&format!("/raw{}", payload.path)).send().await?;
+        assert_eq!(res.status(), StatusCode::MULTIPLE_CHOICES);
+        assert_eq!(
+            res.text().await?,
+            format!(
+                "https://localhost:8888/raw{0}/Cargo.toml\nhttps://localhost:8888/raw{0}/main.rs\n",
+                payload.path
+            )
+        );
+
+        let res = client
+            .get(&format!("/raw{}/main.rs", payload.path))
+            .send()
+            .await?;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.text().await?, "fn main() {}");
+
+        let res = client
+            .get(&format!("/raw{}/lib.rs", payload.path))
+            .send()
+            .await?;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+        let entry = Entry {
+            text: String::from("FooBarBaz"),
+            files: entry.files,
+            ..Default::default()
+        };
+        let res = client.post_json().json(&entry).send().await?;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn raw_zstd() -> Result<(), Box<dyn std::error::Error>> {
+        let client = Client::new(StoreCookies(false)).await;
+        let text = "FooBarBaz\n".repeat(1000);
+
+        let entry = Entry {
+            text: text.clone(),
+            ..Default::default()
+        };
+
+        let res = client.post_json().json(&entry).send().await?;
+        let payload = res.json::<RedirectResponse>().await?;
+
+        let res = client
+            .get(&format!("/raw{}", payload.path))
+            .header(ACCEPT_ENCODING, "gzip, zstd;q=0.5")
+            .send()
+            .await?;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers().get(CONTENT_ENCODING).unwrap(), "zstd");
+        assert_eq!(
+            zstd::decode_all(res.bytes().await?.as_ref())?,
+            text.as_bytes()
+        );
+
+        let res = client
+            .get(&format!("/raw{}", payload.path))
+            .header(ACCEPT_ENCODING, "zstd;q=0")
+            .send()
+            .await?;
+        assert!(res.headers().get(CONTENT_ENCODING).is_none());
+        assert_eq!(res.text().await?, text);
+
+        Ok(())
+    }
+}
@@ -44,6 +44,8 @@ static EN: phf::Map<&'static str, &'static str> = phf_map! {
     "nav.home" => "home",
     "nav.upload" => "upload",
     "nav.delete" => "delete paste",
+    "nav.edit" => "edit paste",
+    "nav.fork" => "fork paste",
     "nav.download" => "download file",
     "nav.raw" => "display raw file",
     "nav.copy" => "copy to clipboard",
@@ -58,14 +60,19 @@ static EN: phf::Map<&'static str, &'static str> = phf_map! {
     "index.placeholder.paste" => "paste, type, or drop a file here …",
     "index.drop" => "drop to load file",
     "index.label.title" => "title",
+    "index.label.slug" => "custom id",
+    "index.placeholder.slug" => "random",
     "index.placeholder.title" => "untitled",
+    "index.placeholder.filename" => "file name",
     "index.label.language" => "language",
     "index.aria.language" => "Language",
     "index.placeholder.filter" => "filter …",
     "index.label.expires" => "expires",
+    "index.expires.at" => "at date",
+    "index.aria.expires_at" => "Expiration date and time",
     "index.label.options" => "options",
-    "index.toggle.burn" => "burn after reading",
-    "index.toggle.burn.hint" => "delete on first view",
+    "index.label.max_views" => "max views",
+    "index.label.max_views.hint" => "delete after this many views",
     "index.toggle.encrypt" => "encrypt",
     "index.toggle.encrypt.hint" => "password-protect the paste",
     "index.placeholder.password" => "password",
@@ -74,8 +81,22 @@ static EN: phf::Map<&'static str, &'static str> = phf_map! {
     "index.stat.bytes" => "bytes",
     "index.button.paste" => "Paste",
     "index.button.paste.label" => "paste",
+    "index.button.add_file" => "add file",
+    "index.button.remove_file" => "remove file",
+    "edit.button.save" => "Save",
+    "edit.button.save.label" => "save",
 
     "paste.expires_in" => "expires in",
+    "paste.expiration.change" => "change expiration",
+    "paste.metadata.title" => "title",
+    "paste.metadata.extension" => "extension",
+    "paste.metadata.change" => "change title and extension",
+    "paste.revision" => "revision",
+    "paste.raw_file" => "raw",
+    "paste.forked_from" => "forked from",
+    "paste.created" => "created",
+    "paste.compressed" => "compressed",
+    "paste.accessed" => "last accessed",
     "paste.toast.copied_content" => "Copied content",
     "paste.toast.copied_url" => "Copied URL",
     "paste.toast.burned" => "Content is burned and cannot be looked up again!",
@@ -98,10 +119,16 @@ static EN: phf::Map<&'static str, &'static str> = phf_map! {
 
     "burn.title" => "Burn after reading",
     "burn.body" => "Copy and send <a class=\"text-link\" href=\"/{0}\">this link</a>. The recipient will be shown a confirmation prompt. The paste is deleted the moment they confirm.",
+    "burn.body.views" => "Copy and send <a class=\"text-link\" href=\"/{0}\">this link</a>. Recipients will be shown a confirmation prompt. The paste is deleted once its views are used up.",
 
     "burn_confirm.body" => "This paste will be <strong>permanently deleted</strong> the moment it is revealed. You will not be able to view it again.",
+    "burn_confirm.body.views" => "This paste will be <strong>permanently deleted</strong> after {0} more views, including this one.",
     "burn_confirm.cancel" => "cancel",
     "burn_confirm.reveal" => "reveal",
+    "restore.title" => "Deleted paste",
+    "restore.body" => "You deleted this paste. It can still be restored for a while before it is removed for good.",
+    "restore.cancel" => "cancel",
+    "restore.restore" => "restore",
 
     "encrypted.title" => "Encrypted paste",
     "encrypted.placeholder" => "password …",
@@ -118,6 +145,8 @@ static DE: phf::Map<&'static str, &'static str> = phf_map! {
     "nav.home" => "Start",
     "nav.upload" => "Hochladen",
     "nav.delete" => "Paste löschen",
+    "nav.edit" => "Paste bearbeiten",
+    "nav.fork" => "Paste forken",
     "nav.download" => "Datei herunterladen",
     "nav.raw" => "Rohansicht",
     "nav.copy" => "In Zwischenablage kopieren",
@@ -132,14 +161,19 @@ static DE: phf::Map<&'static str, &'static str> = phf_map! {
     "index.placeholder.paste" => "Text einfügen, tippen oder Datei hierher ziehen …",
     "index.drop" => "Datei hier ablegen",
     "index.label.title" => "Titel",
+    "index.label.slug" => "eigene ID",
+    "index.placeholder.slug" => "zufällig",
     "index.placeholder.title" => "ohne Titel",
+    "index.placeholder.filename" => "Dateiname",
     "index.label.language" => "Sprache",
     "index.aria.language" => "Sprache",
     "index.placeholder.filter" => "filtern …",
     "index.label.expires" => "Läuft ab",
+    "index.expires.at" => "zum Datum",
+    "index.aria.expires_at" => "Ablaufdatum und -uhrzeit",
     "index.label.options" => "Optionen",
-    "index.toggle.burn" => "Nach Lesen vernichten",
-    "index.toggle.burn.hint" => "Nach erstem Aufruf löschen",
+    "index.label.max_views" => "Maximale Aufrufe",
+    "index.label.max_views.hint" => "Nach so vielen Aufrufen löschen",
     "index.toggle.encrypt" => "Verschlüsseln",
     "index.toggle.encrypt.hint" => "Paste mit Passwort schützen",
     "index.placeholder.password" => "Passwort",
@@ -148,8 +182,22 @@ static DE: phf::Map<&'static str, &'static str> = phf_map! {
     "index.stat.bytes" => "Bytes",
     "index.button.paste" => "Einfügen",
     "index.button.paste.label" => "einfügen",
+    "index.button.add_file" => "Datei hinzufügen",
+    "index.button.remove_file" => "Datei entfernen",
+    "edit.button.save" => "Speichern",
+    "edit.button.save.label" => "speichern",
 
     "paste.expires_in" => "läuft ab in",
+    "paste.expiration.change" => "Ablauf ändern",
+    "paste.metadata.title" => "Titel",
+    "paste.metadata.extension" => "Endung",
+    "paste.metadata.change" => "Titel und Endung ändern",
+    "paste.revision" => "Revision",
+    "paste.raw_file" => "roh",
+    "paste.forked_from" => "geforkt von",
+    "paste.created" => "erstellt",
+    "paste.compressed" => "komprimiert",
+    "paste.accessed" => "zuletzt abgerufen",
     "paste.toast.copied_content" => "Inhalt kopiert",
     "paste.toast.copied_url" => "URL kopiert",
     "paste.toast.burned" => "Inhalt ist vernichtet und kann nicht mehr abgerufen werden!",
@@ -172,10 +220,16 @@ static DE: phf::Map<&'static str, &'static str> = phf_map! {
 
     "burn.title" => "Nach Lesen vernichten",
     "burn.body" => "Kopiere und schicke <a class=\"text-link\" href=\"/{0}\">diesen Link</a>. Dem Empfänger wird eine Bestätigungsaufforderung angezeigt und der Paste nach Bestätigung gelöscht.",
+    "burn.body.views" => "Kopiere und schicke <a class=\"text-link\" href=\"/{0}\">diesen Link</a>. Empfängern wird eine Bestätigungsaufforderung angezeigt und der Paste gelöscht, sobald alle Aufrufe verbraucht sind.",
 
     "burn_confirm.body" => "Dieser Paste wird <strong>unwiderruflich gelöscht</strong>, sobald er angezeigt wird und kann danach nicht mehr eingesehen werden.",
+    "burn_confirm.body.views" => "Dieser Paste wird nach {0} weiteren Aufrufen, diesen eingeschlossen, <strong>unwiderruflich gelöscht</strong>.",
     "burn_confirm.cancel" => "Abbrechen",
     "burn_confirm.reveal" => "Anzeigen",
+    "restore.title" => "Gelöschter Paste",
+    "restore.body" => "Du hast diesen Paste gelöscht. Er kann noch eine Weile wiederhergestellt werden, bevor er endgültig entfernt wird.",
+    "restore.cancel" => "Abbrechen",
+    "restore.restore" => "Wiederherstellen",
 
     "encrypted.title" => "Verschlüsselter Paste",
     "encrypted.placeholder" => "Passwort …",
@@ -192,6 +246,8 @@ static ZH: phf::Map<&'static str, &'static str> = phf_map! {
     "nav.home" => "主页",
     "nav.upload" => "上传",
     "nav.delete" => "删除剪贴",
+    "nav.edit" => "编辑剪贴",
+    "nav.fork" => "派生剪贴",
     "nav.download" => "下载文件",
     "nav.raw" => "显示原始内容",
     "nav.copy" => "复制到剪贴板",
@@ -206,14 +262,19 @@ static ZH: phf::Map<&'static str, &'static str> = phf_map! {
     "index.placeholder.paste" => "在此处粘贴、输入或拖放文件…",
     "index.drop" => "拖放以加载文件",
     "index.label.title" => "标题",
+    "index.label.slug" => "自定义 ID",
+    "index.placeholder.slug" => "随机",
     "index.placeholder.title" => "无标题",
+    "index.placeholder.filename" => "文件名",
     "index.label.language" => "语言",
     "index.aria.language" => "语言",
     "index.placeholder.filter" => "过滤…",
     "index.label.expires" => "过期时间",
+    "index.expires.at" => "指定时间",
+    "index.aria.expires_at" => "过期日期和时间",
     "index.label.options" => "选项",
-    "index.toggle.burn" => "阅后即焚",
-    "index.toggle.burn.hint" => "首次查看后删除",
+    "index.label.max_views" => "最大查看次数",
+    "index.label.max_views.hint" => "达到查看次数后删除",
     "index.toggle.encrypt" => "加密",
     "index.toggle.encrypt.hint" => "使用密码保护剪贴",
     "index.placeholder.password" => "密码",
@@ -222,8 +283,22 @@ static ZH: phf::Map<&'static str, &'static str> = phf_map! {
     "index.stat.bytes" => "字节",
     "index.button.paste" => "粘贴",
     "index.button.paste.label" => "粘贴",
+    "index.button.add_file" => "添加文件",
+    "index.button.remove_file" => "移除文件",
+    "edit.button.save" => "保存",
+    "edit.button.save.label" => "保存",
 
     "paste.expires_in" => "过期于",
+    "paste.expiration.change" => "更改过期时间",
+    "paste.metadata.title" => "标题",
+    "paste.metadata.extension" => "扩展名",
+    "paste.metadata.change" => "更改标题和扩展名",
+    "paste.revision" => "版本",
+    "paste.raw_file" => "原始",
+    "paste.forked_from" => "派生自",
+    "paste.created" => "创建于",
+    "paste.compressed" => "压缩后",
+    "paste.accessed" => "上次访问",
     "paste.toast.copied_content" => "已复制内容",
     "paste.toast.copied_url" => "已复制链接",
     "paste.toast.burned" => "内容已销毁，无法再次查看！",
@@ -246,10 +321,16 @@ static ZH: phf::Map<&'static str, &'static str> = phf_map! {
 
     "burn.title" => "阅后即焚",
     "burn.body" => "复制并发送 <a class=\"text-link\" href=\"/{0}\">此链接</a>。收件人将看到确认提示。在他们确认的那一刻，剪贴将被删除。",
+    "burn.body.views" => "复制并发送 <a class=\"text-link\" href=\"/{0}\">此链接</a>。收件人将看到确认提示。查看次数用完后，剪贴将被删除。",
 
     "burn_confirm.body" => "此剪贴在显示的那一刻将被 <strong>永久删除</strong>。您将无法再次查看它。",
+    "burn_confirm.body.views" => "此剪贴将在包括本次在内的 {0} 次查看后被 <strong>永久删除</strong>。",
     "burn_confirm.cancel" => "取消",
     "burn_confirm.reveal" => "显示",
+    "restore.title" => "已删除的剪贴",
+    "restore.body" => "您已删除此剪贴。在被彻底移除之前，它仍可在一段时间内恢复。",
+    "restore.cancel" => "取消",
+    "restore.restore" => "恢复",
 
     "encrypted.title" => "加密的剪贴",
     "encrypted.placeholder" => "密码…",
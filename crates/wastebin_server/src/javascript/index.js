@@ -36,7 +36,10 @@ function formatSize(bytes) {
 $("progress-limit").textContent = LABEL_LIMIT + " " + formatSize(MAX_BYTES);
 
 function updateStats() {
-  const text = textarea.value;
+  let text = textarea.value;
+  for (const pane of document.querySelectorAll("#files textarea")) {
+    text += "\n" + pane.value;
+  }
   const lines = Math.max(1, text.split("\n").length);
   const chars = text.length;
   let bytes;
@@ -60,6 +63,31 @@ function updateStats() {
 textarea.addEventListener("input", updateStats);
 updateStats();
 
+const files = $("files");
+const fileName = $("filename");
+
+function updateFileName() {
+  fileName.hidden = files.children.length === 0;
+}
+
+$("add-file").addEventListener("click", function() {
+  const pane = $("file-template").content.firstElementChild.cloneNode(true);
+  files.appendChild(pane);
+  updateFileName();
+  pane.querySelector("input").focus();
+});
+
+files.addEventListener("click", function(e) {
+  const button = e.target.closest(".remove-file");
+  if (button) {
+    button.closest(".file-pane").remove();
+    updateFileName();
+    updateStats();
+  }
+});
+
+files.addEventListener("input", updateStats);
+
 const langSelect = $("langs");
 const langFilter = $("filter");
 
@@ -87,14 +115,25 @@ if (encryptToggle && passwordGroup) {
   });
 }
 
-$("burn-after-reading").addEventListener("change", function() {
-  const disabled = this.checked;
-  const radios = document.querySelectorAll('#expiry-list input[type="radio"]');
-  for (const radio of radios) {
-    radio.disabled = disabled;
+const expiresAtOption = $("expires-at-option");
+const expiresAtPicker = $("expires-at-picker");
+const expiresAt = $("expires-at");
+
+function updateExpiresAt() {
+  const date = new Date(expiresAtPicker.value);
+  expiresAt.value = expiresAtOption.checked && !isNaN(date) ? date.toISOString() : "";
+}
+
+$("expiry-list").addEventListener("change", function() {
+  expiresAtPicker.classList.toggle("shown", expiresAtOption.checked);
+  if (expiresAtOption.checked) {
+    expiresAtPicker.focus();
   }
+  updateExpiresAt();
 });
 
+expiresAtPicker.addEventListener("input", updateExpiresAt);
+
 const overlay = $("drop-overlay");
 let dragCounter = 0;
 
@@ -5,6 +5,7 @@ mod errors;
 mod handlers;
 mod i18n;
 mod page;
+mod tasks;
 #[cfg(test)]
 mod test_helpers;
 
@@ -16,7 +17,7 @@ use axum::extract::{DefaultBodyLimit, FromRef, Request, State};
 use axum::http::{HeaderName, HeaderValue, StatusCode};
 use axum::middleware::{Next, from_fn, from_fn_with_state};
 use axum::response::{IntoResponse, Response};
-use axum::routing::{Router, get, post};
+use axum::routing::{Router, get, post, put};
 use axum_extra::extract::cookie::Key;
 use futures::future::TryFutureExt;
 use http::header::{
@@ -32,9 +33,11 @@ use tower_http::trace::TraceLayer;
 use crate::cache::Cache;
 use crate::errors::Error;
 use crate::handlers::extract::Theme;
-use crate::handlers::{delete, download, html, insert, raw, robots, theme};
+use crate::handlers::{
+    delete, download, edit, expiration, html, insert, metadata, raw, robots, theme,
+};
 use crate::i18n::Lang;
-use wastebin_core::db::Database;
+use wastebin_core::db::{Access, Config, Database};
 
 /// Reference counted [`page::Page`] wrapper.
 pub(crate) type Page = Arc<page::Page>;
@@ -210,7 +213,7 @@ fn make_app(state: AppState, timeout: Duration, max_body_size: usize) -> Router
             state.page.assets.password_toggle_js.route(),
             get(async |State(page): State<Page>| page.assets.password_toggle_js.clone()),
         )
-        .route("/", get(html::index::get).post(insert::api::post))
+        .route("/", get(html::index::get).post(insert::post))
         .route("/robots.txt", get(robots::get))
         .route("/theme", get(theme::get))
         .route("/new", post(insert::form::post))
@@ -224,11 +227,26 @@ fn make_app(state: AppState, timeout: Duration, max_body_size: usize) -> Router
             "/{id}",
             get(html::paste::get)
                 .post(html::paste::get)
+                .put(edit::api::put)
                 .delete(delete::api::delete),
         )
         .route("/dl/{id}", get(download::get))
         .route("/raw/{id}", get(raw::get))
+        .route("/raw/{id}/{name}", get(raw::get_file))
+        .route(
+            "/meta/{id}",
+            get(metadata::get)
+                .put(metadata::put)
+                .post(metadata::form::post),
+        )
         .route("/delete/{id}", post(delete::form::delete))
+        .route("/edit/{id}", get(html::edit::get).post(html::edit::get))
+        .route("/update/{id}", post(edit::form::post))
+        .route("/fork/{id}", get(html::fork::get).post(html::fork::get))
+        .route(
+            "/expiration/{id}",
+            put(expiration::api::put).post(expiration::form::post),
+        )
         .layer(
             ServiceBuilder::new()
                 .layer(DefaultBodyLimit::max(max_body_size))
@@ -248,23 +266,67 @@ async fn start() -> Result<(), Box<dyn std::error::Error>> {
     tracing_subscriber::fmt::init();
 
     let cache_size = env::cache_size()?;
+    let readers = env::reader_pool_size()?;
+    let max_storage = env::max_storage()?;
+    let compression_level = env::compression_level()?;
+    let backend = env::storage_backend()?;
     let method = env::database_method()?;
     let key = env::signing_key()?;
     let socket_type = env::socket_type()?;
     let max_body_size = env::max_body_size()?;
+    let max_stream_size = env::max_stream_size(max_body_size)?;
     let base_url = env::base_url()?;
     let timeout = env::http_timeout()?;
     let expirations = env::expiration_set()?;
     let theme = env::theme()?;
     let title = env::title();
+    let purge_interval = env::purge_interval()?;
+    let purge_vacuum = env::purge_vacuum()?;
+    let trash_period = env::trash_period()?;
+    let backup = env::backup()?;
+    let slugs = env::slugs()?;
+    let generator = env::id_generator()?;
 
     let cache = Cache::new(cache_size)?;
-    let (db, db_handler) = Database::new(method)?;
+    let (db, db_handler) = Database::open(
+        backend,
+        method,
+        Config {
+            readers,
+            max_storage,
+            compression_level,
+            generator,
+            trash_period,
+            actor: "wastebin",
+            access: Access::Migrate,
+        },
+    )?;
 
     tracing::debug!("serving on {socket_type}");
+    tracing::debug!("storing pastes with {backend:?} backend");
     tracing::debug!("caching {cache_size} paste highlights");
+    tracing::debug!("generating paste identifiers with {generator:?}");
+    tracing::debug!("reading from up to {readers} database connections");
+    if let Some(max_storage) = max_storage {
+        tracing::debug!("restricting stored paste data to {max_storage} bytes");
+    }
+    if let Some(compression_level) = compression_level {
+        tracing::debug!("compressing new pastes at zstd level {compression_level}");
+    }
+    if let Some(trash_period) = trash_period {
+        tracing::debug!("keeping deleted pastes restorable for {trash_period:#?}");
+    }
     tracing::debug!("restricting maximum body size to {max_body_size} bytes");
+    tracing::debug!("restricting maximum streamed body size to {max_stream_size} bytes");
     tracing::debug!("enforcing a http timeout of {timeout:#?}");
+    match &slugs {
+        Some(slugs) => tracing::debug!(
+            "allowing custom identifiers of {} to {} characters",
+            slugs.min_length,
+            slugs.max_length
+        ),
+        None => tracing::debug!("custom identifiers are disabled"),
+    }
 
     let page = Arc::new(page::Page::new(
         title,
@@ -272,8 +334,38 @@ async fn start() -> Result<(), Box<dyn std::error::Error>> {
         theme,
         expirations,
         max_body_size,
+        max_stream_size,
+        slugs,
     ));
     let highlighter = Arc::new(wastebin_highlight::Highlighter::default());
+
+    let mut tasks = vec![tokio::spawn(tasks::flush_views(
+        db.clone(),
+        tasks::VIEWS_INTERVAL,
+    ))];
+
+    if let Some(interval) = purge_interval {
+        tracing::debug!("purging expired pastes every {interval:#?}");
+        tasks.push(tokio::spawn(tasks::purge(
+            db.clone(),
+            cache.clone(),
+            interval,
+            purge_vacuum,
+        )));
+    }
+
+    if let Some(backup) = backup {
+        tracing::debug!(
+            "backing up to {} every {:#?}",
+            backup.path.display(),
+            backup.interval
+        );
+        tasks.push(tokio::spawn(tasks::backup(db.clone(), backup)));
+    }
+
+    // Kept to write the views counted since the last flush on shutdown.
+    let views = db.clone();
+
     let state = AppState {
         db,
         cache,
@@ -300,6 +392,17 @@ async fn start() -> Result<(), Box<dyn std::error::Error>> {
             }
         }
 
+        // Release the database so that its handler can finish.
+        for task in &tasks {
+            task.abort();
+        }
+
+        if let Err(err) = views.flush_views().await {
+            tracing::error!("failed to write views: {err}");
+        }
+
+        drop(views);
+
         Ok::<(), Box<dyn std::error::Error>>(())
     };
 
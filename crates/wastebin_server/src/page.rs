@@ -2,6 +2,7 @@ use url::Url;
 
 use crate::assets::{Asset, Css, Kind};
 use wastebin_core::expiration::{Expiration, ExpirationSet};
+use wastebin_core::id::MAX_SLUG_LENGTH;
 use wastebin_highlight::Theme;
 
 /// Static page assets.
@@ -14,6 +15,16 @@ pub(crate) struct Assets {
     pub password_toggle_js: Asset,
 }
 
+/// Rules for custom identifiers requested by paste creators.
+pub(crate) struct Slugs {
+    /// Minimum length in bytes
+    pub min_length: usize,
+    /// Maximum length in bytes
+    pub max_length: usize,
+    /// Allowed characters, all characters valid in slugs if `None`
+    pub chars: Option<String>,
+}
+
 pub(crate) struct Page {
     pub version: &'static str,
     pub title: String,
@@ -21,6 +32,20 @@ pub(crate) struct Page {
     pub base_url: Url,
     pub expirations: Vec<Expiration>,
     pub max_body_size: usize,
+    /// Maximum size of unencrypted content streamed into the database
+    pub max_stream_size: usize,
+    /// Rules for custom identifiers, `None` if they are disabled
+    pub slugs: Option<Slugs>,
+}
+
+impl Default for Slugs {
+    fn default() -> Self {
+        Self {
+            min_length: 1,
+            max_length: MAX_SLUG_LENGTH,
+            chars: None,
+        }
+    }
 }
 
 impl Page {
@@ -32,6 +57,8 @@ impl Page {
         theme: Theme,
         expirations: ExpirationSet,
         max_body_size: usize,
+        max_stream_size: usize,
+        slugs: Option<Slugs>,
     ) -> Self {
         let assets = Assets::new(theme);
         let expirations = expirations.into_inner();
@@ -43,8 +70,18 @@ impl Page {
             base_url,
             expirations,
             max_body_size,
+            max_stream_size,
+            slugs,
         }
     }
+
+    /// Longest expiration a paste can be given or `None` if pastes may never expire.
+    #[must_use]
+    pub fn max_expiration(&self) -> Option<&Expiration> {
+        self.expirations
+            .last()
+            .filter(|_| !self.expirations.iter().any(|exp| exp.duration.is_zero()))
+    }
 }
 
 impl Assets {
@@ -0,0 +1,169 @@
+use std::cmp::Reverse;
+use std::num::NonZeroUsize;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use tokio::time::MissedTickBehavior;
+
+use crate::cache::Cache;
+use crate::env;
+use wastebin_core::db::Database;
+
+/// File name prefix of scheduled backups, followed by the Unix timestamp of the backup.
+const BACKUP_PREFIX: &str = "wastebin-";
+
+/// File name suffix of scheduled backups.
+const BACKUP_SUFFIX: &str = ".db";
+
+/// Interval at which views counted in memory are written to the database.
+pub(crate) const VIEWS_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Purge expired pastes and deleted ones whose trash period ended every `interval` and reclaim
+/// their disk space if `vacuum` is set.
+pub(crate) async fn purge(db: Database, cache: Cache, interval: Duration, vacuum: bool) {
+    let mut interval = tokio::time::interval(interval);
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    loop {
+        interval.tick().await;
+
+        let ids = match db.purge().await {
+            Ok(ids) => ids,
+            Err(err) => {
+                tracing::error!("failed to purge expired pastes: {err}");
+                continue;
+            }
+        };
+
+        if ids.is_empty() {
+            continue;
+        }
+
+        for id in &ids {
+            tracing::info!("purged expired or deleted paste {id}");
+            cache.remove(*id);
+        }
+
+        if vacuum && let Err(err) = db.vacuum().await {
+            tracing::error!("failed to vacuum database: {err}");
+        }
+    }
+}
+
+/// Write the views counted in memory to the database every `interval`.
+pub(crate) async fn flush_views(db: Database, interval: Duration) {
+    let mut interval = tokio::time::interval(interval);
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    loop {
+        interval.tick().await;
+
+        if let Err(err) = db.flush_views().await {
+            tracing::error!("failed to write views: {err}");
+        }
+    }
+}
+
+/// Back up the database every `interval` and keep the latest `retention` backups in `path`.
+pub(crate) async fn backup(
+    db: Database,
+    env::Backup {
+        path,
+        interval,
+        retention,
+    }: env::Backup,
+) {
+    let mut interval = tokio::time::interval(interval);
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    loop {
+        interval.tick().await;
+
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| since.as_secs());
+
+        let file = path.join(format!("{BACKUP_PREFIX}{secs}{BACKUP_SUFFIX}"));
+
+        // Write to a temporary file first so that incomplete backups are never rotated in.
+        let tmp = file.with_extension("db.tmp");
+
+        if let Err(err) = db.backup(tmp.clone()).await {
+            tracing::error!("failed to back up database: {err}");
+            let _ = tokio::fs::remove_file(&tmp).await;
+            continue;
+        }
+
+        if let Err(err) = tokio::fs::rename(&tmp, &file).await {
+            tracing::error!("failed to move backup to {}: {err}", file.display());
+            continue;
+        }
+
+        tracing::info!("backed up database to {}", file.display());
+
+        if let Err(err) = remove_old_backups(&path, retention).await {
+            tracing::error!("failed to remove old backups: {err}");
+        }
+    }
+}
+
+/// Remove all but the latest `retention` backups in `path`.
+async fn remove_old_backups(path: &Path, retention: NonZeroUsize) -> std::io::Result<()> {
+    let mut backups = Vec::new();
+    let mut entries = tokio::fs::read_dir(path).await?;
+
+    while let Some(entry) = entries.next_entry().await? {
+        let secs = entry.file_name().to_str().and_then(|name| {
+            name.strip_prefix(BACKUP_PREFIX)?
+                .strip_suffix(BACKUP_SUFFIX)?
+                .parse::<u64>()
+                .ok()
+        });
+
+        if let Some(secs) = secs {
+            backups.push((secs, entry.path()));
+        }
+    }
+
+    backups.sort_unstable_by_key(|(secs, _)| Reverse(*secs));
+
+    for (_, backup) in backups.into_iter().skip(retention.get()) {
+        tokio::fs::remove_file(&backup).await?;
+        tracing::info!("removed old backup {}", backup.display());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn remove_old_backups() -> Result<(), Box<dyn std::error::Error>> {
+        let path = std::env::temp_dir().join(format!("wastebin-backups-{}", std::process::id()));
+        tokio::fs::create_dir_all(&path).await?;
+
+        for name in [
+            "wastebin-9.db",
+            "wastebin-10.db",
+            "wastebin-11.db",
+            "other.db",
+        ] {
+            tokio::fs::write(path.join(name), b"").await?;
+        }
+
+        super::remove_old_backups(&path, NonZeroUsize::new(2).unwrap()).await?;
+
+        let mut names = std::fs::read_dir(&path)?
+            .map(|entry| Ok(entry?.file_name().into_string().unwrap_or_default()))
+            .collect::<std::io::Result<Vec<_>>>()?;
+        names.sort();
+
+        assert_eq!(names, ["other.db", "wastebin-10.db", "wastebin-11.db"]);
+
+        tokio::fs::remove_dir_all(&path).await?;
+
+        Ok(())
+    }
+}
@@ -24,7 +24,13 @@ pub(crate) struct StoreCookies(pub bool);
 
 impl Client {
     pub(crate) async fn new(store_cookies: StoreCookies) -> Self {
-        let (db, handler) = Database::new(db::Open::Memory).expect("open memory database");
+        Self::with_config(store_cookies, db::Config::default()).await
+    }
+
+    /// Start a server on top of an in-memory SQLite database opened with `config`.
+    pub(crate) async fn with_config(store_cookies: StoreCookies, config: db::Config) -> Self {
+        let (db, handler) = Database::open(db::Backend::Sqlite, db::Open::Memory, config)
+            .expect("open memory database");
         let cache = Cache::new(NonZeroUsize::new(128).unwrap()).unwrap();
         let key = Key::generate();
         let expirations = "0".parse::<ExpirationSet>().unwrap();
@@ -34,6 +40,8 @@ impl Client {
             Theme::Ayu,
             expirations,
             1024 * 1024,
+            4 * 1024 * 1024,
+            Some(page::Slugs::default()),
         ));
         let state = crate::AppState {
             db,
@@ -85,6 +93,10 @@ impl Client {
         self.client.post(format!("http://{}/", self.addr))
     }
 
+    pub(crate) fn put(&self, url: &str) -> RequestBuilder {
+        self.client.put(format!("http://{}{}", self.addr, url))
+    }
+
     pub(crate) fn delete(&self, url: &str) -> RequestBuilder {
         self.client.delete(format!("http://{}{}", self.addr, url))
     }